@@ -21,6 +21,20 @@ pub struct AppListConfig {
     pub filter_top_levels: Option<TopLevelFilter>,
     pub favorites: Vec<String>,
     pub enable_drag_source: bool,
+    /// Show running applications that are not pinned.
+    pub show_running_unpinned: bool,
+    /// Dragging a pinned app off the app tray unpins it. When disabled, a
+    /// remove zone is shown while dragging and apps are only unpinned when
+    /// dropped on it.
+    pub drag_off_unpins: bool,
+    /// Ask for confirmation before unpinning an app that was dragged away.
+    pub confirm_unpin: bool,
+    /// Show a dot per open window below running apps, up to three, instead
+    /// of a single dot that widens into a bar for several windows.
+    pub window_count_dots: bool,
+    /// Show the windows of an app under a single icon that lists them when
+    /// clicked. When disabled, each window gets its own icon.
+    pub group_windows: bool,
 }
 
 impl Default for AppListConfig {
@@ -29,6 +43,11 @@ impl Default for AppListConfig {
             filter_top_levels: None,
             favorites: Vec::new(),
             enable_drag_source: true,
+            show_running_unpinned: true,
+            drag_off_unpins: true,
+            confirm_unpin: false,
+            window_count_dots: false,
+            group_windows: true,
         }
     }
 }
@@ -5,4 +5,7 @@ quit-all = Quit All
 new-window = New Window
 run = Run
 run-on = Run on {$gpu}
-run-on-default = (Default)
\ No newline at end of file
+run-on-default = (Default)
+unpin = Unpin
+unpin-confirm = Unpin {$name} from the app tray?
+cancel = Cancel
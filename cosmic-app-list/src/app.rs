@@ -53,12 +53,22 @@ use futures::future::pending;
 use iced::{widget::container, Alignment, Background, Length};
 use itertools::Itertools;
 use rand::{thread_rng, Rng};
-use std::{borrow::Cow, collections::HashMap, path::PathBuf, rc::Rc, str::FromStr, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+    time::Duration,
+};
 use switcheroo_control::Gpu;
 use tokio::time::sleep;
 use url::Url;
 
 static MIME_TYPE: &str = "text/uri-list";
+/// Most dots shown for the windows of an app with `window_count_dots`.
+const MAX_WINDOW_DOTS: usize = 3;
+const DOT_SPACING: f32 = 2.0;
 
 pub fn run() -> cosmic::iced::Result {
     cosmic::applet::run::<CosmicAppList>(())
@@ -143,8 +153,10 @@ impl DockItem {
         interaction_enabled: bool,
         dnd_source_enabled: bool,
         gpus: Option<&[Gpu]>,
-        is_focused: bool,
+        focused_item: &[ZcosmicToplevelHandleV1],
         dot_border_radius: [f32; 4],
+        window_count_dots: bool,
+        group_windows: bool,
         window_id: window::Id,
     ) -> Element<'_, Message> {
         let Self {
@@ -160,109 +172,69 @@ impl DockItem {
             .as_cosmic_icon()
             .size(app_icon.icon_size);
 
-        let dots = if toplevels.is_empty() {
-            (0..1)
-                .map(|_| {
-                    container(vertical_space().height(Length::Fixed(0.0)))
-                        .padding(app_icon.dot_radius)
-                        .into()
-                })
-                .collect_vec()
-        } else {
-            (0..1)
-                .map(|_| {
-                    container(if toplevels.len() == 1 {
-                        vertical_space().height(Length::Fixed(0.0))
-                    } else {
-                        match applet.anchor {
-                            PanelAnchor::Left | PanelAnchor::Right => {
-                                vertical_space().height(app_icon.bar_size)
-                            }
-                            PanelAnchor::Top | PanelAnchor::Bottom => {
-                                horizontal_space().width(app_icon.bar_size)
-                            }
-                        }
-                    })
-                    .padding(app_icon.dot_radius)
-                    .class(theme::style::Container::Custom(Box::new(move |theme| {
-                        container::Style {
-                            text_color: Some(Color::TRANSPARENT),
-                            background: if is_focused {
-                                Some(Background::Color(theme.cosmic().accent_color().into()))
-                            } else {
-                                Some(Background::Color(theme.cosmic().on_bg_color().into()))
-                            },
-                            border: Border {
-                                radius: dot_border_radius.into(),
-                                width: 0.0,
-                                color: Color::TRANSPARENT,
-                            },
-                            shadow: Shadow::default(),
-                            icon_color: Some(Color::TRANSPARENT),
-                        }
-                    })))
+        let icon_button: Element<_> = if !group_windows && toplevels.len() > 1 {
+            // one button per window, sharing the app's menu and drag source
+            let buttons = toplevels
+                .iter()
+                .map(|(handle, _, _)| {
+                    window_icon_button(
+                        applet,
+                        &app_icon,
+                        cosmic_icon.clone(),
+                        1,
+                        focused_item.contains(handle),
+                        dot_border_radius,
+                        window_count_dots,
+                    )
+                    .on_press_maybe(interaction_enabled.then(|| Message::Toggle(handle.clone())))
                     .into()
                 })
-                .collect_vec()
-        };
-
-        let icon_wrapper: Element<_> = match applet.anchor {
-            PanelAnchor::Left => row(vec![
-                column(dots).into(),
-                horizontal_space().width(Length::Fixed(1.0)).into(),
-                cosmic_icon.clone().into(),
-            ])
-            .align_y(Alignment::Center)
-            .into(),
-            PanelAnchor::Right => row(vec![
-                cosmic_icon.clone().into(),
-                horizontal_space().width(Length::Fixed(1.0)).into(),
-                column(dots).into(),
-            ])
-            .align_y(Alignment::Center)
-            .into(),
-            PanelAnchor::Top => column(vec![
-                row(dots).into(),
-                vertical_space().height(Length::Fixed(1.0)).into(),
-                cosmic_icon.clone().into(),
-            ])
-            .align_x(Alignment::Center)
-            .into(),
-            PanelAnchor::Bottom => column(vec![
-                cosmic_icon.clone().into(),
-                vertical_space().height(Length::Fixed(1.0)).into(),
-                row(dots).into(),
-            ])
-            .align_x(Alignment::Center)
-            .into(),
-        };
-
-        let icon_button = button::custom(icon_wrapper)
-            .padding(app_icon.padding)
-            .selected(is_focused)
-            .class(app_list_icon_style(is_focused));
-
-        let icon_button: Element<_> = if interaction_enabled {
-            mouse_area(
-                icon_button
-                    .on_press_maybe(if toplevels.is_empty() {
-                        launch_on_preferred_gpu(desktop_info, gpus)
-                    } else if toplevels.len() == 1 {
-                        toplevels.first().map(|t| Message::Toggle(t.0.clone()))
-                    } else {
-                        Some(Message::TopLevelListPopup((*id).into(), window_id))
-                    })
-                    .width(Length::Shrink)
-                    .height(Length::Shrink),
+                .collect_vec();
+            match applet.anchor {
+                PanelAnchor::Left | PanelAnchor::Right => {
+                    column(buttons).spacing(app_icon.icon_spacing).into()
+                }
+                PanelAnchor::Top | PanelAnchor::Bottom => {
+                    row(buttons).spacing(app_icon.icon_spacing).into()
+                }
+            }
+        } else {
+            let is_focused = toplevels
+                .iter()
+                .any(|(handle, _, _)| focused_item.contains(handle));
+            window_icon_button(
+                applet,
+                &app_icon,
+                cosmic_icon.clone(),
+                toplevels.len(),
+                is_focused,
+                dot_border_radius,
+                window_count_dots,
             )
-            .on_right_release(Message::Popup((*id).into(), window_id))
-            .on_middle_release({
+            .on_press_maybe(if !interaction_enabled {
+                None
+            } else if toplevels.is_empty() {
                 launch_on_preferred_gpu(desktop_info, gpus)
-                    .unwrap_or_else(|| Message::Popup((*id).into(), window_id))
+            } else if toplevels.len() == 1 {
+                toplevels.first().map(|t| Message::Toggle(t.0.clone()))
+            } else {
+                Some(Message::TopLevelListPopup((*id).into(), window_id))
             })
+            .width(Length::Shrink)
+            .height(Length::Shrink)
             .into()
+        };
+
+        let icon_button: Element<_> = if interaction_enabled {
+            mouse_area(icon_button)
+                .on_right_release(Message::Popup((*id).into(), window_id))
+                .on_middle_release({
+                    launch_on_preferred_gpu(desktop_info, gpus)
+                        .unwrap_or_else(|| Message::Popup((*id).into(), window_id))
+                })
+                .into()
         } else {
-            icon_button.into()
+            icon_button
         };
 
         let path = desktop_info.path.to_path_buf();
@@ -292,10 +264,120 @@ impl DockItem {
     }
 }
 
+/// The icon of an app with a dot below it for its `windows`.
+fn window_icon_button<'a>(
+    applet: &Context,
+    app_icon: &AppletIconData,
+    cosmic_icon: icon::Icon,
+    windows: usize,
+    is_focused: bool,
+    dot_border_radius: [f32; 4],
+    window_count_dots: bool,
+) -> button::Button<'a, Message> {
+    let dots = if windows == 0 {
+        (0..1)
+            .map(|_| {
+                container(vertical_space().height(Length::Fixed(0.0)))
+                    .padding(app_icon.dot_radius)
+                    .into()
+            })
+            .collect_vec()
+    } else {
+        let count = if window_count_dots {
+            windows.min(MAX_WINDOW_DOTS)
+        } else {
+            1
+        };
+        (0..count)
+            .map(|_| {
+                container(if windows == 1 || window_count_dots {
+                    vertical_space().height(Length::Fixed(0.0))
+                } else {
+                    match applet.anchor {
+                        PanelAnchor::Left | PanelAnchor::Right => {
+                            vertical_space().height(app_icon.bar_size)
+                        }
+                        PanelAnchor::Top | PanelAnchor::Bottom => {
+                            horizontal_space().width(app_icon.bar_size)
+                        }
+                    }
+                })
+                .padding(app_icon.dot_radius)
+                .class(theme::style::Container::Custom(Box::new(move |theme| {
+                    container::Style {
+                        text_color: Some(Color::TRANSPARENT),
+                        background: if is_focused {
+                            Some(Background::Color(theme.cosmic().accent_color().into()))
+                        } else {
+                            Some(Background::Color(theme.cosmic().on_bg_color().into()))
+                        },
+                        border: Border {
+                            radius: dot_border_radius.into(),
+                            width: 0.0,
+                            color: Color::TRANSPARENT,
+                        },
+                        shadow: Shadow::default(),
+                        icon_color: Some(Color::TRANSPARENT),
+                    }
+                })))
+                .into()
+            })
+            .collect_vec()
+    };
+
+    let icon_wrapper: Element<_> = match applet.anchor {
+        PanelAnchor::Left => row(vec![
+            column(dots).spacing(DOT_SPACING).into(),
+            horizontal_space().width(Length::Fixed(1.0)).into(),
+            cosmic_icon.into(),
+        ])
+        .align_y(Alignment::Center)
+        .into(),
+        PanelAnchor::Right => row(vec![
+            cosmic_icon.into(),
+            horizontal_space().width(Length::Fixed(1.0)).into(),
+            column(dots).spacing(DOT_SPACING).into(),
+        ])
+        .align_y(Alignment::Center)
+        .into(),
+        PanelAnchor::Top => column(vec![
+            row(dots).spacing(DOT_SPACING).into(),
+            vertical_space().height(Length::Fixed(1.0)).into(),
+            cosmic_icon.into(),
+        ])
+        .align_x(Alignment::Center)
+        .into(),
+        PanelAnchor::Bottom => column(vec![
+            cosmic_icon.into(),
+            vertical_space().height(Length::Fixed(1.0)).into(),
+            row(dots).spacing(DOT_SPACING).into(),
+        ])
+        .align_x(Alignment::Center)
+        .into(),
+    };
+
+    button::custom(icon_wrapper)
+        .padding(app_icon.padding)
+        .selected(is_focused)
+        .class(app_list_icon_style(is_focused))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum DndList {
+    #[default]
+    Pinned,
+    Active,
+    Remove,
+}
+
 #[derive(Debug, Clone, Default)]
 struct DndOffer {
+    list: DndList,
     dock_item: Option<DockItem>,
     preview_index: usize,
+    // a dragged file which isn't a desktop entry is opened with the hovered item instead
+    file: Option<PathBuf>,
+    position: iced::Point,
 }
 
 #[derive(Debug, Clone)]
@@ -315,6 +397,9 @@ struct CosmicAppList {
     active_list: Vec<DockItem>,
     pinned_list: Vec<DockItem>,
     dnd_source: Option<(window::Id, DockItem, DndAction)>,
+    /// Position in the pinned list of the item being dragged, if it was pinned.
+    dragged_pin_index: Option<usize>,
+    dropped_on_remove: bool,
     config: AppListConfig,
     wayland_sender: Option<Sender<WaylandRequest>>,
     seat: Option<WlSeat>,
@@ -334,6 +419,7 @@ struct CosmicAppList {
 pub enum PopupType {
     RightClickMenu,
     TopLevelList,
+    ConfirmUnpin,
 }
 
 #[derive(Debug, Clone)]
@@ -341,6 +427,7 @@ enum Message {
     Wayland(WaylandUpdate),
     PinApp(u32),
     UnpinApp(u32),
+    ConfirmUnpin(u32),
     Popup(u32, window::Id),
     Pressed(window::Id),
     TopLevelListPopup(u32, window::Id),
@@ -356,11 +443,12 @@ enum Message {
     Rectangle(RectangleUpdate<DockItemId>),
     StartDrag(u32),
     DragFinished,
-    DndEnter(f64, f64),
+    DndEnter(DndList, f64, f64),
     DndExit,
     DndMotion(f64, f64),
     DndDropFinished,
     DndData(Option<DndPathBuf>),
+    OpenFile(u32, PathBuf),
     StartListeningForDnd,
     StopListeningForDnd,
     IncrementSubscriptionCtr,
@@ -699,6 +787,48 @@ impl cosmic::Application for CosmicAppList {
                     return Task::batch([gpu_update, get_popup(popup_settings)]);
                 }
             }
+            Message::ConfirmUnpin(id) => {
+                if let Some(Popup { id: popup_id, .. }) = self.popup.take() {
+                    return destroy_popup(popup_id);
+                }
+                let Some(parent_window_id) = self.core.main_window_id() else {
+                    return Task::none();
+                };
+                if let Some(dock_item) = self.pinned_list.iter().find(|t| t.id == id) {
+                    let Some(rectangle) = self.rectangles.get(&dock_item.id.into()) else {
+                        return Task::none();
+                    };
+
+                    let new_id = window::Id::unique();
+                    self.popup = Some(Popup {
+                        parent: parent_window_id,
+                        id: new_id,
+                        dock_item: dock_item.clone(),
+                        popup_type: PopupType::ConfirmUnpin,
+                    });
+
+                    let mut popup_settings = self.core.applet.get_popup_settings(
+                        parent_window_id,
+                        new_id,
+                        None,
+                        None,
+                        None,
+                    );
+                    let iced::Rectangle {
+                        x,
+                        y,
+                        width,
+                        height,
+                    } = *rectangle;
+                    popup_settings.positioner.anchor_rect = iced::Rectangle::<i32> {
+                        x: x as i32,
+                        y: y as i32,
+                        width: width as i32,
+                        height: height as i32,
+                    };
+                    return get_popup(popup_settings);
+                }
+            }
             Message::TopLevelListPopup(id, parent_window_id) => {
                 if let Some(Popup {
                     parent,
@@ -868,11 +998,10 @@ impl cosmic::Application for CosmicAppList {
                     })
                     .or_else(|| {
                         if let Some(pos) = self.pinned_list.iter().position(|t| t.id == id) {
+                            // the favorites are only saved once the drag ends, so
+                            // that a reorder is written as a single change
                             let t = self.pinned_list.remove(pos);
-                            self.config.remove_pinned(
-                                &t.original_app_id,
-                                &Config::new(APP_ID, AppListConfig::VERSION).unwrap(),
-                            );
+                            self.dragged_pin_index = Some(pos);
                             Some((true, t))
                         } else {
                             None
@@ -884,21 +1013,42 @@ impl cosmic::Application for CosmicAppList {
                 }
             }
             Message::DragFinished => {
+                let pin_index = self.dragged_pin_index.take();
+                let dropped_on_remove = std::mem::take(&mut self.dropped_on_remove);
                 if let Some((_, mut toplevel_group, _)) = self.dnd_source.take() {
-                    if !self
+                    if self
                         .pinned_list
                         .iter()
                         .chain(self.active_list.iter())
                         .any(|t| t.desktop_info.id() == toplevel_group.desktop_info.id())
-                        && !toplevel_group.toplevels.is_empty()
                     {
-                        self.item_ctr += 1;
-                        toplevel_group.id = self.item_ctr;
-                        self.active_list.push(toplevel_group);
+                        return Task::none();
+                    }
+                    let unpin = dropped_on_remove || self.config.drag_off_unpins;
+                    match pin_index {
+                        // put the app back where it was
+                        Some(index) if !unpin || self.config.confirm_unpin => {
+                            let id = toplevel_group.id;
+                            self.pinned_list
+                                .insert(index.min(self.pinned_list.len()), toplevel_group);
+                            if unpin {
+                                return self.update(Message::ConfirmUnpin(id));
+                            }
+                        }
+                        _ if !toplevel_group.toplevels.is_empty() => {
+                            self.item_ctr += 1;
+                            toplevel_group.id = self.item_ctr;
+                            self.active_list.push(toplevel_group);
+                            if pin_index.is_some() {
+                                self.save_pinned();
+                            }
+                        }
+                        Some(_) => self.save_pinned(),
+                        None => {}
                     }
                 }
             }
-            Message::DndEnter(x, y) => {
+            Message::DndEnter(list, x, y) => {
                 let item_size = self.core.applet.suggested_size(false).0
                     + 2 * self.core.applet.suggested_padding(false);
                 let pos_in_list = match self.core.applet.anchor {
@@ -908,12 +1058,16 @@ impl cosmic::Application for CosmicAppList {
                 let num_pinned = self.pinned_list.len();
                 let index = index_in_list(num_pinned, item_size as f32, 4.0, None, pos_in_list);
                 self.dnd_offer = Some(DndOffer {
+                    list,
                     preview_index: index,
+                    position: iced::Point::new(x as f32, y as f32),
                     ..DndOffer::default()
                 });
                 // TODO dnd
                 if let Some(dnd_source) = self.dnd_source.as_ref() {
-                    self.dnd_offer.as_mut().unwrap().dock_item = Some(dnd_source.1.clone());
+                    if list == DndList::Pinned {
+                        self.dnd_offer.as_mut().unwrap().dock_item = Some(dnd_source.1.clone());
+                    }
                 } else {
                     // TODO dnd
                     return peek_dnd::<DndPathBuf>()
@@ -938,6 +1092,7 @@ impl cosmic::Application for CosmicAppList {
                 );
                 if let Some(o) = self.dnd_offer.as_mut() {
                     o.preview_index = index;
+                    o.position = iced::Point::new(x as f32, y as f32);
                 }
             }
             Message::DndExit => {
@@ -948,19 +1103,48 @@ impl cosmic::Application for CosmicAppList {
                     tracing::error!("Couldn't peek at hovered path.");
                     return Task::none();
                 };
-                if let Some(DndOffer { dock_item, .. }) = self.dnd_offer.as_mut() {
-                    if let Ok(de) = fde::DesktopEntry::from_path(file_path.0, Some(&self.locales)) {
-                        self.item_ctr += 1;
-                        *dock_item = Some(DockItem {
-                            id: self.item_ctr,
-                            toplevels: Vec::new(),
-                            original_app_id: de.id().to_string(),
-                            desktop_info: de,
-                        });
+                if let Some(DndOffer {
+                    list,
+                    dock_item,
+                    file,
+                    ..
+                }) = self.dnd_offer.as_mut()
+                {
+                    let is_desktop_entry = file_path.0.extension().is_some_and(|e| e == "desktop");
+                    if !is_desktop_entry {
+                        *file = Some(file_path.0);
+                    } else if *list == DndList::Pinned {
+                        if let Ok(de) =
+                            fde::DesktopEntry::from_path(file_path.0, Some(&self.locales))
+                        {
+                            self.item_ctr += 1;
+                            *dock_item = Some(DockItem {
+                                id: self.item_ctr,
+                                toplevels: Vec::new(),
+                                original_app_id: de.id().to_string(),
+                                desktop_info: de,
+                            });
+                        }
                     }
                 }
             }
             Message::DndDropFinished => {
+                if self
+                    .dnd_offer
+                    .as_ref()
+                    .is_some_and(|o| o.list == DndList::Remove)
+                {
+                    self.dnd_offer = None;
+                    self.dropped_on_remove = true;
+                    return Task::none();
+                }
+                if let Some((id, file)) = self.dnd_offer.as_ref().and_then(|o| {
+                    let item = self.dock_item_at(o.position)?;
+                    Some((item.id, o.file.clone()?))
+                }) {
+                    self.dnd_offer = None;
+                    return self.update(Message::OpenFile(id, file));
+                }
                 // we actually should have the data already, if not, we probably shouldn't do
                 // anything anyway
                 if let Some((mut dock_item, index)) = self
@@ -994,13 +1178,7 @@ impl cosmic::Application for CosmicAppList {
                     if dock_item.desktop_info.exec().is_some() {
                         self.pinned_list
                             .insert(index.min(self.pinned_list.len()), dock_item);
-                        self.config.update_pinned(
-                            self.pinned_list
-                                .iter()
-                                .map(|dock_item| dock_item.original_app_id.clone())
-                                .collect(),
-                            &Config::new(APP_ID, AppListConfig::VERSION).unwrap(),
-                        );
+                        self.save_pinned();
                     }
                 }
             }
@@ -1149,6 +1327,24 @@ impl cosmic::Application for CosmicAppList {
             Message::RemovedSeat => {
                 self.seat.take();
             }
+            Message::OpenFile(id, file) => {
+                let Some(dock_item) = self
+                    .pinned_list
+                    .iter()
+                    .chain(self.active_list.iter())
+                    .find(|item| item.id == id)
+                else {
+                    return Task::none();
+                };
+                if let Some(exec) = dock_item
+                    .desktop_info
+                    .exec()
+                    .and_then(|exec| exec_with_file(exec, &file))
+                {
+                    let gpu_idx = preferred_gpu_idx(&dock_item.desktop_info, self.gpus.as_deref());
+                    return self.update(Message::Exec(exec, gpu_idx));
+                }
+            }
             Message::Exec(exec, gpu_idx) => {
                 if let Some(tx) = self.wayland_sender.as_ref() {
                     let _ = tx.send(WaylandRequest::TokenRequest {
@@ -1265,9 +1461,12 @@ impl cosmic::Application for CosmicAppList {
                         + 2 * self.core.applet.suggested_padding(false);
                     let (_favorite_popup_cutoff, active_popup_cutoff) =
                         self.panel_overflow_lengths();
-                    let popup_applet_count = self.active_list.len().saturating_sub(
-                        (active_popup_cutoff.unwrap_or_default()).saturating_sub(1) as usize,
-                    ) as f32;
+                    let popup_applet_count = self
+                        .active_list
+                        .iter()
+                        .skip(active_popup_cutoff.unwrap_or_default().saturating_sub(1))
+                        .map(|item| self.icon_slots(item))
+                        .sum::<usize>() as f32;
                     let popup_applet_size = applet_suggested_size as f32 * popup_applet_count
                         + 4.0 * (popup_applet_count - 1.);
                     let (max_width, max_height) = match self.core.applet.anchor {
@@ -1370,6 +1569,7 @@ impl cosmic::Application for CosmicAppList {
                 4
             }
         };
+        let active_list = self.visible_active_list();
         let (favorite_popup_cutoff, active_popup_cutoff) = self.panel_overflow_lengths();
         let mut favorite_to_remove = if let Some(cutoff) = favorite_popup_cutoff {
             if cutoff < self.pinned_list.len() {
@@ -1394,19 +1594,20 @@ impl cosmic::Application for CosmicAppList {
             .iter()
             .rev()
             .map(|dock_item| {
-                dock_item.as_icon(
-                    &self.core.applet,
-                    self.rectangle_tracker.as_ref(),
-                    self.popup.is_none(),
-                    self.config.enable_drag_source,
-                    self.gpus.as_deref(),
-                    dock_item
-                        .toplevels
-                        .iter()
-                        .any(|y| focused_item.contains(&y.0)),
-                    theme.cosmic().radius_xs(),
-                    self.core.main_window_id().unwrap(),
-                )
+                dock_item
+                    .as_icon(
+                        &self.core.applet,
+                        self.rectangle_tracker.as_ref(),
+                        self.popup.is_none(),
+                        self.config.enable_drag_source,
+                        self.gpus.as_deref(),
+                        &focused_item,
+                        theme.cosmic().radius_xs(),
+                        self.config.window_count_dots,
+                        self.config.group_windows,
+                        self.core.main_window_id().unwrap(),
+                    )
+                    .apply(|icon| self.file_drop_highlight(dock_item, icon))
             })
             .collect();
 
@@ -1446,8 +1647,10 @@ impl cosmic::Application for CosmicAppList {
                     false,
                     self.config.enable_drag_source,
                     self.gpus.as_deref(),
-                    item.toplevels.iter().any(|y| focused_item.contains(&y.0)),
+                    &focused_item,
                     dot_radius,
+                    self.config.window_count_dots,
+                    self.config.group_windows,
                     self.core.main_window_id().unwrap(),
                 ),
             );
@@ -1463,34 +1666,35 @@ impl cosmic::Application for CosmicAppList {
             );
         }
 
-        let mut active: Vec<_> = self.active_list[..active_popup_cutoff
+        let mut active: Vec<_> = active_list[..active_popup_cutoff
             .map(|n| {
-                if n < self.active_list.len() {
+                if n < active_list.len() {
                     n.saturating_sub(1)
                 } else {
                     n
                 }
             })
-            .unwrap_or(self.active_list.len())]
+            .unwrap_or(active_list.len())]
             .iter()
             .map(|dock_item| {
-                dock_item.as_icon(
-                    &self.core.applet,
-                    self.rectangle_tracker.as_ref(),
-                    self.popup.is_none(),
-                    self.config.enable_drag_source,
-                    self.gpus.as_deref(),
-                    dock_item
-                        .toplevels
-                        .iter()
-                        .any(|y| focused_item.contains(&y.0)),
-                    dot_radius,
-                    self.core.main_window_id().unwrap(),
-                )
+                dock_item
+                    .as_icon(
+                        &self.core.applet,
+                        self.rectangle_tracker.as_ref(),
+                        self.popup.is_none(),
+                        self.config.enable_drag_source,
+                        self.gpus.as_deref(),
+                        &focused_item,
+                        dot_radius,
+                        self.config.window_count_dots,
+                        self.config.group_windows,
+                        self.core.main_window_id().unwrap(),
+                    )
+                    .apply(|icon| self.file_drop_highlight(dock_item, icon))
             })
             .collect();
 
-        if active_popup_cutoff.is_some_and(|n| n < self.active_list.len()) {
+        if active_popup_cutoff.is_some_and(|n| n < active_list.len()) {
             // button to show more active
             let icon = match self.core.applet.anchor {
                 PanelAnchor::Bottom => "go-up-symbolic",
@@ -1543,7 +1747,10 @@ impl cosmic::Application for CosmicAppList {
                     row(favorites).spacing(app_icon.icon_spacing),
                     |_, _| Message::DndDropFinished,
                 ),
-                row(active).spacing(app_icon.icon_spacing).into(),
+                DndDestination::for_data::<DndPathBuf>(
+                    row(active).spacing(app_icon.icon_spacing),
+                    |_, _| Message::DndDropFinished,
+                ),
                 container(vertical_rule(1))
                     .height(Length::Fill)
                     .padding([divider_padding, 0])
@@ -1557,7 +1764,10 @@ impl cosmic::Application for CosmicAppList {
                     column(favorites).spacing(app_icon.icon_spacing),
                     |_data: Option<DndPathBuf>, _| Message::DndDropFinished,
                 ),
-                column(active).spacing(app_icon.icon_spacing).into(),
+                DndDestination::for_data::<DndPathBuf>(
+                    column(active).spacing(app_icon.icon_spacing),
+                    |_, _| Message::DndDropFinished,
+                ),
                 container(divider::horizontal::default())
                     .width(Length::Fill)
                     .padding([0, divider_padding])
@@ -1566,18 +1776,43 @@ impl cosmic::Application for CosmicAppList {
         };
 
         let favorites = favorites
-            .on_enter(|x, y, _| Message::DndEnter(x, y))
+            .on_enter(|x, y, _| Message::DndEnter(DndList::Pinned, x, y))
             .on_motion(Message::DndMotion)
             .on_leave(|| Message::DndExit);
+        let active: Element<_> = active
+            .on_enter(|x, y, _| Message::DndEnter(DndList::Active, x, y))
+            .on_motion(Message::DndMotion)
+            .on_leave(|| Message::DndExit)
+            .into();
+
+        // dragging a pin away only unpins it when dropped here
+        let remove_zone = (!self.config.drag_off_unpins
+            && self.dnd_source.is_some()
+            && self.dragged_pin_index.is_some())
+        .then(|| {
+            let suggested_size = self.core.applet.suggested_size(false);
+            let padding = self.core.applet.suggested_padding(false);
+            Element::from(
+                DndDestination::for_data::<DndPathBuf>(
+                    container(icon::from_name("user-trash-symbolic").size(suggested_size.0))
+                        .padding(padding),
+                    |_, _| Message::DndDropFinished,
+                )
+                .on_enter(|x, y, _| Message::DndEnter(DndList::Remove, x, y))
+                .on_leave(|| Message::DndExit),
+            )
+        });
 
         let show_pinned =
             !self.pinned_list.is_empty() || self.dnd_offer.is_some() || self.is_listening_for_dnd;
-        let content_list: Vec<Element<_>> = if show_pinned && !self.active_list.is_empty() {
+        let mut content_list: Vec<Element<_>> = if show_pinned && !active_list.is_empty() {
             vec![favorites.into(), divider, active]
         } else if show_pinned {
             vec![favorites.into()]
-        } else if !self.active_list.is_empty() {
+        } else if !active_list.is_empty() {
             vec![active]
+        } else if remove_zone.is_some() {
+            Vec::new()
         } else {
             vec![
                 cosmic::widget::icon::from_name("com.system76.CosmicAppList")
@@ -1585,6 +1820,7 @@ impl cosmic::Application for CosmicAppList {
                     .into(),
             ]
         };
+        content_list.extend(remove_zone);
 
         let mut content = match &self.core.applet.anchor {
             PanelAnchor::Left | PanelAnchor::Right => container(
@@ -1602,7 +1838,7 @@ impl cosmic::Application for CosmicAppList {
                     .width(w),
             ),
         };
-        if self.active_list.is_empty() && self.pinned_list.is_empty() {
+        if active_list.is_empty() && self.pinned_list.is_empty() {
             let suggested_size = self.core.applet.suggested_size(false);
             content = content.width(suggested_size.0).height(suggested_size.1);
         }
@@ -1809,6 +2045,35 @@ impl cosmic::Application for CosmicAppList {
                         )
                         .into()
                 }
+                PopupType::ConfirmUnpin => {
+                    let space_xxs = theme::active().cosmic().space_xxs();
+                    let name = desktop_info
+                        .name(&self.locales)
+                        .unwrap_or_default()
+                        .into_owned();
+                    let content = column![
+                        text::body(fl!("unpin-confirm", name = name)),
+                        row![
+                            button::text(fl!("cancel")).on_press(Message::ClosePopup),
+                            button::destructive(fl!("unpin")).on_press(Message::UnpinApp(*id)),
+                        ]
+                        .spacing(space_xxs)
+                    ]
+                    .padding(8)
+                    .spacing(8)
+                    .align_x(Alignment::End);
+                    self.core
+                        .applet
+                        .popup_container(container(content))
+                        .limits(
+                            Limits::NONE
+                                .min_width(1.)
+                                .min_height(1.)
+                                .max_width(300.)
+                                .max_height(1000.),
+                        )
+                        .into()
+                }
                 PopupType::TopLevelList => match self.core.applet.anchor {
                     PanelAnchor::Left | PanelAnchor::Right => {
                         let mut content =
@@ -1882,11 +2147,10 @@ impl cosmic::Application for CosmicAppList {
                         self.popup.is_none(),
                         self.config.enable_drag_source,
                         self.gpus.as_deref(),
-                        dock_item
-                            .toplevels
-                            .iter()
-                            .any(|y| focused_item.contains(&y.0)),
+                        &focused_item,
                         dot_radius,
+                        self.config.window_count_dots,
+                        self.config.group_windows,
                         id,
                     )
                 })
@@ -1969,11 +2233,10 @@ impl cosmic::Application for CosmicAppList {
                         self.popup.is_none(),
                         self.config.enable_drag_source,
                         self.gpus.as_deref(),
-                        dock_item
-                            .toplevels
-                            .iter()
-                            .any(|y| focused_item.contains(&y.0)),
+                        &focused_item,
                         dot_radius,
+                        self.config.window_count_dots,
+                        self.config.group_windows,
                         id,
                     )
                 })
@@ -2071,11 +2334,24 @@ impl CosmicAppList {
         }
         Task::batch(commands)
     }
+
+    /// Writes the order of the pinned list to the favorites in the config.
+    fn save_pinned(&mut self) {
+        self.config.update_pinned(
+            self.pinned_list
+                .iter()
+                .map(|dock_item| dock_item.original_app_id.clone())
+                .collect(),
+            &Config::new(APP_ID, AppListConfig::VERSION).unwrap(),
+        );
+    }
+
     /// Returns the length of the group in the favorite list after which items are displayed in a popup.
     /// Shrink the favorite list until it only has active windows, or until it fits in the length provided.
     fn panel_overflow_lengths(&self) -> (Option<usize>, Option<usize>) {
         let mut favorite_index;
         let mut active_index = None;
+        let active_list = self.visible_active_list();
         let Some(mut max_major_axis_len) = self.core.applet.suggested_bounds.as_ref().map(|c| {
             // if we have a configure for width and height, we're in a overflow popup
             match self.core.applet.anchor {
@@ -2094,27 +2370,33 @@ impl CosmicAppList {
             + self.core.applet.suggested_padding(true) * 2
             + applet_icon.icon_spacing as u16;
 
-        let favorite_active_cnt = self
+        // without grouping, running apps take one slot per window
+        let slots =
+            |items: &[DockItem]| -> usize { items.iter().map(|item| self.icon_slots(item)).sum() };
+        let pinned_slots = slots(&self.pinned_list);
+        let active_slots = slots(active_list);
+        let favorite_active_cnt: usize = self
             .pinned_list
             .iter()
             .filter(|t| !t.toplevels.is_empty())
-            .count();
+            .map(|t| self.icon_slots(t))
+            .sum();
 
         // initial calculation of favorite_index
         let btn_count = max_major_axis_len / button_total_size as u32;
-        if btn_count >= self.pinned_list.len() as u32 + self.active_list.len() as u32 {
+        if btn_count >= pinned_slots as u32 + active_slots as u32 {
             return (None, active_index);
         } else {
             favorite_index = (btn_count as usize).min(favorite_active_cnt).max(2);
         }
 
         // calculation of active_index based on favorite_index if there is still not enough space
-        let active_index_max = (btn_count as i32)
-            - (self.pinned_list.len() as i32).saturating_sub(favorite_index as i32);
-        if active_index_max >= self.active_list.len() as i32 {
-            active_index = Some(self.active_list.len());
+        let active_index_max =
+            (btn_count as i32) - (pinned_slots as i32).saturating_sub(favorite_index as i32);
+        if active_index_max >= active_slots as i32 {
+            active_index = Some(active_slots);
         } else {
-            active_index = Some((active_index_max.max(2) as usize).min(self.active_list.len()));
+            active_index = Some((active_index_max.max(2) as usize).min(active_slots));
         }
 
         // final calculation of favorite_index if there is still not enough space
@@ -2122,12 +2404,94 @@ impl CosmicAppList {
             let favorite_index_max = (btn_count as i32) - active_index as i32;
             favorite_index = favorite_index_max.max(2) as usize;
         } else {
-            favorite_index = (btn_count as usize).min(self.pinned_list.len());
+            favorite_index = (btn_count as usize).min(pinned_slots);
         }
+
+        // convert the slot counts back to item indices; only pinned items
+        // without windows move to the popup, and those take a single slot
+        let favorite_index = favorite_index.saturating_sub(pinned_slots - self.pinned_list.len());
+        let active_index = active_index.map(|cutoff| {
+            if cutoff >= active_slots {
+                return active_list.len();
+            }
+            // the last slot is taken by the button opening the popup
+            let mut used = 0;
+            let shown = active_list
+                .iter()
+                .take_while(|item| {
+                    used += self.icon_slots(item);
+                    used < cutoff
+                })
+                .count();
+            (shown + 1).max(2).min(active_list.len())
+        });
         // tracing::error!("{} {} {:?}", btn_count, favorite_index, active_index);
         return (Some(favorite_index), active_index);
     }
 
+    /// Number of icons the item takes in the panel.
+    fn icon_slots(&self, item: &DockItem) -> usize {
+        if self.config.group_windows {
+            1
+        } else {
+            item.toplevels.len().max(1)
+        }
+    }
+
+    /// Pinned or running item whose icon is under `position`.
+    fn dock_item_at(&self, position: iced::Point) -> Option<&DockItem> {
+        self.rectangles.iter().find_map(|(id, rectangle)| match id {
+            DockItemId::Item(id) if rectangle.contains(position) => self
+                .pinned_list
+                .iter()
+                .chain(self.visible_active_list())
+                .find(|item| item.id == *id),
+            _ => None,
+        })
+    }
+
+    /// Outlines the item a file is dragged over, depending on whether it can open files.
+    fn file_drop_highlight<'a>(
+        &self,
+        dock_item: &DockItem,
+        icon: Element<'a, Message>,
+    ) -> Element<'a, Message> {
+        let Some(offer) = self.dnd_offer.as_ref().filter(|o| o.file.is_some()) else {
+            return icon;
+        };
+        if self.dock_item_at(offer.position).map(|item| item.id) != Some(dock_item.id) {
+            return icon;
+        }
+        let accepts_files = dock_item.desktop_info.exec().is_some_and(accepts_files);
+
+        container(icon)
+            .class(Container::custom(move |theme| {
+                let cosmic = theme.cosmic();
+                container::Style {
+                    border: Border {
+                        color: if accepts_files {
+                            cosmic.accent_color().into()
+                        } else {
+                            cosmic.destructive_color().into()
+                        },
+                        width: 2.0,
+                        radius: cosmic.radius_s().into(),
+                    },
+                    ..Default::default()
+                }
+            }))
+            .into()
+    }
+
+    /// Running applications that aren't pinned, unless hidden by the config.
+    fn visible_active_list(&self) -> &[DockItem] {
+        if self.config.show_running_unpinned {
+            &self.active_list
+        } else {
+            &[]
+        }
+    }
+
     fn currently_active_toplevel(&self) -> Vec<ZcosmicToplevelHandleV1> {
         if self.active_workspaces.is_empty() {
             return Vec::new();
@@ -2155,20 +2519,56 @@ impl CosmicAppList {
     }
 }
 
-fn launch_on_preferred_gpu(desktop_info: &DesktopEntry, gpus: Option<&[Gpu]>) -> Option<Message> {
-    let Some(exec) = desktop_info.exec() else {
-        return None;
-    };
-
-    let gpu_idx = gpus.map(|gpus| {
+fn preferred_gpu_idx(desktop_info: &DesktopEntry, gpus: Option<&[Gpu]>) -> Option<usize> {
+    gpus.map(|gpus| {
         if desktop_info.prefers_non_default_gpu() {
             gpus.iter().position(|gpu| !gpu.default).unwrap_or(0)
         } else {
             gpus.iter().position(|gpu| gpu.default).unwrap_or(0)
         }
-    });
+    })
+}
 
-    Some(Message::Exec(exec.to_string(), gpu_idx))
+fn launch_on_preferred_gpu(desktop_info: &DesktopEntry, gpus: Option<&[Gpu]>) -> Option<Message> {
+    let Some(exec) = desktop_info.exec() else {
+        return None;
+    };
+
+    Some(Message::Exec(
+        exec.to_string(),
+        preferred_gpu_idx(desktop_info, gpus),
+    ))
+}
+
+/// Whether a desktop entry's `Exec` key has a file or URL field code.
+fn accepts_files(exec: &str) -> bool {
+    ["%f", "%F", "%u", "%U"]
+        .iter()
+        .any(|code| exec.contains(code))
+}
+
+/// Substitutes `file` for the file and URL field codes of a desktop entry's `Exec` key.
+/// Returns `None` if the entry doesn't take files.
+fn exec_with_file(exec: &str, file: &Path) -> Option<String> {
+    // quoted for the shell-style splitting of `spawn_desktop_exec`
+    fn quote(arg: &str) -> String {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+
+    if !accepts_files(exec) {
+        return None;
+    }
+    let path = quote(file.to_str()?);
+    let url = Url::from_file_path(file)
+        .map(|url| quote(url.as_str()))
+        .unwrap_or_else(|()| path.clone());
+
+    Some(
+        exec.replace("%f", &path)
+            .replace("%F", &path)
+            .replace("%u", &url)
+            .replace("%U", &url),
+    )
 }
 
 #[derive(Debug, Default, Clone)]
@@ -5,3 +5,4 @@ sound-settings = Klang-Einstellungen...
 disconnected = PulseAudio getrennt
 no-device = Kein Gerät ausgewählt
 unknown-artist = Unbekannt
+volume-percent = { $percent } %
@@ -5,3 +5,21 @@ sound-settings = Sound Settings...
 disconnected = PulseAudio Disconnected
 no-device = No device selected
 unknown-artist = Unknown
+balance = Balance
+balance-center = Center
+no-devices = No audio devices found
+no-devices-hint = Check your sound settings and audio drivers.
+call-volume = Call volume
+confirm-bluetooth-output = Switch audio to { $device }?
+cancel = Cancel
+switch = Switch
+resampling = Resampling { $stream } to { $device }
+applications = Applications
+applications-playing = { $count ->
+    [one] 1 application playing
+   *[other] { $count } applications playing
+}
+confirm-mute = { $app } is playing. Mute anyway?
+mute = Mute
+media-player = Media player
+volume-percent = { $percent }%
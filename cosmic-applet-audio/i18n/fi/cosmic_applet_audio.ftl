@@ -5,3 +5,4 @@ sound-settings = Ääni Asetukset...
 disconnected = PulseAudio Ei Yhdistetty
 no-device = Laitetta ei ole valittu
 
+volume-percent = { $percent } %
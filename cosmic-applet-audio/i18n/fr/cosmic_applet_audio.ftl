@@ -5,3 +5,4 @@ sound-settings = Paramètres sonores...
 disconnected = PulseAudio est déconnecté
 no-device = Pas de périphérique sélectionné 
 unknown-artist = Inconnu(e)
+volume-percent = { $percent } %
@@ -5,3 +5,4 @@ sound-settings = Ses Ayarları...
 disconnected = PulseAudio bağlantısı kesildi
 no-device = Seçili aygıt yok
 unknown-artist = Bilinmeyen
+volume-percent = %{ $percent }
@@ -3,9 +3,84 @@
 
 use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+/// Upper bound for `volume_write_debounce_ms`.
+const MAX_VOLUME_WRITE_DEBOUNCE_MS: u64 = 500;
+
+/// Upper bound for `volume_ramp_ms`.
+const MAX_VOLUME_RAMP_MS: u64 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
 #[version = 1]
 pub struct AudioAppletConfig {
     pub show_media_controls_in_top_panel: bool,
+    /// Pair of source names the input swap button toggles between.
+    pub input_swap_pair: Option<(String, String)>,
+    /// Delay before a volume change is written to the server. 0 disables debouncing.
+    pub volume_write_debounce_ms: u64,
+    /// Scrolling up over the panel icon lowers the volume instead of raising it.
+    pub invert_scroll: bool,
+    /// Show the microphone volume and device selection. They are hidden
+    /// regardless when there are no input devices.
+    pub show_input_controls: bool,
+    /// MPRIS player the media controls follow whenever it is running, matched
+    /// against its `Identity`, `DesktopEntry`, or bus name suffix.
+    pub preferred_player: Option<String>,
+    /// Ask before switching to a Bluetooth output while media is playing or a
+    /// call is in progress.
+    pub confirm_bluetooth_output: bool,
+    /// Warn below the balance control when a playing stream is resampled to
+    /// the output's sample rate.
+    pub show_resampling_warning: bool,
+    /// Volume in percent that right-clicking a volume slider resets the
+    /// device to, unmuting it as well.
+    pub default_volume: u32,
+    /// Volume change in percent per step of the mouse wheel over the panel icon.
+    pub scroll_step: u32,
+    /// Scrolling over the panel icon changes the input volume instead of the output volume.
+    pub scroll_adjusts_input: bool,
+    /// Ask before muting the output while a screen reader or an alarm is
+    /// playing on it.
+    pub confirm_mute_critical: bool,
+    /// Use vertical volume sliders in the popup when the panel is on the left
+    /// or right edge of the screen.
+    pub vertical_sliders: bool,
+    /// Duration of the transition to a volume set by scrolling over the panel
+    /// icon. 0 changes the volume at once; dragging a slider always does.
+    pub volume_ramp_ms: u64,
+}
+
+impl Default for AudioAppletConfig {
+    fn default() -> Self {
+        Self {
+            show_media_controls_in_top_panel: false,
+            input_swap_pair: None,
+            volume_write_debounce_ms: 64,
+            invert_scroll: false,
+            show_input_controls: true,
+            preferred_player: None,
+            confirm_bluetooth_output: false,
+            show_resampling_warning: false,
+            default_volume: 60,
+            scroll_step: 5,
+            scroll_adjusts_input: false,
+            confirm_mute_critical: false,
+            vertical_sliders: false,
+            volume_ramp_ms: 0,
+        }
+    }
+}
+
+impl AudioAppletConfig {
+    pub fn volume_write_debounce(&self) -> Duration {
+        Duration::from_millis(
+            self.volume_write_debounce_ms
+                .min(MAX_VOLUME_WRITE_DEBOUNCE_MS),
+        )
+    }
+
+    pub fn volume_ramp(&self) -> Duration {
+        Duration::from_millis(self.volume_ramp_ms.min(MAX_VOLUME_RAMP_MS))
+    }
 }
@@ -4,9 +4,10 @@
 mod localize;
 mod mouse_area;
 
-use std::time::Duration;
-
-use crate::{localize::localize, pulse::DeviceInfo};
+use crate::{
+    localize::localize,
+    pulse::{DeviceInfo, SinkInputInfo},
+};
 use config::AudioAppletConfig;
 use cosmic::{
     app,
@@ -27,7 +28,6 @@ use cosmic::{
     widget::{button, divider, horizontal_space, icon, text, Column, Row},
     Element, Renderer, Task, Theme,
 };
-use cosmic_settings_subscriptions::pulse as sub_pulse;
 use cosmic_time::{anim, chain, id, once_cell::sync::Lazy, Instant, Timeline};
 use iced::{
     platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup},
@@ -36,10 +36,14 @@ use iced::{
 use libpulse_binding::volume::Volume;
 use mpris2_zbus::player::PlaybackStatus;
 use mpris_subscription::{MprisRequest, MprisUpdate};
+use std::{borrow::Cow, rc::Rc, time::Duration};
 
 mod config;
 mod mpris_subscription;
 mod pulse;
+mod sub_pulse;
+
+pub use pulse::set_bluetooth_default_sink;
 
 // Full, in this case, means 100%.
 static FULL_VOLUME: f64 = Volume::NORMAL.0 as f64;
@@ -48,17 +52,36 @@ static FULL_VOLUME: f64 = Volume::NORMAL.0 as f64;
 static MAX_VOLUME: f64 = FULL_VOLUME + (FULL_VOLUME * 0.5);
 
 static SHOW_MEDIA_CONTROLS: Lazy<id::Toggler> = Lazy::new(id::Toggler::unique);
+/// Keeps the timeline producing frames while a volume transition runs.
+static VOLUME_RAMP: Lazy<id::Container> = Lazy::new(id::Container::unique);
+/// Keeps the timeline producing frames while the input level meter falls.
+static PEAK_FALLOFF: Lazy<id::Container> = Lazy::new(id::Container::unique);
 
 const GO_BACK: &str = "media-skip-backward-symbolic";
 const GO_NEXT: &str = "media-skip-forward-symbolic";
 const PAUSE: &str = "media-playback-pause-symbolic";
 const PLAY: &str = "media-playback-start-symbolic";
+/// Height of the volume sliders in the vertical layout.
+const VERTICAL_SLIDER_HEIGHT: f32 = 150.0;
+/// Time to wait for the seek bar to settle before seeking the player.
+const SEEK_DEBOUNCE: Duration = Duration::from_millis(100);
+/// Time for the input level meter to fall from full to empty.
+const PEAK_FALL: Duration = Duration::from_secs(1);
 
 pub fn run() -> cosmic::iced::Result {
     localize();
     cosmic::applet::run::<Audio>(())
 }
 
+/// Transition of a device volume towards a new level.
+#[derive(Debug, Clone, Copy)]
+struct VolumeRamp {
+    input: bool,
+    from: f64,
+    to: f64,
+    start: Instant,
+}
+
 #[derive(Default)]
 pub struct Audio {
     core: cosmic::app::Core,
@@ -69,28 +92,106 @@ pub struct Audio {
     input_volume: f64,
     input_volume_debounce: bool,
     input_volume_text: String,
+    output_balance_debounce: bool,
+    /// Volume of the streams of an ongoing call, if any.
+    communication_volume: Option<f64>,
+    communication_volume_debounce: bool,
+    /// Rate of a stream the current output resamples, if any.
+    resampled_rate: Option<u32>,
+    /// Decaying peak level of the current input, shown while the popup is open.
+    input_peak: f32,
+    /// Last peak the meter falls from, and when it was reached.
+    last_peak: Option<(f32, Instant)>,
     current_output: Option<DeviceInfo>,
     current_input: Option<DeviceInfo>,
+    previous_input: Option<String>,
+    /// Bluetooth sink waiting for the user to confirm the switch.
+    pending_output: Option<String>,
+    /// Muting the output waits for the user to confirm it.
+    pending_mute: bool,
     outputs: Vec<DeviceInfo>,
     inputs: Vec<DeviceInfo>,
+    /// Streams of the applications playing audio.
+    sink_inputs: Vec<SinkInputInfo>,
     pulse_state: PulseState,
     popup: Option<window::Id>,
     timeline: Timeline,
+    volume_ramp: Option<VolumeRamp>,
     config: AudioAppletConfig,
-    player_status: Option<mpris_subscription::PlayerStatus>,
+    /// Every running media player.
+    players: Vec<mpris_subscription::PlayerStatus>,
+    /// Index of the player the subscription would follow on its own.
+    active_player: Option<usize>,
+    /// Bus name of the player picked in the popup, kept for the session.
+    selected_player: Option<String>,
+    /// When the positions in `players` were read.
+    players_updated: Option<std::time::Instant>,
+    /// Seek bar position in seconds that has yet to be sent to the player.
+    seek_position: Option<f64>,
+    seek_debounce: bool,
     token_tx: Option<calloop::channel::Sender<TokenRequest>>,
 }
 
 impl Audio {
+    /// Runs the timeline for as long as the volume transition has left.
+    fn animate_volume_ramp(&mut self) {
+        let Some(ramp) = self.volume_ramp else {
+            return;
+        };
+        let remaining = self
+            .config
+            .volume_ramp()
+            .saturating_sub(Instant::now().duration_since(ramp.start));
+        self.timeline
+            .set_chain(cosmic_time::chain![
+                VOLUME_RAMP,
+                cosmic_time::container(Duration::ZERO),
+                cosmic_time::container(remaining),
+            ])
+            .start();
+    }
+
+    /// Lets the input level meter fall from its last peak over the next frames.
+    fn animate_peak_falloff(&mut self) {
+        self.timeline
+            .set_chain(cosmic_time::chain![
+                PEAK_FALLOFF,
+                cosmic_time::container(Duration::ZERO),
+                cosmic_time::container(PEAK_FALL),
+            ])
+            .start();
+    }
+
     fn update_output(&mut self, output: Option<DeviceInfo>) {
         self.current_output = output;
 
         if let Some(device) = self.current_output.as_ref() {
             self.output_volume = volume_to_percent(device.volume.avg());
-            self.output_volume_text = format!("{}%", self.output_volume.round());
+            self.output_volume_text = volume_percent(self.output_volume);
         }
     }
 
+    fn set_output_mute(&mut self, mute: bool) {
+        if let Some(output) = self.current_output.as_mut() {
+            output.mute = mute;
+        }
+        if let PulseState::Connected(connection) = &mut self.pulse_state {
+            if let Some(device) = &self.current_output {
+                if let Some(name) = &device.name {
+                    connection.send(pulse::Message::SetSinkMuteByName(name.clone(), device.mute))
+                }
+            }
+        }
+    }
+
+    /// Unmuted screen reader or alarm stream playing on the current output.
+    fn critical_stream(&self) -> Option<&SinkInputInfo> {
+        let output = self.current_output.as_ref()?;
+        self.sink_inputs
+            .iter()
+            .find(|input| input.sink == output.index && !input.mute && input.is_critical())
+    }
+
     fn output_icon_name(&self) -> &'static str {
         let volume = self.output_volume;
         let mute = self.current_output_mute();
@@ -108,14 +209,138 @@ impl Audio {
     }
 
     fn update_input(&mut self, input: Option<DeviceInfo>) {
+        let prev_name = self.current_input.as_ref().and_then(|i| i.name.clone());
+        if prev_name.is_some() && prev_name != input.as_ref().and_then(|i| i.name.clone()) {
+            self.previous_input = prev_name;
+        }
         self.current_input = input;
 
         if let Some(device) = self.current_input.as_ref() {
             self.input_volume = volume_to_percent(device.volume.avg());
-            self.input_volume_text = format!("{}%", self.input_volume.round());
+            self.input_volume_text = volume_percent(self.input_volume);
         }
     }
 
+    /// The source to switch to when the input swap button is pressed.
+    ///
+    /// Prefers the configured pair, then the previously used source, and
+    /// finally any other available source.
+    fn swap_input_target(&self) -> Option<String> {
+        if self.inputs.len() < 2 {
+            return None;
+        }
+        let current = self.current_input.as_ref().and_then(|i| i.name.as_ref());
+        let available = |name: &String| {
+            Some(name) != current && self.inputs.iter().any(|i| i.name.as_ref() == Some(name))
+        };
+
+        let preferred = match self.config.input_swap_pair.as_ref() {
+            Some((a, b)) if current == Some(a) => Some(b),
+            Some((a, _)) => Some(a),
+            None => self.previous_input.as_ref(),
+        };
+
+        preferred
+            .filter(|name| available(*name))
+            .or_else(|| {
+                self.inputs
+                    .iter()
+                    .filter_map(|i| i.name.as_ref())
+                    .find(|name| available(*name))
+            })
+            .cloned()
+    }
+
+    /// Whether switching to `name` should be confirmed first, so that audio
+    /// isn't accidentally routed to a Bluetooth device mid-playback.
+    fn confirm_output_change(&self, name: &str) -> bool {
+        self.config.confirm_bluetooth_output
+            && name.starts_with("bluez_output.")
+            && self.current_output.as_ref().and_then(|o| o.name.as_deref()) != Some(name)
+            && (self.communication_volume.is_some()
+                || self
+                    .player_status()
+                    .is_some_and(|s| matches!(s.status, PlaybackStatus::Playing)))
+    }
+
+    /// Player the media controls act on: the one picked in the popup while it
+    /// is running, otherwise the one the subscription considers active.
+    fn player_status(&self) -> Option<&mpris_subscription::PlayerStatus> {
+        self.selected_player
+            .as_ref()
+            .and_then(|name| self.players.iter().find(|p| &p.name == name))
+            .or_else(|| self.players.get(self.active_player?))
+    }
+
+    /// Output and input volume as vertical sliders side by side, each with its
+    /// percentage above and mute button below.
+    fn vertical_volume_controls(&self, show_input: bool) -> Element<Message> {
+        let out_mute = self.current_output_mute();
+        let in_mute = self.current_input_mute();
+        let control = |icon_name: &'static str,
+                       volume: f64,
+                       volume_text_: &str,
+                       mute: bool,
+                       on_change: fn(f64) -> Message,
+                       on_mute: Message,
+                       on_reset: Message| {
+            column![
+                volume_text(volume_text_.to_string(), mute).width(Length::Shrink),
+                crate::mouse_area::MouseArea::new(
+                    widget::vertical_slider(0.0..=150.0, volume, on_change)
+                        .height(Length::Fixed(VERTICAL_SLIDER_HEIGHT))
+                        .class(volume_slider_class(mute)),
+                )
+                .on_right_press(on_reset),
+                button::icon(icon::from_name(icon_name).size(24).symbolic(true))
+                    .class(cosmic::theme::Button::Icon)
+                    .icon_size(24)
+                    .line_height(24)
+                    .on_press(on_mute),
+            ]
+            .spacing(8)
+            .align_x(Alignment::Center)
+        };
+
+        row![control(
+            self.output_icon_name(),
+            self.output_volume,
+            &self.output_volume_text,
+            out_mute,
+            Message::SetOutputVolume,
+            Message::SetOutputMute(!out_mute),
+            Message::ResetOutputVolume,
+        )]
+        .push_maybe(show_input.then(|| {
+            control(
+                self.input_icon_name(),
+                self.input_volume,
+                &self.input_volume_text,
+                in_mute,
+                Message::SetInputVolume,
+                Message::SetInputMute(!in_mute),
+                Message::ResetInputVolume,
+            )
+        }))
+        .spacing(24)
+        .width(Length::Fill)
+        .align_y(Alignment::Start)
+        .into()
+    }
+
+    /// Current playback position of a player, advanced by the time passed
+    /// since it was last read while playing.
+    fn player_position(&self, s: &mpris_subscription::PlayerStatus) -> Option<Duration> {
+        let mut position = s.position?;
+        if matches!(s.status, PlaybackStatus::Playing) {
+            position += self
+                .players_updated
+                .map(|t| t.elapsed())
+                .unwrap_or_default();
+        }
+        Some(s.length.map_or(position, |length| position.min(length)))
+    }
+
     fn input_icon_name(&self) -> &'static str {
         let volume = self.input_volume;
         let mute = self.current_input_mute();
@@ -136,6 +361,8 @@ enum IsOpen {
     None,
     Output,
     Input,
+    Applications,
+    Players,
 }
 
 #[derive(Debug, Clone)]
@@ -143,14 +370,33 @@ pub enum Message {
     Ignore,
     ApplyOutputVolume,
     ApplyInputVolume,
+    ApplyOutputBalance,
+    ApplyCommunicationVolume,
     SetOutputVolume(f64),
     SetInputVolume(f64),
+    /// Change the volume scrolled over the panel icon by this many percent.
+    ScrollVolume(f64),
+    SetOutputBalance(f32),
+    SetCommunicationVolume(f64),
     SetOutputMute(bool),
     SetInputMute(bool),
+    ResetOutputVolume,
+    ResetInputVolume,
     OutputToggle,
     InputToggle,
+    ApplicationsToggle,
+    RefreshSinkInputs,
+    SetSinkInputVolume(u32, f64),
+    SetSinkInputMute(u32, bool),
     OutputChanged(String),
+    /// Activate the port with this name on the current output.
+    OutputPortChanged(String),
+    ConfirmOutputChange,
+    CancelOutputChange,
+    ConfirmMute,
+    CancelMute,
     InputChanged(String),
+    SwapInput,
     Pulse(pulse::Event),
     TogglePopup,
     CloseRequested(window::Id),
@@ -159,6 +405,10 @@ pub enum Message {
     ConfigChanged(AudioAppletConfig),
     Mpris(mpris_subscription::MprisUpdate),
     MprisRequest(MprisRequest),
+    PlayersToggle,
+    SelectPlayer(String),
+    Seek(f64),
+    ApplySeek,
     Token(TokenUpdate),
     OpenSettings,
     PulseSub(sub_pulse::Event),
@@ -166,11 +416,10 @@ pub enum Message {
 
 impl Audio {
     fn playback_buttons(&self) -> Option<Element<Message>> {
-        if self.player_status.is_some() && self.config.show_media_controls_in_top_panel {
+        if self.player_status().is_some() && self.config.show_media_controls_in_top_panel {
             let mut elements = Vec::with_capacity(3);
             if self
-                .player_status
-                .as_ref()
+                .player_status()
                 .map(|s| s.can_go_previous)
                 .unwrap_or_default()
             {
@@ -196,8 +445,7 @@ impl Audio {
                 );
             }
             if self
-                .player_status
-                .as_ref()
+                .player_status()
                 .map(|s| s.can_go_next)
                 .unwrap_or_default()
             {
@@ -224,7 +472,7 @@ impl Audio {
     }
 
     fn go_previous(&self, icon_size: u16) -> Option<Element<Message>> {
-        self.player_status.as_ref().and_then(|s| {
+        self.player_status().and_then(|s| {
             if s.can_go_previous {
                 Some(
                     button::icon(icon::from_name(GO_BACK).size(icon_size).symbolic(true))
@@ -240,7 +488,7 @@ impl Audio {
     }
 
     fn go_next(&self, icon_size: u16) -> Option<Element<Message>> {
-        self.player_status.as_ref().and_then(|s| {
+        self.player_status().and_then(|s| {
             if s.can_go_next {
                 Some(
                     button::icon(icon::from_name(GO_NEXT).size(icon_size).symbolic(true))
@@ -256,7 +504,7 @@ impl Audio {
     }
 
     fn is_play(&self) -> Option<bool> {
-        self.player_status.as_ref().and_then(|s| match s.status {
+        self.player_status().and_then(|s| match s.status {
             PlaybackStatus::Playing => {
                 if s.can_pause {
                     Some(false)
@@ -326,10 +574,77 @@ impl cosmic::Application for Audio {
 
     fn update(&mut self, message: Message) -> app::Task<Message> {
         match message {
-            Message::Frame(now) => self.timeline.now(now),
+            Message::Frame(now) => {
+                self.timeline.now(now);
+                if let Some((peak, at)) = self.last_peak {
+                    let fallen = now.duration_since(at).as_secs_f32() / PEAK_FALL.as_secs_f32();
+                    self.input_peak = (peak - fallen).max(0.0);
+                    if self.input_peak == 0.0 {
+                        self.last_peak = None;
+                    }
+                }
+                if let Some(ramp) = self.volume_ramp.take() {
+                    let duration = self.config.volume_ramp().as_secs_f64();
+                    let t = if duration > 0. {
+                        (now.duration_since(ramp.start).as_secs_f64() / duration).min(1.)
+                    } else {
+                        1.
+                    };
+                    // ease out
+                    let volume = ramp.from + (ramp.to - ramp.from) * (1. - (1. - t).powi(3));
+                    let task = self.update(if ramp.input {
+                        Message::SetInputVolume(volume)
+                    } else {
+                        Message::SetOutputVolume(volume)
+                    });
+                    if t < 1. {
+                        self.volume_ramp = Some(ramp);
+                    }
+                    return task;
+                }
+            }
+            Message::ScrollVolume(change) => {
+                let input = self.config.scroll_adjusts_input;
+                let ongoing = self.volume_ramp.filter(|ramp| ramp.input == input);
+                // the sliders only follow the devices while nothing else
+                // moves them, so start from the device unless mid-transition
+                let device = if input {
+                    self.current_input.as_ref()
+                } else {
+                    self.current_output.as_ref()
+                };
+                let current = match (ongoing, input) {
+                    (Some(_), true) => self.input_volume,
+                    (Some(_), false) => self.output_volume,
+                    (None, _) => device.map_or(0.0, |d| volume_to_percent(d.volume.avg())),
+                };
+                // keep scrolling relative to where an ongoing transition ends
+                let from = ongoing.map_or(current, |ramp| ramp.to);
+                let to = (from + change).clamp(0.0, 150.0);
+
+                if self.config.volume_ramp().is_zero() {
+                    return self.update(if input {
+                        Message::SetInputVolume(to)
+                    } else {
+                        Message::SetOutputVolume(to)
+                    });
+                }
+
+                self.volume_ramp = Some(VolumeRamp {
+                    input,
+                    from: current,
+                    to,
+                    start: Instant::now(),
+                });
+                self.animate_volume_ramp();
+            }
             Message::Ignore => {}
             Message::TogglePopup => {
                 if let Some(p) = self.popup.take() {
+                    self.pending_output = None;
+                    self.pending_mute = false;
+                    self.input_peak = 0.0;
+                    self.last_peak = None;
                     return destroy_popup(p);
                 } else {
                     if let Some(conn) = self.pulse_state.connection() {
@@ -338,6 +653,7 @@ impl cosmic::Application for Audio {
                     let new_id = window::Id::unique();
                     self.popup.replace(new_id);
                     self.timeline = Timeline::new();
+                    self.animate_volume_ramp();
 
                     let mut popup_settings = self.core.applet.get_popup_settings(
                         self.core.main_window_id().unwrap(),
@@ -357,18 +673,39 @@ impl cosmic::Application for Audio {
                         conn.send(pulse::Message::GetDefaultSource);
                         conn.send(pulse::Message::GetSinks);
                         conn.send(pulse::Message::GetSources);
+                        conn.send(pulse::Message::GetCommunicationVolume);
+                        conn.send(pulse::Message::GetSinkInputs);
+                        self.resampled_rate = None;
+                        if let Some(output) = self
+                            .current_output
+                            .as_ref()
+                            .filter(|_| self.config.show_resampling_warning)
+                        {
+                            conn.send(pulse::Message::GetResampledRate(
+                                output.index,
+                                output.sample_rate,
+                            ));
+                        }
                     }
 
                     return get_popup(popup_settings);
                 }
             }
             Message::SetOutputVolume(vol) => {
+                if self.volume_ramp.is_some_and(|ramp| !ramp.input) {
+                    self.volume_ramp = None;
+                }
                 if self.output_volume == vol {
                     return Task::none();
                 }
 
                 self.output_volume = vol;
-                self.output_volume_text = format!("{}%", self.output_volume.round());
+                self.output_volume_text = volume_percent(self.output_volume);
+
+                let debounce = self.config.volume_write_debounce();
+                if debounce.is_zero() {
+                    return self.update(Message::ApplyOutputVolume);
+                }
 
                 if self.output_volume_debounce {
                     return Task::none();
@@ -377,17 +714,25 @@ impl cosmic::Application for Audio {
                 self.output_volume_debounce = true;
 
                 return cosmic::task::future(async move {
-                    tokio::time::sleep(Duration::from_millis(64)).await;
+                    tokio::time::sleep(debounce).await;
                     Message::ApplyOutputVolume
                 });
             }
             Message::SetInputVolume(vol) => {
+                if self.volume_ramp.is_some_and(|ramp| ramp.input) {
+                    self.volume_ramp = None;
+                }
                 if self.input_volume == vol {
                     return Task::none();
                 }
 
                 self.input_volume = vol;
-                self.input_volume_text = format!("{}%", self.input_volume.round());
+                self.input_volume_text = volume_percent(self.input_volume);
+
+                let debounce = self.config.volume_write_debounce();
+                if debounce.is_zero() {
+                    return self.update(Message::ApplyInputVolume);
+                }
 
                 if self.input_volume_debounce {
                     return Task::none();
@@ -396,17 +741,16 @@ impl cosmic::Application for Audio {
                 self.input_volume_debounce = true;
 
                 return cosmic::task::future(async move {
-                    tokio::time::sleep(Duration::from_millis(64)).await;
+                    tokio::time::sleep(debounce).await;
                     Message::ApplyInputVolume
                 });
             }
             Message::ApplyOutputVolume => {
                 self.output_volume_debounce = false;
 
-                self.current_output.as_mut().map(|o| {
-                    o.volume
-                        .set(o.volume.len(), percent_to_volume(self.output_volume))
-                });
+                if let Some(output) = self.current_output.as_mut() {
+                    output.set_volume(percent_to_volume(self.output_volume));
+                }
 
                 if let PulseState::Connected(connection) = &mut self.pulse_state {
                     if let Some(device) = &self.current_output {
@@ -439,19 +783,61 @@ impl cosmic::Application for Audio {
                     }
                 }
             }
-            Message::SetOutputMute(mute) => {
+            Message::SetOutputBalance(balance) => {
                 if let Some(output) = self.current_output.as_mut() {
-                    output.mute = mute;
+                    output.set_balance(balance);
+                }
+
+                let debounce = self.config.volume_write_debounce();
+                if debounce.is_zero() {
+                    return self.update(Message::ApplyOutputBalance);
                 }
+
+                if self.output_balance_debounce {
+                    return Task::none();
+                }
+
+                self.output_balance_debounce = true;
+
+                return cosmic::task::future(async move {
+                    tokio::time::sleep(debounce).await;
+                    Message::ApplyOutputBalance
+                });
+            }
+            Message::ApplyOutputBalance => {
+                self.output_balance_debounce = false;
+
                 if let PulseState::Connected(connection) = &mut self.pulse_state {
                     if let Some(device) = &self.current_output {
                         if let Some(name) = &device.name {
-                            connection
-                                .send(pulse::Message::SetSinkMuteByName(name.clone(), device.mute))
+                            connection.send(pulse::Message::SetSinkVolumeByName(
+                                name.clone(),
+                                device.volume,
+                            ))
                         }
                     }
                 }
             }
+            Message::SetOutputMute(mute) => {
+                // streams are only tracked while the popup is open to show the prompt
+                if mute
+                    && self.config.confirm_mute_critical
+                    && self.popup.is_some()
+                    && self.critical_stream().is_some()
+                {
+                    self.pending_mute = true;
+                    return Task::none();
+                }
+                self.pending_mute = false;
+                self.set_output_mute(mute);
+            }
+            Message::ConfirmMute => {
+                self.pending_mute = false;
+                self.set_output_mute(true);
+            }
+            Message::CancelMute => {
+                self.pending_mute = false;
+            }
             Message::SetInputMute(mute) => {
                 if let Some(input) = self.current_input.as_mut() {
                     input.mute = mute;
@@ -467,13 +853,57 @@ impl cosmic::Application for Audio {
                     }
                 }
             }
+            Message::ResetOutputVolume => {
+                let volume = f64::from(self.config.default_volume.min(150));
+                return Task::batch([
+                    self.update(Message::SetOutputMute(false)),
+                    self.update(Message::SetOutputVolume(volume)),
+                ]);
+            }
+            Message::ResetInputVolume => {
+                let volume = f64::from(self.config.default_volume.min(150));
+                return Task::batch([
+                    self.update(Message::SetInputMute(false)),
+                    self.update(Message::SetInputVolume(volume)),
+                ]);
+            }
             Message::OutputChanged(val) => {
+                if self.confirm_output_change(&val) {
+                    self.pending_output = Some(val);
+                    return Task::none();
+                }
+                self.pending_output = None;
                 if let Some(conn) = self.pulse_state.connection() {
                     if let Some(val) = self.outputs.iter().find(|o| o.name.as_ref() == Some(&val)) {
                         conn.send(pulse::Message::SetDefaultSink(val.clone()));
                     }
                 }
             }
+            Message::OutputPortChanged(port) => {
+                if let Some(output) = self.current_output.as_mut() {
+                    output.active_port = Some(port.clone());
+                    if let (Some(name), Some(conn)) =
+                        (output.name.clone(), self.pulse_state.connection())
+                    {
+                        conn.send(pulse::Message::SetSinkPort(name, port));
+                        conn.send(pulse::Message::GetDefaultSink);
+                    }
+                }
+            }
+            Message::ConfirmOutputChange => {
+                // dismiss the banner even if the switch can't be sent
+                let pending = self.pending_output.take();
+                if let Some(conn) = self.pulse_state.connection() {
+                    if let Some(val) = pending
+                        .and_then(|val| self.outputs.iter().find(|o| o.name.as_ref() == Some(&val)))
+                    {
+                        conn.send(pulse::Message::SetDefaultSink(val.clone()));
+                    }
+                }
+            }
+            Message::CancelOutputChange => {
+                self.pending_output = None;
+            }
             Message::InputChanged(val) => {
                 if let Some(conn) = self.pulse_state.connection() {
                     if let Some(val) = self.inputs.iter().find(|i| i.name.as_ref() == Some(&val)) {
@@ -481,6 +911,41 @@ impl cosmic::Application for Audio {
                     }
                 }
             }
+            Message::SetCommunicationVolume(vol) => {
+                self.communication_volume = Some(vol);
+
+                let debounce = self.config.volume_write_debounce();
+                if debounce.is_zero() {
+                    return self.update(Message::ApplyCommunicationVolume);
+                }
+
+                if self.communication_volume_debounce {
+                    return Task::none();
+                }
+
+                self.communication_volume_debounce = true;
+
+                return cosmic::task::future(async move {
+                    tokio::time::sleep(debounce).await;
+                    Message::ApplyCommunicationVolume
+                });
+            }
+            Message::ApplyCommunicationVolume => {
+                self.communication_volume_debounce = false;
+
+                if let (PulseState::Connected(connection), Some(vol)) =
+                    (&mut self.pulse_state, self.communication_volume)
+                {
+                    connection.send(pulse::Message::SetCommunicationVolume(percent_to_volume(
+                        vol,
+                    )));
+                }
+            }
+            Message::SwapInput => {
+                if let Some(name) = self.swap_input_target() {
+                    return self.update(Message::InputChanged(name));
+                }
+            }
             Message::OutputToggle => {
                 self.is_open = if self.is_open == IsOpen::Output {
                     IsOpen::None
@@ -501,6 +966,34 @@ impl cosmic::Application for Audio {
                     IsOpen::Input
                 }
             }
+            Message::ApplicationsToggle => {
+                self.is_open = if self.is_open == IsOpen::Applications {
+                    IsOpen::None
+                } else {
+                    IsOpen::Applications
+                }
+            }
+            Message::RefreshSinkInputs => {
+                if let Some(conn) = self.pulse_state.connection() {
+                    conn.send(pulse::Message::GetSinkInputs);
+                }
+            }
+            Message::SetSinkInputVolume(index, vol) => {
+                if let Some(input) = self.sink_inputs.iter_mut().find(|i| i.index == index) {
+                    input.volume.set(input.volume.len(), percent_to_volume(vol));
+                    if let PulseState::Connected(connection) = &mut self.pulse_state {
+                        connection.send(pulse::Message::SetSinkInputVolume(index, input.volume));
+                    }
+                }
+            }
+            Message::SetSinkInputMute(index, mute) => {
+                if let Some(input) = self.sink_inputs.iter_mut().find(|i| i.index == index) {
+                    input.mute = mute;
+                    if let PulseState::Connected(connection) = &mut self.pulse_state {
+                        connection.send(pulse::Message::SetSinkInputMute(index, mute));
+                    }
+                }
+            }
             Message::Pulse(event) => match event {
                 pulse::Event::Init(mut conn) => {
                     conn.send(pulse::Message::UpdateConnection);
@@ -538,6 +1031,18 @@ impl cosmic::Application for Audio {
                         pulse::Message::SetDefaultSource(source) => {
                             self.update_input(Some(source));
                         }
+                        pulse::Message::CommunicationVolume(volume) => {
+                            self.communication_volume = volume.map(volume_to_percent);
+                        }
+                        pulse::Message::SetSinkInputs(inputs) => {
+                            self.sink_inputs = inputs;
+                            if self.sink_inputs.is_empty() && self.is_open == IsOpen::Applications {
+                                self.is_open = IsOpen::None;
+                            }
+                        }
+                        pulse::Message::ResampledRate(rate) => {
+                            self.resampled_rate = rate;
+                        }
                         pulse::Message::Disconnected => {
                             panic!("Subscription error handling is bad. This should never happen.")
                         }
@@ -570,22 +1075,59 @@ impl cosmic::Application for Audio {
             Message::CloseRequested(id) => {
                 if Some(id) == self.popup {
                     self.popup = None;
+                    self.pending_output = None;
+                    self.pending_mute = false;
+                    self.input_peak = 0.0;
+                    self.last_peak = None;
                 }
             }
             Message::ConfigChanged(c) => {
                 self.config = c;
             }
-            Message::Mpris(mpris_subscription::MprisUpdate::Player(p)) => {
-                self.player_status = Some(p);
+            Message::Mpris(MprisUpdate::Players { players, active }) => {
+                self.players = players;
+                self.active_player = active;
+                self.players_updated = Some(std::time::Instant::now());
+            }
+            Message::Seek(position) => {
+                self.seek_position = Some(position);
+
+                if self.seek_debounce {
+                    return Task::none();
+                }
+
+                self.seek_debounce = true;
+
+                return cosmic::task::future(async move {
+                    tokio::time::sleep(SEEK_DEBOUNCE).await;
+                    Message::ApplySeek
+                });
+            }
+            Message::ApplySeek => {
+                self.seek_debounce = false;
+                if let Some(position) = self.seek_position.take() {
+                    return self.update(Message::MprisRequest(MprisRequest::SetPosition(
+                        Duration::from_secs_f64(position.max(0.0)),
+                    )));
+                }
             }
-            Message::Mpris(MprisUpdate::Finished) => {
-                self.player_status = None;
+            Message::Mpris(MprisUpdate::Finished | MprisUpdate::Setup) => {
+                self.players.clear();
+                self.active_player = None;
             }
-            Message::Mpris(MprisUpdate::Setup) => {
-                self.player_status = None;
+            Message::PlayersToggle => {
+                self.is_open = if self.is_open == IsOpen::Players {
+                    IsOpen::None
+                } else {
+                    IsOpen::Players
+                }
+            }
+            Message::SelectPlayer(name) => {
+                self.selected_player = Some(name);
+                self.is_open = IsOpen::None;
             }
             Message::MprisRequest(r) => {
-                let Some(player_status) = self.player_status.as_ref() else {
+                let Some(player_status) = self.player_status() else {
                     tracing::error!("No player found");
                     return Task::none();
                 };
@@ -616,6 +1158,33 @@ impl cosmic::Application for Audio {
                             tracing::error!("Error playing previous: {}", err);
                         }
                     }),
+                    MprisRequest::SetPosition(position) => {
+                        let Some(track_id) = player_status.track_id.clone() else {
+                            return Task::none();
+                        };
+                        // move the seek bar right away instead of waiting for the player
+                        let name = player_status.name.clone();
+                        let now = std::time::Instant::now();
+                        let elapsed = self.players_updated.map(|t| now - t);
+                        for p in &mut self.players {
+                            if p.name == name {
+                                p.position = Some(position);
+                            } else if let (Some(pos), Some(elapsed), PlaybackStatus::Playing) =
+                                (p.position.as_mut(), elapsed, &p.status)
+                            {
+                                *pos += elapsed;
+                            }
+                        }
+                        self.players_updated = Some(now);
+                        tokio::spawn(async move {
+                            let res =
+                                mpris_subscription::set_position(&player, &track_id, position)
+                                    .await;
+                            if let Err(err) = res {
+                                tracing::error!("Error seeking: {}", err);
+                            }
+                        })
+                    }
                 };
             }
             Message::OpenSettings => {
@@ -646,34 +1215,53 @@ impl cosmic::Application for Audio {
                     tokio::spawn(cosmic::process::spawn(cmd));
                 }
             },
-            Message::PulseSub(event) => match event {
-                sub_pulse::Event::SinkVolume(value) => {
-                    self.current_output.as_mut().map(|output| {
-                        output
-                            .volume
-                            .set(output.volume.len(), percent_to_volume(value as f64))
-                    });
+            Message::PulseSub(sub_pulse::Event::PeakLevel(level)) => {
+                // fall off gradually instead of jumping between peaks
+                if level >= self.input_peak {
+                    self.input_peak = level;
+                    self.last_peak = Some((level, Instant::now()));
+                    self.animate_peak_falloff();
                 }
-                sub_pulse::Event::SinkMute(value) => {
+            }
+            Message::PulseSub(sub_pulse::Event::Settings(event)) => match event {
+                sub_pulse::settings::Event::SinkVolume(value) => {
+                    if let Some(output) = self.current_output.as_mut() {
+                        output.set_volume(percent_to_volume(value as f64));
+                    }
+                }
+                sub_pulse::settings::Event::SinkMute(value) => {
+                    // Muted from elsewhere, e.g. a hardware key or pactl.
                     if let Some(output) = self.current_output.as_mut() {
                         output.mute = value;
+                        if let Some(device) =
+                            self.outputs.iter_mut().find(|o| o.name == output.name)
+                        {
+                            device.mute = value;
+                        }
+                    }
+                    if value {
+                        self.pending_mute = false;
                     }
                 }
-                sub_pulse::Event::SourceVolume(value) => {
+                sub_pulse::settings::Event::SourceVolume(value) => {
                     self.current_input.as_mut().map(|input| {
                         input
                             .volume
                             .set(input.volume.len(), percent_to_volume(value as f64))
                     });
                 }
-                sub_pulse::Event::SourceMute(value) => {
+                sub_pulse::settings::Event::SourceMute(value) => {
                     if let Some(input) = self.current_input.as_mut() {
                         input.mute = value;
+                        if let Some(device) = self.inputs.iter_mut().find(|i| i.name == input.name)
+                        {
+                            device.mute = value;
+                        }
                     }
                 }
-                sub_pulse::Event::DefaultSink(_) => {}
-                sub_pulse::Event::DefaultSource(_) => {}
-                sub_pulse::Event::CardInfo(_) => {}
+                sub_pulse::settings::Event::DefaultSink(_) => {}
+                sub_pulse::settings::Event::DefaultSource(_) => {}
+                sub_pulse::settings::Event::CardInfo(_) => {}
             },
         };
 
@@ -681,7 +1269,39 @@ impl cosmic::Application for Audio {
     }
 
     fn subscription(&self) -> Subscription<Message> {
+        // Streams come and go without an event from the connection, so poll
+        // them while the popup is open.
+        let sink_inputs = if self.popup.is_some() {
+            iced::time::every(Duration::from_secs(1)).map(|_| Message::RefreshSinkInputs)
+        } else {
+            Subscription::none()
+        };
+
+        let input_peak = self
+            .current_input
+            .as_ref()
+            .filter(|_| self.popup.is_some() && self.config.show_input_controls)
+            .filter(|_| !self.current_input_mute())
+            .and_then(|input| input.name.clone())
+            .map_or_else(Subscription::none, |name| {
+                sub_pulse::peak_level(name).map(Message::PulseSub)
+            });
+
+        // keep the seek bar moving while something plays
+        let seek_bar = if self.popup.is_some()
+            && self
+                .player_status()
+                .is_some_and(|s| s.can_seek && matches!(s.status, PlaybackStatus::Playing))
+        {
+            iced::time::every(Duration::from_secs(1)).map(|_| Message::Ignore)
+        } else {
+            Subscription::none()
+        };
+
         Subscription::batch(vec![
+            sink_inputs,
+            seek_bar,
+            input_peak,
             pulse::connect().map(Message::Pulse),
             self.timeline
                 .as_subscription()
@@ -692,7 +1312,8 @@ impl cosmic::Application for Audio {
                 }
                 Message::ConfigChanged(u.config)
             }),
-            mpris_subscription::mpris_subscription(0).map(Message::Mpris),
+            mpris_subscription::mpris_subscription(0, self.config.preferred_player.clone())
+                .map(Message::Mpris),
             activation_token_subscription(0).map(Message::Token),
             sub_pulse::subscription().map(Message::PulseSub),
         ])
@@ -704,21 +1325,22 @@ impl cosmic::Application for Audio {
             .applet
             .icon_button(self.output_icon_name())
             .on_press_down(Message::TogglePopup);
-        let btn = crate::mouse_area::MouseArea::new(btn).on_mouse_wheel(|delta| {
-            let change = match delta {
-                iced::mouse::ScrollDelta::Lines { x, y } => (x + y) * 5.,
-                iced::mouse::ScrollDelta::Pixels { y, .. } => y / 40.3125,
-            };
-            if change.abs() < f32::EPSILON {
-                return Message::Ignore;
-            }
-            let new_volume = self
-                .current_output
-                .as_ref()
-                .map_or(0f64, |v| volume_to_percent(v.volume.avg()) + change as f64)
-                .clamp(0.0, 150.0);
-            Message::SetOutputVolume(new_volume)
-        });
+        let btn = crate::mouse_area::MouseArea::new(btn)
+            .on_middle_press(Message::SetOutputMute(!self.current_output_mute()))
+            .on_mouse_wheel(|delta| {
+                let step = self.config.scroll_step.clamp(1, 25) as f32;
+                let mut change = match delta {
+                    iced::mouse::ScrollDelta::Lines { x, y } => (x + y) * step,
+                    iced::mouse::ScrollDelta::Pixels { y, .. } => y / 40.3125 * step / 5.,
+                };
+                if self.config.invert_scroll {
+                    change = -change;
+                }
+                if change.abs() < f32::EPSILON {
+                    return Message::Ignore;
+                }
+                Message::ScrollVolume(change as f64)
+            });
         let playback_buttons = (!self.core.applet.suggested_bounds.as_ref().is_some_and(|c| {
             // if we have a configure for width and height, we're in a overflow popup
             c.width > 0. && c.height > 0.
@@ -753,6 +1375,13 @@ impl cosmic::Application for Audio {
         let audio_disabled = matches!(self.pulse_state, PulseState::Disconnected(_));
         let out_mute = self.current_output_mute();
         let in_mute = self.current_input_mute();
+        // monitor sources are already filtered out of `inputs`
+        let show_input = self.config.show_input_controls && !self.inputs.is_empty();
+        let vertical = self.config.vertical_sliders
+            && matches!(
+                self.core.applet.anchor,
+                PanelAnchor::Left | PanelAnchor::Right
+            );
 
         let mut audio_content = if audio_disabled {
             column![padded_control(
@@ -760,8 +1389,20 @@ impl cosmic::Application for Audio {
                     .width(Length::Fill)
                     .align_x(Alignment::Center)
             )]
+        } else if self.outputs.is_empty() && self.inputs.is_empty() {
+            column![padded_control(
+                column![
+                    text::title3(fl!("no-devices")),
+                    text::caption(fl!("no-devices-hint")),
+                ]
+                .spacing(space_xxs)
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+            )]
         } else {
-            column![
+            column![if vertical {
+                Element::from(padded_control(self.vertical_volume_controls(show_input)))
+            } else {
                 padded_control(
                     row![
                         button::icon(
@@ -773,17 +1414,99 @@ impl cosmic::Application for Audio {
                         .icon_size(24)
                         .line_height(24)
                         .on_press(Message::SetOutputMute(!out_mute)),
-                        slider(0.0..=150.0, self.output_volume, Message::SetOutputVolume)
-                            .width(Length::FillPortion(5))
-                            .breakpoints(&[100.]),
-                        text(&self.output_volume_text)
+                        crate::mouse_area::MouseArea::new(
+                            slider(0.0..=150.0, self.output_volume, Message::SetOutputVolume)
+                                .width(Length::FillPortion(5))
+                                .breakpoints(&[100.])
+                                .class(volume_slider_class(out_mute)),
+                        )
+                        .on_right_press(Message::ResetOutputVolume),
+                        volume_text(&self.output_volume_text, out_mute)
+                    ]
+                    .spacing(12)
+                    .align_y(Alignment::Center),
+                )
+                .into()
+            }]
+            .push_maybe(
+                self.critical_stream()
+                    .filter(|_| self.pending_mute)
+                    .map(|stream| {
+                        padded_control(
+                            row![
+                                text::body(fl!("confirm-mute", app = stream.name.as_str()))
+                                    .width(Length::Fill),
+                                button::text(fl!("cancel")).on_press(Message::CancelMute),
+                                button::destructive(fl!("mute")).on_press(Message::ConfirmMute),
+                            ]
+                            .spacing(space_xxs)
+                            .align_y(Alignment::Center),
+                        )
+                    }),
+            )
+            .push_maybe(
+                self.current_output
+                    .as_ref()
+                    .filter(|output| output.channel_map.can_balance())
+                    .map(|output| {
+                        padded_control(
+                            row![
+                                text::body(fl!("balance")).width(Length::FillPortion(1)),
+                                slider(-1.0..=1.0, output.balance(), Message::SetOutputBalance)
+                                    .step(0.01)
+                                    .width(Length::FillPortion(4))
+                                    .breakpoints(&[0.]),
+                                button::text(fl!("balance-center"))
+                                    .on_press(Message::SetOutputBalance(0.0)),
+                            ]
+                            .spacing(12)
+                            .align_y(Alignment::Center),
+                        )
+                    }),
+            )
+            .push_maybe(
+                self.current_output
+                    .as_ref()
+                    .zip(self.resampled_rate)
+                    .filter(|_| self.config.show_resampling_warning)
+                    .map(|(output, rate)| {
+                        padded_control(
+                            row![
+                                icon::from_name("dialog-warning-symbolic")
+                                    .size(16)
+                                    .symbolic(true),
+                                text::caption(fl!(
+                                    "resampling",
+                                    stream = format_rate(rate),
+                                    device = format_rate(output.sample_rate)
+                                )),
+                            ]
+                            .spacing(8)
+                            .align_y(Alignment::Center),
+                        )
+                    }),
+            )
+            .push_maybe(self.communication_volume.map(|volume| {
+                padded_control(
+                    row![
+                        icon::from_name("call-start-symbolic")
+                            .size(24)
+                            .symbolic(true),
+                        column![
+                            text::caption(fl!("call-volume")),
+                            slider(0.0..=100.0, volume, Message::SetCommunicationVolume),
+                        ]
+                        .width(Length::FillPortion(5)),
+                        text(volume_percent(volume))
                             .size(16)
                             .width(Length::FillPortion(1))
-                            .align_x(Alignment::End)
+                            .align_x(Alignment::End),
                     ]
                     .spacing(12)
-                    .align_y(Alignment::Center)
-                ),
+                    .align_y(Alignment::Center),
+                )
+            }))
+            .push_maybe((show_input && !vertical).then(|| {
                 padded_control(
                     row![
                         button::icon(
@@ -795,36 +1518,84 @@ impl cosmic::Application for Audio {
                         .icon_size(24)
                         .line_height(24)
                         .on_press(Message::SetInputMute(!in_mute)),
-                        slider(0.0..=150.0, self.input_volume, Message::SetInputVolume)
-                            .width(Length::FillPortion(5))
-                            .breakpoints(&[100.]),
-                        text(&self.input_volume_text)
-                            .size(16)
-                            .width(Length::FillPortion(1))
-                            .align_x(Alignment::End)
+                        crate::mouse_area::MouseArea::new(
+                            column![
+                                slider(0.0..=150.0, self.input_volume, Message::SetInputVolume)
+                                    .breakpoints(&[100.])
+                                    .class(volume_slider_class(in_mute)),
+                                widget::progress_bar(
+                                    0.0..=1.0,
+                                    if in_mute { 0.0 } else { self.input_peak },
+                                )
+                                .height(Length::Fixed(4.0)),
+                            ]
+                            .spacing(4)
+                            .width(Length::FillPortion(5)),
+                        )
+                        .on_right_press(Message::ResetInputVolume),
+                        volume_text(&self.input_volume_text, in_mute)
                     ]
+                    .push_maybe(self.swap_input_target().map(|_| {
+                        button::icon(
+                            icon::from_name("media-playlist-repeat-symbolic")
+                                .size(16)
+                                .symbolic(true),
+                        )
+                        .class(cosmic::theme::Button::Icon)
+                        .on_press(Message::SwapInput)
+                    }))
                     .spacing(12)
-                    .align_y(Alignment::Center)
-                ),
-                padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
-                revealer(
-                    self.is_open == IsOpen::Output,
-                    fl!("output"),
-                    match &self.current_output {
-                        Some(output) => pretty_name(output.description.clone()),
-                        None => String::from("No device selected"),
-                    },
-                    self.outputs
-                        .clone()
-                        .into_iter()
-                        .map(|output| (
+                    .align_y(Alignment::Center),
+                )
+            }))
+            .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]))
+            .push(revealer(
+                self.is_open == IsOpen::Output,
+                fl!("output"),
+                match &self.current_output {
+                    Some(output) => pretty_name(output.description.clone()),
+                    None => String::from("No device selected"),
+                },
+                self.outputs
+                    .clone()
+                    .into_iter()
+                    .map(|output| {
+                        (
                             output.name.clone().unwrap_or_default(),
-                            pretty_name(output.description)
+                            pretty_name(output.description),
+                        )
+                    })
+                    .collect(),
+                Message::OutputToggle,
+                Message::OutputChanged,
+            ))
+            .push_maybe(
+                self.current_output
+                    .as_ref()
+                    .filter(|_| self.is_open == IsOpen::Output)
+                    .map(|output| output_ports(output, space_xxs)),
+            )
+            .push_maybe(self.pending_output.as_ref().map(|name| {
+                let description = self
+                    .outputs
+                    .iter()
+                    .find(|o| o.name.as_ref() == Some(name))
+                    .and_then(|o| o.description.clone());
+                padded_control(
+                    row![
+                        text::body(fl!(
+                            "confirm-bluetooth-output",
+                            device = pretty_name(description)
                         ))
-                        .collect(),
-                    Message::OutputToggle,
-                    Message::OutputChanged,
-                ),
+                        .width(Length::Fill),
+                        button::text(fl!("cancel")).on_press(Message::CancelOutputChange),
+                        button::suggested(fl!("switch")).on_press(Message::ConfirmOutputChange),
+                    ]
+                    .spacing(space_xxs)
+                    .align_y(Alignment::Center),
+                )
+            }))
+            .push_maybe(show_input.then(|| {
                 revealer(
                     self.is_open == IsOpen::Input,
                     fl!("input"),
@@ -835,21 +1606,56 @@ impl cosmic::Application for Audio {
                     self.inputs
                         .clone()
                         .into_iter()
-                        .map(|input| (
-                            input.name.clone().unwrap_or_default(),
-                            pretty_name(input.description)
-                        ))
+                        .map(|input| {
+                            (
+                                input.name.clone().unwrap_or_default(),
+                                pretty_name(input.description),
+                            )
+                        })
                         .collect(),
                     Message::InputToggle,
                     Message::InputChanged,
                 )
-            ]
+            }))
+            .push_maybe((!self.sink_inputs.is_empty()).then(|| {
+                self.sink_inputs.iter().fold(
+                    column![revealer_head(
+                        self.is_open == IsOpen::Applications,
+                        fl!("applications"),
+                        fl!("applications-playing", count = self.sink_inputs.len()),
+                        Message::ApplicationsToggle,
+                    )]
+                    .width(Length::Fill),
+                    |col, input| {
+                        if self.is_open != IsOpen::Applications {
+                            return col;
+                        }
+                        col.push(sink_input_control(input))
+                    },
+                )
+            }))
             .align_x(Alignment::Start)
         };
 
-        if let Some(s) = self.player_status.as_ref() {
+        if let Some(s) = self.player_status() {
             let mut elements = Vec::with_capacity(5);
 
+            audio_content = audio_content
+                .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]));
+            if self.players.len() > 1 {
+                audio_content = audio_content.push(revealer(
+                    self.is_open == IsOpen::Players,
+                    fl!("media-player"),
+                    s.identity.clone(),
+                    self.players
+                        .iter()
+                        .map(|p| (p.name.clone(), p.identity.clone()))
+                        .collect(),
+                    Message::PlayersToggle,
+                    Message::SelectPlayer,
+                ));
+            }
+
             if let Some(icon_path) = s.icon.clone() {
                 elements.push(icon(icon::from_path(icon_path)).size(36).into());
             }
@@ -922,14 +1728,29 @@ impl cosmic::Application for Audio {
                     .into(),
             );
 
-            audio_content = audio_content
-                .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]));
             audio_content = audio_content.push(
                 Row::with_children(elements)
                     .align_y(Alignment::Center)
                     .spacing(8)
                     .padding(menu_control_padding()),
             );
+            if let Some((position, length)) = self
+                .player_position(s)
+                .zip(s.length)
+                .filter(|_| s.can_seek && s.track_id.is_some())
+            {
+                let position = self.seek_position.unwrap_or_else(|| position.as_secs_f64());
+                audio_content = audio_content.push(padded_control(
+                    row![
+                        text::caption(format_position(Duration::from_secs_f64(position))),
+                        slider(0.0..=length.as_secs_f64(), position, Message::Seek)
+                            .width(Length::Fill),
+                        text::caption(format_position(length)),
+                    ]
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+                ));
+            }
         }
         let content = column![
             audio_content,
@@ -1002,6 +1823,140 @@ fn revealer_head(
     .on_press(toggle)
 }
 
+/// Ports of the current output, hiding those Pulse reports as unplugged.
+/// Nothing is shown unless there is more than one to choose from.
+fn output_ports(output: &DeviceInfo, spacing: u16) -> Column<'_, Message> {
+    let ports: Vec<_> = output.ports.iter().filter(|p| p.available).collect();
+    if ports.len() < 2 {
+        return Column::new();
+    }
+
+    ports
+        .into_iter()
+        .fold(Column::new().width(Length::Fill), |col, port| {
+            let active = output.active_port.as_ref() == Some(&port.name);
+            col.push(
+                menu_button(
+                    row![text::body(port.description.as_str()).width(Length::Fill)]
+                        .push_maybe(active.then(|| {
+                            icon::from_name("emblem-ok-symbolic")
+                                .size(12)
+                                .symbolic(true)
+                        }))
+                        .spacing(spacing)
+                        .align_y(Alignment::Center),
+                )
+                .on_press(Message::OutputPortChanged(port.name.clone()))
+                .width(Length::Fill)
+                .padding([8, 48]),
+            )
+        })
+}
+
+/// Volume slider and mute button of an application's stream.
+fn sink_input_control(input: &SinkInputInfo) -> Element<'_, Message> {
+    let volume = volume_to_percent(input.volume.avg());
+    let index = input.index;
+    padded_control(
+        row![
+            icon::from_name(
+                input
+                    .icon_name
+                    .as_deref()
+                    .unwrap_or("application-x-executable-symbolic"),
+            )
+            .size(24),
+            column![
+                text::caption(input.name.as_str()),
+                slider(0.0..=150.0, volume, move |vol| {
+                    Message::SetSinkInputVolume(index, vol)
+                })
+                .breakpoints(&[100.])
+                .class(volume_slider_class(input.mute)),
+            ]
+            .width(Length::FillPortion(5)),
+            volume_text(volume_percent(volume), input.mute),
+            button::icon(
+                icon::from_name(if input.mute {
+                    "audio-volume-muted-symbolic"
+                } else {
+                    "audio-volume-high-symbolic"
+                })
+                .size(16)
+                .symbolic(true),
+            )
+            .class(cosmic::theme::Button::Icon)
+            .on_press(Message::SetSinkInputMute(index, !input.mute)),
+        ]
+        .spacing(12)
+        .align_y(Alignment::Center),
+    )
+    .into()
+}
+
+/// Style of a volume slider, dimmed like its level while the device is muted.
+fn volume_slider_class(muted: bool) -> theme::iced::Slider {
+    if !muted {
+        return theme::iced::Slider::Standard;
+    }
+    let dimmed = |status: slider::Status| -> Rc<dyn Fn(&Theme) -> slider::Style> {
+        Rc::new(move |theme| {
+            let mut style = slider::Catalog::style(theme, &theme::iced::Slider::Standard, status);
+            let color = iced::Background::Color(theme.cosmic().palette.neutral_6.into());
+            style.rail.backgrounds.0 = color;
+            style.handle.background = color;
+            style
+        })
+    };
+    theme::iced::Slider::Custom {
+        active: dimmed(slider::Status::Active),
+        hovered: dimmed(slider::Status::Hovered),
+        dragging: dimmed(slider::Status::Dragged),
+    }
+}
+
+/// Volume level next to a slider. A muted device keeps showing its level, dimmed,
+/// so that it isn't mistaken for a volume of 0.
+fn volume_text<'a>(
+    volume: impl Into<Cow<'a, str>>,
+    muted: bool,
+) -> widget::Text<'a, Theme, Renderer> {
+    let volume = text(volume.into())
+        .size(16)
+        .width(Length::FillPortion(1))
+        .align_x(Alignment::End);
+    if muted {
+        volume.class(theme::Text::Custom(|theme| widget::text::Style {
+            color: Some(theme.cosmic().palette.neutral_6.into()),
+        }))
+    } else {
+        volume
+    }
+}
+
+/// A volume level as a percentage, in the format of the current locale.
+fn volume_percent(volume: f64) -> String {
+    fl!(
+        "volume-percent",
+        percent = localize::format_number(volume.round() as i64)
+    )
+}
+
+/// `m:ss`, or `h:mm:ss` for positions of an hour or more.
+fn format_position(position: Duration) -> String {
+    let secs = position.as_secs();
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{secs:02}")
+    } else {
+        format!("{minutes}:{secs:02}")
+    }
+}
+
+fn format_rate(rate: u32) -> String {
+    format!("{} kHz", f64::from(rate) / 1000.)
+}
+
 fn pretty_name(name: Option<String>) -> String {
     match name {
         Some(n) => n,
@@ -1052,3 +2007,44 @@ fn volume_to_percent(volume: Volume) -> f64 {
 fn percent_to_volume(percent: f64) -> Volume {
     Volume((percent / 100. * FULL_VOLUME).clamp(0., MAX_VOLUME).round() as u32)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmic::Application;
+    use libpulse_binding::{channelmap, volume::ChannelVolumes};
+
+    fn speakers() -> DeviceInfo {
+        let mut volume = ChannelVolumes::default();
+        volume.set(2, Volume::NORMAL);
+        DeviceInfo {
+            name: Some("speakers".to_string()),
+            description: Some("Speakers".to_string()),
+            volume,
+            channel_map: channelmap::Map::default(),
+            mute: false,
+            index: 0,
+            sample_rate: 48000,
+            ports: Vec::new(),
+            active_port: None,
+        }
+    }
+
+    #[test]
+    fn external_mute_updates_panel_and_popup() {
+        let mut audio = Audio {
+            outputs: vec![speakers()],
+            ..Default::default()
+        };
+        audio.update_output(Some(speakers()));
+        assert_eq!(audio.output_icon_name(), "audio-volume-high-symbolic");
+
+        let _ = audio.update(Message::PulseSub(sub_pulse::Event::Settings(
+            sub_pulse::settings::Event::SinkMute(true),
+        )));
+
+        assert!(audio.current_output_mute());
+        assert_eq!(audio.output_icon_name(), "audio-volume-muted-symbolic");
+        assert!(audio.outputs[0].mute);
+    }
+}
@@ -2,10 +2,15 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use cosmic_time::once_cell::sync::Lazy;
+use fixed_decimal::FixedDecimal;
 use i18n_embed::{
     fluent::{fluent_language_loader, FluentLanguageLoader},
     DefaultLocalizer, LanguageLoader, Localizer,
 };
+use icu::{
+    decimal::{options::FixedDecimalFormatterOptions, FixedDecimalFormatter},
+    locid::Locale,
+};
 use rust_embed::RustEmbed;
 
 #[derive(RustEmbed)]
@@ -22,6 +27,23 @@ pub static LANGUAGE_LOADER: Lazy<FluentLanguageLoader> = Lazy::new(|| {
     loader
 });
 
+/// Number formatter for the language the applet is shown in, once it is selected.
+static NUMBER_FORMATTER: Lazy<Option<FixedDecimalFormatter>> =
+    Lazy::new(|| number_formatter(&LANGUAGE_LOADER.current_language().to_string()));
+
+fn number_formatter(language: &str) -> Option<FixedDecimalFormatter> {
+    let locale: Locale = language.parse().ok()?;
+    FixedDecimalFormatter::try_new(&locale.into(), FixedDecimalFormatterOptions::default()).ok()
+}
+
+/// An integer with the digits and grouping of the current language.
+pub fn format_number(n: i64) -> String {
+    match NUMBER_FORMATTER.as_ref() {
+        Some(formatter) => formatter.format_to_string(&FixedDecimal::from(n)),
+        None => n.to_string(),
+    }
+}
+
 #[macro_export]
 macro_rules! fl {
     ($message_id:literal) => {{
@@ -46,3 +68,21 @@ pub fn localize() {
         eprintln!("Error while loading language for App List {}", error);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_numbers_with_locale_digits() {
+        let format = |language, n: i64| {
+            number_formatter(language)
+                .unwrap()
+                .format_to_string(&FixedDecimal::from(n))
+        };
+        assert_eq!(format("en", 50), "50");
+        assert_eq!(format("ar", 50), "٥٠");
+        assert_eq!(format("fa", 150), "۱۵۰");
+        assert_eq!(format("en", 1500), "1,500");
+    }
+}
@@ -1,11 +1,13 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::{borrow::Cow, fmt::Debug, hash::Hash, path::PathBuf};
+use std::{
+    borrow::Cow, collections::HashMap, fmt::Debug, hash::Hash, path::PathBuf, time::Duration,
+};
 
 use cosmic::{
     iced::{self, stream, Subscription},
-    iced_futures::futures::{self, future::OptionFuture, SinkExt, StreamExt},
+    iced_futures::futures::{self, SinkExt, StreamExt},
 };
 use mpris2_zbus::{
     enumerator,
@@ -16,12 +18,17 @@ use tokio::join;
 use urlencoding::decode;
 use zbus::{
     names::{BusName, OwnedBusName},
+    zvariant::{OwnedObjectPath, OwnedValue, Value},
     Connection,
 };
 
 #[derive(Clone, Debug)]
 pub struct PlayerStatus {
     pub player: Player,
+    /// Bus name of the player.
+    pub name: String,
+    /// Human readable name of the player, falling back to its bus name.
+    pub identity: String,
     pub icon: Option<PathBuf>,
     pub title: Option<Cow<'static, str>>,
     pub artists: Option<Vec<Cow<'static, str>>>,
@@ -30,10 +37,30 @@ pub struct PlayerStatus {
     pub can_play: bool,
     pub can_go_previous: bool,
     pub can_go_next: bool,
+    pub can_seek: bool,
+    /// Playback position when the status was read.
+    pub position: Option<Duration>,
+    /// Length of the current track.
+    pub length: Option<Duration>,
+    /// `mpris:trackid` of the current track, needed to set the position.
+    pub track_id: Option<OwnedObjectPath>,
 }
 
 impl PlayerStatus {
-    async fn new(player: Player) -> Option<Self> {
+    async fn new(mpris_player: &MprisPlayer) -> Option<Self> {
+        let player = mpris_player.player.clone();
+        let name = mpris_player.name().to_string();
+        let identity = mpris_player
+            .media_player
+            .identity()
+            .await
+            .ok()
+            .filter(|i| !i.is_empty())
+            .unwrap_or_else(|| {
+                name.strip_prefix("org.mpris.MediaPlayer2.")
+                    .unwrap_or(&name)
+                    .to_string()
+            });
         let metadata = player.metadata().await.ok()?;
         let pathname = metadata.url().unwrap_or("".into());
         let pathbuf = PathBuf::from(pathname);
@@ -52,12 +79,37 @@ impl PlayerStatus {
             .art_url()
             .and_then(|u| url::Url::parse(&u).ok())
             .and_then(|u| {
-                if u.scheme() == "file" {
-                    u.to_file_path().ok()
-                } else {
-                    None
+                if u.scheme() != "file" {
+                    return None;
                 }
-            });
+                // `to_file_path` rejects URLs with a host other than localhost
+                u.to_file_path().ok().or_else(|| {
+                    decode(u.path())
+                        .ok()
+                        .map(|path| PathBuf::from(path.into_owned()))
+                })
+            })
+            .filter(|path| path.exists());
+
+        // Position, length and track id are read from the raw properties, as
+        // they are plain integers and object paths on the bus.
+        let proxy = player.inner();
+        let (raw_metadata, position, can_seek) = join!(
+            proxy.get_property::<HashMap<String, OwnedValue>>("Metadata"),
+            proxy.get_property::<i64>("Position"),
+            proxy.get_property::<bool>("CanSeek"),
+        );
+        let raw_metadata = raw_metadata.unwrap_or_default();
+        let length = raw_metadata.get("mpris:length").and_then(|v| micros(v));
+        let track_id = raw_metadata.get("mpris:trackid").and_then(|v| match &**v {
+            Value::ObjectPath(path) => Some(path.clone().into()),
+            Value::Str(path) => OwnedObjectPath::try_from(path.as_str()).ok(),
+            _ => None,
+        });
+        let position = position
+            .ok()
+            .and_then(|us| u64::try_from(us).ok())
+            .map(Duration::from_micros);
 
         let (playback_status, can_pause, can_play, can_go_previous, can_go_next) = join!(
             player.playback_status(),
@@ -67,6 +119,8 @@ impl PlayerStatus {
             player.can_go_next()
         );
         Some(Self {
+            name,
+            identity,
             icon,
             title,
             artists,
@@ -75,18 +129,46 @@ impl PlayerStatus {
             can_play: can_play.unwrap_or_default(),
             can_go_previous: can_go_previous.unwrap_or_default(),
             can_go_next: can_go_next.unwrap_or_default(),
+            can_seek: can_seek.unwrap_or_default(),
+            position,
+            length,
+            track_id,
             player,
         })
     }
 }
 
+/// A duration in microseconds, which players send as either signed or unsigned.
+fn micros(value: &Value) -> Option<Duration> {
+    i64::try_from(value)
+        .ok()
+        .and_then(|us| u64::try_from(us).ok())
+        .or_else(|| u64::try_from(value).ok())
+        .map(Duration::from_micros)
+}
+
+/// Move playback of `track_id` to `position`.
+pub async fn set_position(
+    player: &Player,
+    track_id: &OwnedObjectPath,
+    position: Duration,
+) -> zbus::Result<()> {
+    let position = i64::try_from(position.as_micros()).unwrap_or(i64::MAX);
+    player
+        .inner()
+        .call_method("SetPosition", &(track_id, position))
+        .await
+        .map(|_| ())
+}
+
 pub fn mpris_subscription<I: 'static + Hash + Copy + Send + Sync + Debug>(
     id: I,
+    preferred_player: Option<String>,
 ) -> iced::Subscription<MprisUpdate> {
     Subscription::run_with_id(
-        id,
+        (id, preferred_player.clone()),
         stream::channel(50, move |mut output| async move {
-            run(&mut output).await;
+            run(&mut output, preferred_player).await;
             let _ = output.send(MprisUpdate::Finished).await;
             futures::future::pending().await
         }),
@@ -96,7 +178,6 @@ pub fn mpris_subscription<I: 'static + Hash + Copy + Send + Sync + Debug>(
 #[derive(Clone, Debug)]
 struct MprisPlayer {
     player: Player,
-    #[allow(dead_code)]
     media_player: MediaPlayer,
 }
 
@@ -111,12 +192,30 @@ impl MprisPlayer {
     fn name(&self) -> &BusName {
         self.player.inner().destination()
     }
+
+    /// Matches the player by its bus name suffix, `Identity`, or `DesktopEntry`.
+    async fn is(&self, preferred: &str) -> bool {
+        if self.name().strip_prefix("org.mpris.MediaPlayer2.") == Some(preferred) {
+            return true;
+        }
+        let (identity, desktop_entry) = join!(
+            self.media_player.identity(),
+            self.media_player.desktop_entry()
+        );
+        identity.is_ok_and(|i| i.eq_ignore_ascii_case(preferred))
+            || desktop_entry.is_ok_and(|d| d == preferred)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum MprisUpdate {
     Setup,
-    Player(PlayerStatus),
+    /// Every running player, and the index of the one the controls should
+    /// follow unless the user picked another.
+    Players {
+        players: Vec<PlayerStatus>,
+        active: Option<usize>,
+    },
     Finished,
 }
 
@@ -126,6 +225,7 @@ pub enum MprisRequest {
     Pause,
     Next,
     Previous,
+    SetPosition(Duration),
 }
 
 struct State {
@@ -133,9 +233,9 @@ struct State {
     enumerator_stream:
         Box<dyn futures::Stream<Item = zbus::Result<enumerator::Event>> + Unpin + Send>,
     players: Vec<MprisPlayer>,
-    active_player: Option<MprisPlayer>,
-    active_player_metadata_stream: Option<Box<dyn futures::Stream<Item = ()> + Unpin + Send>>,
-    any_player_state_stream: futures::stream::SelectAll<zbus::PropertyStream<'static, String>>,
+    any_player_changed_stream:
+        futures::stream::SelectAll<Box<dyn futures::Stream<Item = ()> + Unpin + Send>>,
+    preferred_player: Option<String>,
 }
 
 fn filter_firefox_players(players: &mut Vec<MprisPlayer>) {
@@ -148,7 +248,7 @@ fn filter_firefox_players(players: &mut Vec<MprisPlayer>) {
 }
 
 impl State {
-    async fn new() -> Result<Self, zbus::Error> {
+    async fn new(preferred_player: Option<String>) -> Result<Self, zbus::Error> {
         let conn = Connection::session().await?;
 
         let enumerator = enumerator::Enumerator::new(&conn).await?;
@@ -175,12 +275,10 @@ impl State {
             conn,
             enumerator_stream: Box::new(enumerator_stream),
             players,
-            active_player: None,
-            active_player_metadata_stream: None,
-            any_player_state_stream: futures::stream::select_all(Vec::new()),
+            any_player_changed_stream: futures::stream::select_all(Vec::new()),
+            preferred_player,
         };
-        state.update_active_player().await;
-        state.update_any_player_state_stream().await;
+        state.update_any_player_changed_stream().await;
         Ok(state)
     }
 
@@ -195,50 +293,58 @@ impl State {
         self.players.push(player);
         filter_firefox_players(&mut self.players);
         self.players.sort_by(|a, b| a.name().cmp(&b.name()));
-        self.update_any_player_state_stream().await;
+        self.update_any_player_changed_stream().await;
     }
 
     async fn remove_player(&mut self, name: OwnedBusName) {
         if let Some(idx) = self.players.iter().position(|p| p.name() == &name) {
             self.players.remove(idx);
         }
-        self.update_any_player_state_stream().await;
+        self.update_any_player_changed_stream().await;
     }
 
-    async fn update_active_player(&mut self) {
-        let new_active_player = find_active(&self.players).await;
-        if self.active_player.as_ref().map(|p| p.name()) != new_active_player.map(|p| p.name()) {
-            self.active_player = new_active_player.cloned();
-            if let Some(player) = new_active_player {
-                let controls_changed = futures::stream::select_all([
-                    player.player.receive_can_pause_changed().await,
-                    player.player.receive_can_play_changed().await,
-                    player.player.receive_can_go_previous_changed().await,
-                    player.player.receive_can_go_next_changed().await,
-                ]);
-                let metadata_changed = player.player.receive_metadata_changed().await;
-                let stream = futures::stream::select(
-                    controls_changed.map(|_| ()),
-                    metadata_changed.map(|_| ()),
-                );
-                self.active_player_metadata_stream = Some(Box::new(stream));
-            } else {
-                self.active_player_metadata_stream = None;
-            }
+    async fn update_any_player_changed_stream(&mut self) {
+        let mut listeners: Vec<Box<dyn futures::Stream<Item = ()> + Unpin + Send>> =
+            Vec::with_capacity(self.players.len());
+        for p in &self.players {
+            let controls_changed = futures::stream::select_all([
+                p.player.receive_can_pause_changed().await,
+                p.player.receive_can_play_changed().await,
+                p.player.receive_can_go_previous_changed().await,
+                p.player.receive_can_go_next_changed().await,
+            ]);
+            let metadata_changed = p.player.receive_metadata_changed().await;
+            let status_changed = p.player.receive_playback_status_changed().await;
+            listeners.push(Box::new(futures::stream::select(
+                futures::stream::select(controls_changed.map(|_| ()), metadata_changed.map(|_| ())),
+                status_changed.map(|_| ()),
+            )));
         }
+        self.any_player_changed_stream = futures::stream::select_all(listeners);
     }
 
-    async fn update_any_player_state_stream(&mut self) {
-        let mut listeners = Vec::with_capacity(self.players.len());
+    async fn players(&self) -> (Vec<PlayerStatus>, Option<usize>) {
+        let active = find_active(&self.players, self.preferred_player.as_deref())
+            .await
+            .map(|p| p.name().to_string());
+        let mut players = Vec::with_capacity(self.players.len());
         for p in &self.players {
-            listeners.push(p.player.receive_playback_status_changed().await);
+            if let Some(status) = PlayerStatus::new(p).await {
+                players.push(status);
+            } else {
+                tracing::error!("Failed to get player status.");
+            }
         }
-        self.any_player_state_stream = futures::stream::select_all(listeners);
+        let active = active.and_then(|name| players.iter().position(|p| p.name == name));
+        (players, active)
     }
 }
 
-async fn run(output: &mut futures::channel::mpsc::Sender<MprisUpdate>) {
-    let mut state = match State::new().await {
+async fn run(
+    output: &mut futures::channel::mpsc::Sender<MprisUpdate>,
+    preferred_player: Option<String>,
+) {
+    let mut state = match State::new(preferred_player).await {
         Ok(state) => state,
         Err(err) => {
             tracing::error!("Faile do monitor for mpris clients: {}", err);
@@ -247,25 +353,14 @@ async fn run(output: &mut futures::channel::mpsc::Sender<MprisUpdate>) {
     };
 
     loop {
-        if let Some(player) = &state.active_player {
-            if let Some(player_status) = PlayerStatus::new(player.player.clone()).await {
-                _ = output.send(MprisUpdate::Player(player_status)).await;
-            } else {
-                tracing::error!("Failed to get player status.");
-            };
-        } else {
+        if state.players.is_empty() {
             let _ = output.send(MprisUpdate::Setup).await;
+        } else {
+            let (players, active) = state.players().await;
+            _ = output.send(MprisUpdate::Players { players, active }).await;
         }
 
-        let metadata_changed_next = OptionFuture::from(
-            state
-                .active_player_metadata_stream
-                .as_mut()
-                .map(|s| s.next()),
-        );
         tokio::select! {
-            _ = metadata_changed_next, if state.active_player.is_some() => {
-            },
             event = state.enumerator_stream.next() => {
                 match event {
                     Some(Ok(enumerator::Event::Add(name))) => state.add_player(name).await,
@@ -276,16 +371,25 @@ async fn run(output: &mut futures::channel::mpsc::Sender<MprisUpdate>) {
                     }
                     None => {}
                 }
-                state.update_active_player().await;
             }
-            _ = state.any_player_state_stream.next(), if !state.players.is_empty() => {
-                state.update_active_player().await;
-            },
+            _ = state.any_player_changed_stream.next(), if !state.players.is_empty() => {},
         };
     }
 }
 
-async fn find_active<'a>(players: &'a Vec<MprisPlayer>) -> Option<&'a MprisPlayer> {
+async fn find_active<'a>(
+    players: &'a Vec<MprisPlayer>,
+    preferred: Option<&str>,
+) -> Option<&'a MprisPlayer> {
+    // the preferred player is followed whenever it is running
+    if let Some(preferred) = preferred {
+        for p in players.iter() {
+            if p.is(preferred).await {
+                return Some(p);
+            }
+        }
+    }
+
     let mut best = (0, None::<&'a MprisPlayer>);
     let eval = |p: Player| async move {
         let v = {
@@ -1,7 +1,7 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::{cell::RefCell, mem, rc::Rc, thread};
+use std::{cell::RefCell, mem, rc::Rc, thread, time::Duration};
 
 extern crate libpulse_binding as pulse;
 
@@ -13,14 +13,18 @@ use cosmic_time::once_cell::sync::Lazy;
 
 use libpulse_binding::{
     callbacks::ListResult,
+    channelmap,
     context::{
         introspect::{Introspector, SinkInfo, SourceInfo},
         Context,
     },
+    def::{BufferAttr, PortAvailable},
     error::PAErr,
     mainloop::standard::{IterateResult, Mainloop},
-    proplist::Proplist,
-    volume::ChannelVolumes,
+    proplist::{self, Proplist},
+    sample,
+    stream::{PeekResult, Stream},
+    volume::{ChannelVolumes, Volume},
 };
 
 use tokio::sync::{mpsc, Mutex};
@@ -43,6 +47,101 @@ pub fn connect() -> iced::Subscription<Event> {
     )
 }
 
+/// Peak level of a source, between 0 and 1, about 25 times a second.
+///
+/// The monitor stream runs on its own thread and stops once the
+/// subscription is dropped.
+pub fn peak_level(source: String) -> iced::Subscription<f32> {
+    struct PeakLevel;
+
+    Subscription::run_with_id(
+        (std::any::TypeId::of::<PeakLevel>(), source.clone()),
+        stream::channel(50, move |output| async move {
+            let (done_tx, done_rx) = futures::channel::oneshot::channel::<()>();
+            thread::spawn(move || {
+                if monitor_peaks(&source, output).is_none() {
+                    tracing::warn!("Peak level monitoring of {source} stopped");
+                }
+                _ = done_tx.send(());
+            });
+            _ = done_rx.await;
+            futures::future::pending::<()>().await;
+        }),
+    )
+}
+
+fn monitor_peaks(source: &str, mut output: futures::channel::mpsc::Sender<f32>) -> Option<()> {
+    let mut mainloop = Mainloop::new()?;
+    let mut context = Context::new(&mainloop, "PeakLevel")?;
+    context
+        .connect(None, pulse::context::FlagSet::NOFLAGS, None)
+        .ok()?;
+    loop {
+        if !matches!(mainloop.iterate(true), IterateResult::Success(_)) {
+            return None;
+        }
+        match context.get_state() {
+            pulse::context::State::Ready => break,
+            pulse::context::State::Failed | pulse::context::State::Terminated => return None,
+            _ => {}
+        }
+    }
+
+    let spec = sample::Spec {
+        format: sample::Format::FLOAT32NE,
+        channels: 1,
+        rate: 25,
+    };
+    let mut stream = Stream::new(&mut context, "Peak detect", &spec, None)?;
+    // one sample per fragment, so every peak is delivered right away
+    let attr = BufferAttr {
+        maxlength: u32::MAX,
+        tlength: u32::MAX,
+        prebuf: u32::MAX,
+        minreq: u32::MAX,
+        fragsize: mem::size_of::<f32>() as u32,
+    };
+    stream
+        .connect_record(
+            Some(source),
+            Some(&attr),
+            pulse::stream::FlagSet::PEAK_DETECT
+                | pulse::stream::FlagSet::ADJUST_LATENCY
+                | pulse::stream::FlagSet::DONT_MOVE,
+        )
+        .ok()?;
+
+    loop {
+        if !matches!(mainloop.iterate(true), IterateResult::Success(_)) {
+            return None;
+        }
+        match stream.get_state() {
+            pulse::stream::State::Ready => {}
+            pulse::stream::State::Failed | pulse::stream::State::Terminated => return None,
+            _ => continue,
+        }
+        while stream.readable_size().is_some_and(|size| size > 0) {
+            let level = match stream.peek().ok()? {
+                PeekResult::Empty => break,
+                PeekResult::Hole(_) => None,
+                PeekResult::Data(data) => data
+                    .get(..mem::size_of::<f32>())
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .map(f32::from_ne_bytes),
+            };
+            stream.discard().ok()?;
+            if let Some(level) = level {
+                if let Err(err) = output.try_send(level.clamp(0.0, 1.0)) {
+                    if err.is_disconnected() {
+                        _ = stream.disconnect();
+                        return Some(());
+                    }
+                }
+            }
+        }
+    }
+}
+
 async fn start_listening(
     state: State,
     output: &mut futures::channel::mpsc::Sender<Event>,
@@ -119,6 +218,24 @@ async fn start_listening(
                         .await;
                     State::Connected
                 }
+                Some(Message::CommunicationVolume(volume)) => {
+                    _ = output
+                        .send(Event::MessageReceived(Message::CommunicationVolume(volume)))
+                        .await;
+                    State::Connected
+                }
+                Some(Message::SetSinkInputs(inputs)) => {
+                    _ = output
+                        .send(Event::MessageReceived(Message::SetSinkInputs(inputs)))
+                        .await;
+                    State::Connected
+                }
+                Some(Message::ResampledRate(rate)) => {
+                    _ = output
+                        .send(Event::MessageReceived(Message::ResampledRate(rate)))
+                        .await;
+                    State::Connected
+                }
                 Some(Message::Disconnected) => {
                     _ = output.send(Event::Disconnected).await;
                     State::Connecting
@@ -185,6 +302,20 @@ pub enum Message {
     SetSourceVolumeByName(String, ChannelVolumes),
     SetSinkMuteByName(String, bool),
     SetSourceMuteByName(String, bool),
+    GetCommunicationVolume,
+    /// Volume of the loudest communication stream, if there is one.
+    CommunicationVolume(Option<Volume>),
+    SetCommunicationVolume(Volume),
+    /// Sink index and its sample rate.
+    GetResampledRate(u32, u32),
+    /// Rate of a playing stream that the sink has to resample, if any.
+    ResampledRate(Option<u32>),
+    GetSinkInputs,
+    SetSinkInputs(Vec<SinkInputInfo>),
+    SetSinkInputVolume(u32, ChannelVolumes),
+    SetSinkInputMute(u32, bool),
+    /// Sink name and the name of the port to activate on it.
+    SetSinkPort(String, String),
 }
 
 struct PulseHandle {
@@ -227,7 +358,9 @@ impl PulseHandle {
                         // Deduplicate volume change messages.
                         if matches!(
                             msg,
-                            Message::SetSinkVolumeByName(..) | Message::SetSourceVolumeByName(..)
+                            Message::SetSinkVolumeByName(..)
+                                | Message::SetSourceVolumeByName(..)
+                                | Message::SetCommunicationVolume(..)
                         ) {
                             let last_msg = msgs.last_mut().unwrap(); //
                             if mem::discriminant(last_msg) == mem::discriminant(&msg) {
@@ -235,6 +368,16 @@ impl PulseHandle {
                                 continue;
                             }
                         }
+                        if matches!(
+                            (msgs.last(), &msg),
+                            (
+                                Some(Message::SetSinkInputVolume(last, _)),
+                                Message::SetSinkInputVolume(index, _),
+                            ) if last == index
+                        ) {
+                            *msgs.last_mut().unwrap() = msg;
+                            continue;
+                        }
 
                         msgs.push(msg);
                     }
@@ -345,6 +488,97 @@ impl PulseHandle {
                                     .set_source_mute_by_name(&name, mute, None);
                                 server.wait_for_result(op).ok();
                             }
+                            Message::GetCommunicationVolume => {
+                                let server = match server.as_mut() {
+                                    Some(s) => s,
+                                    None => continue,
+                                };
+                                match server.get_communication_streams() {
+                                    Ok(streams) => {
+                                        let volume = streams
+                                            .iter()
+                                            .map(|(_, v)| v.max())
+                                            .max_by_key(|v| v.0);
+                                        if let Err(err) = from_pulse_send
+                                            .send(Message::CommunicationVolume(volume))
+                                            .await
+                                        {
+                                            tracing::error!("ERROR! {}", err);
+                                        }
+                                    }
+                                    Err(_) => Self::send_disconnected(&from_pulse_send).await,
+                                }
+                            }
+                            Message::SetCommunicationVolume(volume) => {
+                                let server = match server.as_mut() {
+                                    Some(s) => s,
+                                    None => continue,
+                                };
+                                server.set_communication_volume(volume);
+                            }
+                            Message::GetSinkInputs => {
+                                let server = match server.as_mut() {
+                                    Some(s) => s,
+                                    None => continue,
+                                };
+                                match server.get_sink_input_infos() {
+                                    Ok(inputs) => {
+                                        if let Err(err) = from_pulse_send
+                                            .send(Message::SetSinkInputs(inputs))
+                                            .await
+                                        {
+                                            tracing::error!("ERROR! {}", err);
+                                        }
+                                    }
+                                    Err(_) => Self::send_disconnected(&from_pulse_send).await,
+                                }
+                            }
+                            Message::SetSinkInputVolume(index, channel_volumes) => {
+                                let server = match server.as_mut() {
+                                    Some(s) => s,
+                                    None => continue,
+                                };
+                                let op = server.introspector.set_sink_input_volume(
+                                    index,
+                                    &channel_volumes,
+                                    None,
+                                );
+                                server.wait_for_result(op).ok();
+                            }
+                            Message::SetSinkInputMute(index, mute) => {
+                                let server = match server.as_mut() {
+                                    Some(s) => s,
+                                    None => continue,
+                                };
+                                let op = server.introspector.set_sink_input_mute(index, mute, None);
+                                server.wait_for_result(op).ok();
+                            }
+                            Message::SetSinkPort(name, port) => {
+                                let server = match server.as_mut() {
+                                    Some(s) => s,
+                                    None => continue,
+                                };
+                                let op = server
+                                    .introspector
+                                    .set_sink_port_by_name(&name, &port, None);
+                                server.wait_for_result(op).ok();
+                            }
+                            Message::GetResampledRate(sink, rate) => {
+                                let server = match server.as_mut() {
+                                    Some(s) => s,
+                                    None => continue,
+                                };
+                                match server.get_resampled_rate(sink, rate) {
+                                    Ok(rate) => {
+                                        if let Err(err) =
+                                            from_pulse_send.send(Message::ResampledRate(rate)).await
+                                        {
+                                            tracing::error!("ERROR! {}", err);
+                                        }
+                                    }
+                                    Err(_) => Self::send_disconnected(&from_pulse_send).await,
+                                }
+                            }
                             Message::UpdateConnection => {
                                 tracing::info!(
                                     "Updating Connection, server exists: {:?}",
@@ -710,6 +944,50 @@ impl PulseServer {
         let _ = self.wait_for_result(op);
     }
 
+    // Get the sink inputs playing with the `phone` role, as used by calls
+    fn get_communication_streams(&self) -> Result<Vec<(u32, ChannelVolumes)>, PulseServerError> {
+        let list = Rc::new(RefCell::new(Vec::new()));
+        let list_ref = list.clone();
+        let op = self.introspector.get_sink_input_info_list(move |result| {
+            if let ListResult::Item(item) = result {
+                let role = item.proplist.get_str(proplist::properties::MEDIA_ROLE);
+                if role.as_deref() == Some("phone") {
+                    list_ref.borrow_mut().push((item.index, item.volume));
+                }
+            }
+        });
+        self.wait_for_result(op)?;
+        Ok(list.take())
+    }
+
+    fn set_communication_volume(&mut self, volume: Volume) {
+        let Ok(streams) = self.get_communication_streams() else {
+            return;
+        };
+        for (index, mut channel_volumes) in streams {
+            channel_volumes.set(channel_volumes.len(), volume);
+            let op = self
+                .introspector
+                .set_sink_input_volume(index, &channel_volumes, None);
+            let _ = self.wait_for_result(op);
+        }
+    }
+
+    // Finds an uncorked stream on `sink` whose sample rate differs from the sink's.
+    fn get_resampled_rate(&self, sink: u32, rate: u32) -> Result<Option<u32>, PulseServerError> {
+        let result = Rc::new(RefCell::new(None));
+        let result_ref = result.clone();
+        let op = self.introspector.get_sink_input_info_list(move |list| {
+            if let ListResult::Item(item) = list {
+                if item.sink == sink && !item.corked && item.sample_spec.rate != rate {
+                    result_ref.borrow_mut().get_or_insert(item.sample_spec.rate);
+                }
+            }
+        });
+        self.wait_for_result(op)?;
+        Ok(result.take())
+    }
+
     fn get_source_outputs(&mut self, source: u32) -> Vec<u32> {
         let result = Rc::new(RefCell::new(Vec::new()));
         let result_ref = Rc::new(RefCell::new(Vec::new()));
@@ -724,6 +1002,19 @@ impl PulseServer {
         result_ref.replace(Vec::new())
     }
 
+    // Get the streams of all applications playing audio
+    fn get_sink_input_infos(&self) -> Result<Vec<SinkInputInfo>, PulseServerError> {
+        let list = Rc::new(RefCell::new(Vec::new()));
+        let list_ref = list.clone();
+        let op = self.introspector.get_sink_input_info_list(move |result| {
+            if let ListResult::Item(item) = result {
+                list_ref.borrow_mut().push(item.into());
+            }
+        });
+        self.wait_for_result(op)?;
+        Ok(list.take())
+    }
+
     fn get_sink_inputs(&mut self, sink: u32) -> Vec<u32> {
         let result = Rc::new(RefCell::new(Vec::new()));
         let result_ref = Rc::new(RefCell::new(Vec::new()));
@@ -738,6 +1029,29 @@ impl PulseServer {
         result_ref.replace(Vec::new())
     }
 
+    // Name of the sink playing to the Bluetooth device with this address
+    fn get_bluetooth_sink(&self, address: &str) -> Result<Option<String>, PulseServerError> {
+        let sink = Rc::new(RefCell::new(None));
+        let sink_ref = sink.clone();
+        let address = address.to_string();
+        let op = self.introspector.get_sink_info_list(move |list| {
+            let ListResult::Item(item) = list else {
+                return;
+            };
+            // PulseAudio and PipeWire store the address under different keys
+            let is_device = ["device.string", "api.bluez5.address"].iter().any(|key| {
+                item.proplist
+                    .get_str(key)
+                    .is_some_and(|value| value.eq_ignore_ascii_case(&address))
+            });
+            if is_device {
+                *sink_ref.borrow_mut() = item.name.as_ref().map(|name| name.to_string());
+            }
+        });
+        self.wait_for_result(op)?;
+        Ok(sink.take())
+    }
+
     // after building an operation such as get_devices() we need to keep polling
     // the pulse audio server to "wait" for the operation to complete
     fn wait_for_result<G: ?Sized>(
@@ -770,13 +1084,88 @@ impl PulseServer {
     }
 }
 
+/// Makes the sink of the Bluetooth device with this address the default
+/// output, moving the streams of the previous default to it.
+///
+/// The sink only shows up once the audio profile is set up, so this retries
+/// for a few seconds after the device connects. Blocks the calling thread.
+pub fn set_bluetooth_default_sink(address: &str) -> Result<(), String> {
+    let mut server = PulseServer::connect()
+        .and_then(PulseServer::init)
+        .map_err(|err| format!("failed to connect to the sound server: {err:?}"))?;
+    for _ in 0..10 {
+        let sink = server
+            .get_bluetooth_sink(address)
+            .map_err(|err| format!("failed to list sinks: {err:?}"))?;
+        if let Some(sink) = sink {
+            let previous = server.get_default_sink().ok().map(|sink| sink.index);
+            let to_move = previous.map_or_else(Vec::new, |index| server.get_sink_inputs(index));
+            return if server.set_default_sink(&sink, to_move) {
+                Ok(())
+            } else {
+                Err(format!("the sound server refused {sink} as default sink"))
+            };
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+    Err(format!("no audio sink found for {address}"))
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct DeviceInfo {
     pub name: Option<String>,
     pub description: Option<String>,
     pub volume: ChannelVolumes,
+    pub channel_map: channelmap::Map,
     pub mute: bool,
     pub index: u32,
+    pub sample_rate: u32,
+    /// Available ports, e.g. speakers and headphones.
+    pub ports: Vec<PortInfo>,
+    /// Name of the active port.
+    pub active_port: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortInfo {
+    pub name: String,
+    pub description: String,
+    /// Whether something is plugged into the port, as far as Pulse knows.
+    pub available: bool,
+}
+
+impl PortInfo {
+    fn new(
+        name: Option<&str>,
+        description: Option<&str>,
+        available: PortAvailable,
+    ) -> Option<Self> {
+        let name = name?.to_owned();
+        Some(Self {
+            description: description.map_or_else(|| name.clone(), ToOwned::to_owned),
+            name,
+            available: available != PortAvailable::No,
+        })
+    }
+}
+
+impl DeviceInfo {
+    /// Sets every channel to `volume` while keeping the current balance.
+    pub fn set_volume(&mut self, volume: Volume) {
+        let balance = self.balance();
+        self.volume.set(self.volume.len(), volume);
+        self.set_balance(balance);
+    }
+
+    pub fn balance(&self) -> f32 {
+        self.volume.get_balance(&self.channel_map)
+    }
+
+    pub fn set_balance(&mut self, balance: f32) {
+        if self.channel_map.can_balance() {
+            self.volume.set_balance(&self.channel_map, balance);
+        }
+    }
 }
 
 impl<'a> From<&SinkInfo<'a>> for DeviceInfo {
@@ -785,8 +1174,21 @@ impl<'a> From<&SinkInfo<'a>> for DeviceInfo {
             name: info.name.clone().map(|x| x.into_owned()),
             description: info.description.clone().map(|x| x.into_owned()),
             volume: info.volume,
+            channel_map: info.channel_map,
             mute: info.mute,
             index: info.index,
+            sample_rate: info.sample_spec.rate,
+            ports: info
+                .ports
+                .iter()
+                .filter_map(|p| {
+                    PortInfo::new(p.name.as_deref(), p.description.as_deref(), p.available)
+                })
+                .collect(),
+            active_port: info
+                .active_port
+                .as_ref()
+                .and_then(|p| p.name.as_ref().map(|n| n.to_string())),
         }
     }
 }
@@ -797,14 +1199,69 @@ impl<'a> From<&SourceInfo<'a>> for DeviceInfo {
             name: info.name.clone().map(|x| x.into_owned()),
             description: info.description.clone().map(|x| x.into_owned()),
             volume: info.volume,
+            channel_map: info.channel_map,
             mute: info.mute,
             index: info.index,
+            sample_rate: info.sample_spec.rate,
+            ports: info
+                .ports
+                .iter()
+                .filter_map(|p| {
+                    PortInfo::new(p.name.as_deref(), p.description.as_deref(), p.available)
+                })
+                .collect(),
+            active_port: info
+                .active_port
+                .as_ref()
+                .and_then(|p| p.name.as_ref().map(|n| n.to_string())),
         }
     }
 }
 
 impl Eq for DeviceInfo {}
 
+/// Stream of an application playing audio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SinkInputInfo {
+    pub index: u32,
+    /// Index of the sink the stream plays on.
+    pub sink: u32,
+    pub name: String,
+    pub icon_name: Option<String>,
+    /// `media.role` of the stream, such as `music` or `a11y`.
+    pub role: Option<String>,
+    pub volume: ChannelVolumes,
+    pub mute: bool,
+}
+
+impl SinkInputInfo {
+    /// Whether the stream is a screen reader or an alarm, which shouldn't be
+    /// silenced by accident.
+    pub fn is_critical(&self) -> bool {
+        matches!(self.role.as_deref(), Some("a11y" | "alarm"))
+    }
+}
+
+impl<'a> From<&pulse::context::introspect::SinkInputInfo<'a>> for SinkInputInfo {
+    fn from(info: &pulse::context::introspect::SinkInputInfo<'a>) -> Self {
+        Self {
+            index: info.index,
+            sink: info.sink,
+            name: info
+                .proplist
+                .get_str(proplist::properties::APPLICATION_NAME)
+                .or_else(|| info.name.as_ref().map(|name| name.to_string()))
+                .unwrap_or_default(),
+            icon_name: info
+                .proplist
+                .get_str(proplist::properties::APPLICATION_ICON_NAME),
+            role: info.proplist.get_str(proplist::properties::MEDIA_ROLE),
+            volume: info.volume,
+            mute: info.mute,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ServerInfo {
     /// User name of the daemon process.
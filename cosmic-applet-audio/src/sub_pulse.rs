@@ -0,0 +1,24 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The Pulse subscription shared with cosmic-settings, plus the events only
+//! this applet needs.
+
+use cosmic::iced::Subscription;
+pub use cosmic_settings_subscriptions::pulse as settings;
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Settings(settings::Event),
+    /// Peak level of the current input, between 0 and 1.
+    PeakLevel(f32),
+}
+
+pub fn subscription() -> Subscription<Event> {
+    settings::subscription().map(Event::Settings)
+}
+
+/// Peak levels of a source, see [`crate::pulse::peak_level`].
+pub fn peak_level(source: String) -> Subscription<Event> {
+    crate::pulse::peak_level(source).map(Event::PeakLevel)
+}
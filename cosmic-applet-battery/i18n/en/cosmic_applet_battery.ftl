@@ -9,6 +9,17 @@ seconds = s
 minutes = m
 hours = h
 until-empty = until empty
+until-full = until full
+charging = Charging
+fully-charged = Fully charged
 power-settings = Power & Battery settings...
 dgpu-running = Discrete GPU is active and can reduce battery life
 dgpu-applications = Applications using {$gpu_name} discrete GPU
+battery-health = Health { $percent }%
+battery-health-energy = Health { $percent }% ({ $full } / { $design } Wh)
+no-battery = No battery
+line-power = Running on line power
+unknown-device = Unknown device
+low-battery = Low battery
+low-battery-body = { $percent }% of battery remaining. Plug in the charger soon.
+charging-body = Battery at { $percent }%.
@@ -3,10 +3,11 @@
 
 use crate::{
     backend::{
-        get_charging_limit, power_profile_subscription, set_charging_limit, Power,
-        PowerProfileRequest, PowerProfileUpdate,
+        get_battery_health, get_charge_state, get_charging_limit, peripherals_subscription,
+        power_profile_subscription, send_notification, set_charging_limit, BatteryHealth,
+        BatteryState, Peripheral, Power, PowerProfileRequest, PowerProfileUpdate,
     },
-    config,
+    config::{self, BatteryAppletConfig, NoBatteryDisplay},
     dgpu::{dgpu_subscription, Entry, GpuUpdate},
     fl,
 };
@@ -17,6 +18,7 @@ use cosmic::{
         token::subscription::{activation_token_subscription, TokenRequest, TokenUpdate},
     },
     cctk::sctk::reexports::calloop,
+    cosmic_config::{self, CosmicConfigEntry},
     cosmic_theme::Spacing,
     iced::{
         platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup},
@@ -80,9 +82,21 @@ struct CosmicBatteryApplet {
     display_icon_name: String,
     charging_limit: bool,
     battery_percent: f64,
+    battery_health: Option<BatteryHealth>,
     on_battery: bool,
+    /// UPower found no battery and the config asks to show a plug icon instead.
+    no_battery: bool,
+    config: BatteryAppletConfig,
     gpus: HashMap<PathBuf, GPUData>,
     time_remaining: Duration,
+    battery_state: BatteryState,
+    time_to_full: Duration,
+    peripherals: Vec<Peripheral>,
+    /// Lowest of the `low_battery_warnings` already shown since the battery
+    /// started discharging.
+    warned_level: Option<u8>,
+    /// Id of the last notification sent, which the next one replaces.
+    notification_id: u32,
     max_kbd_brightness: Option<i32>,
     kbd_brightness: Option<i32>,
     max_screen_brightness: Option<i32>,
@@ -95,15 +109,76 @@ struct CosmicBatteryApplet {
     timeline: Timeline,
     token_tx: Option<calloop::channel::Sender<TokenRequest>>,
     zbus_connection: Option<zbus::Connection>,
+    /// System bus connection, reused to read the charge state on each update.
+    system_connection: Option<zbus::Connection>,
     dragging_screen_brightness: bool,
     dragging_kbd_brightness: bool,
 }
 
 impl CosmicBatteryApplet {
+    /// Warns when the battery drops below one of the configured levels, or
+    /// tells that it's charging again after it was plugged in.
+    fn battery_notification(
+        &mut self,
+        was_on_battery: bool,
+    ) -> cosmic::iced::Task<cosmic::app::Message<Message>> {
+        let notification = if self.on_battery {
+            let level = self
+                .config
+                .low_battery_warnings
+                .iter()
+                .copied()
+                .filter(|level| self.battery_percent <= f64::from(*level))
+                .min();
+            match level {
+                Some(level) if self.warned_level.map_or(true, |warned| level < warned) => {
+                    self.warned_level = Some(level);
+                    Some((
+                        "battery-level-10-symbolic",
+                        fl!("low-battery"),
+                        fl!(
+                            "low-battery-body",
+                            percent = format!("{:.0}", self.battery_percent)
+                        ),
+                        true,
+                    ))
+                }
+                _ => None,
+            }
+        } else {
+            self.warned_level = None;
+            (was_on_battery && self.config.notify_charging).then(|| {
+                (
+                    "battery-level-50-charging-symbolic",
+                    fl!("charging"),
+                    fl!(
+                        "charging-body",
+                        percent = format!("{:.0}", self.battery_percent)
+                    ),
+                    false,
+                )
+            })
+        };
+
+        let (Some((icon, summary, body, critical)), Some(conn)) =
+            (notification, self.zbus_connection.clone())
+        else {
+            return Task::none();
+        };
+        cosmic::iced::Task::perform(
+            send_notification(conn, self.notification_id, icon, summary, body, critical),
+            |id| cosmic::app::Message::App(Message::NotificationSent(id)),
+        )
+    }
+
     fn update_battery(&mut self, mut percent: f64, on_battery: bool) {
         percent = percent.clamp(0.0, 100.0);
         self.on_battery = on_battery;
         self.battery_percent = percent;
+        if self.no_battery {
+            self.icon_name = "ac-adapter-symbolic".to_string();
+            return;
+        }
         let battery_percent = if self.battery_percent > 95.0 && !self.charging_limit {
             100
         } else if self.battery_percent > 80.0 && !self.charging_limit {
@@ -173,6 +248,10 @@ enum Message {
     SetScreenBrightnessDebounced,
     ReleaseScreenBrightness,
     InitChargingLimit(bool),
+    BatteryHealth(Option<BatteryHealth>),
+    ChargeState(Option<(BatteryState, Duration)>),
+    Peripherals(Vec<Peripheral>),
+    NotificationSent(Option<u32>),
     SetChargingLimit(chain::Toggler, bool),
     KeyboardBacklight(KeyboardBacklightUpdate),
     UpowerDevice(DeviceDbusEvent),
@@ -188,6 +267,8 @@ enum Message {
     OpenSettings,
     SettingsDaemon(settings_daemon::Event),
     ZbusConnection(zbus::Result<zbus::Connection>),
+    SystemConnection(zbus::Result<zbus::Connection>),
+    ConfigChanged(BatteryAppletConfig),
 }
 
 impl cosmic::Application for CosmicBatteryApplet {
@@ -206,6 +287,9 @@ impl cosmic::Application for CosmicBatteryApplet {
         let zbus_session_cmd = cosmic::iced::Task::perform(zbus::Connection::session(), |res| {
             cosmic::app::Message::App(Message::ZbusConnection(res))
         });
+        let zbus_system_cmd = cosmic::iced::Task::perform(zbus::Connection::system(), |res| {
+            cosmic::app::Message::App(Message::SystemConnection(res))
+        });
         let init_charging_limit_cmd = cosmic::iced::Task::perform(get_charging_limit(), |limit| {
             cosmic::app::Message::App(Message::InitChargingLimit(limit))
         });
@@ -215,10 +299,17 @@ impl cosmic::Application for CosmicBatteryApplet {
                 icon_name: "battery-symbolic".to_string(),
                 display_icon_name: "display-brightness-symbolic".to_string(),
                 token_tx: None,
-
+                config: cosmic_config::Config::new(config::APP_ID, BatteryAppletConfig::VERSION)
+                    .ok()
+                    .and_then(|c| BatteryAppletConfig::get_entry(&c).ok())
+                    .unwrap_or_default(),
                 ..Default::default()
             },
-            Task::batch(vec![zbus_session_cmd, init_charging_limit_cmd]),
+            Task::batch(vec![
+                zbus_session_cmd,
+                zbus_system_cmd,
+                init_charging_limit_cmd,
+            ]),
         )
     }
 
@@ -302,6 +393,20 @@ impl cosmic::Application for CosmicBatteryApplet {
             Message::InitChargingLimit(enable) => {
                 self.set_charging_limit(enable);
             }
+            Message::NotificationSent(id) => {
+                if let Some(id) = id {
+                    self.notification_id = id;
+                }
+            }
+            Message::Peripherals(peripherals) => {
+                self.peripherals = peripherals;
+            }
+            Message::ChargeState(state) => {
+                (self.battery_state, self.time_to_full) = state.unwrap_or_default();
+            }
+            Message::BatteryHealth(health) => {
+                self.battery_health = health;
+            }
             Message::SetChargingLimit(chain, enable) => {
                 self.timeline.set_chain(chain).start();
                 self.set_charging_limit(enable);
@@ -345,7 +450,12 @@ impl cosmic::Application for CosmicBatteryApplet {
                     if let Some(tx) = self.power_profile_sender.as_ref() {
                         let _ = tx.send(PowerProfileRequest::Get);
                     }
-                    return get_popup(popup_settings);
+                    return Task::batch(vec![
+                        get_popup(popup_settings),
+                        cosmic::iced::Task::perform(get_battery_health(), |health| {
+                            cosmic::app::Message::App(Message::BatteryHealth(health))
+                        }),
+                    ]);
                 }
             }
             Message::UpowerDevice(event) => match event {
@@ -354,13 +464,34 @@ impl cosmic::Application for CosmicBatteryApplet {
                     percent,
                     time_to_empty,
                 } => {
+                    let was_on_battery = self.on_battery;
+                    self.no_battery = false;
                     self.update_battery(percent, on_battery);
                     self.time_remaining = Duration::from_secs(time_to_empty as u64);
+                    let notification = self.battery_notification(was_on_battery);
+                    let Some(conn) = self.system_connection.clone() else {
+                        return notification;
+                    };
+                    let charge_state =
+                        cosmic::iced::Task::perform(get_charge_state(conn), |state| {
+                            cosmic::app::Message::App(Message::ChargeState(state))
+                        });
+                    return Task::batch([charge_state, notification]);
                 }
                 DeviceDbusEvent::NoBattery => {
-                    std::process::exit(0);
+                    if self.config.no_battery == NoBatteryDisplay::Hide {
+                        std::process::exit(0);
+                    }
+                    self.no_battery = true;
+                    self.update_battery(self.battery_percent, false);
                 }
             },
+            Message::ConfigChanged(config) => {
+                if self.no_battery && config.no_battery == NoBatteryDisplay::Hide {
+                    std::process::exit(0);
+                }
+                self.config = config;
+            }
             Message::KeyboardBacklight(event) => match event {
                 KeyboardBacklightUpdate::Sender(tx) => {
                     self.kbd_sender = Some(tx);
@@ -453,6 +584,16 @@ impl cosmic::Application for CosmicBatteryApplet {
             Message::ZbusConnection(Ok(conn)) => {
                 self.zbus_connection = Some(conn);
             }
+            Message::SystemConnection(Err(err)) => {
+                tracing::error!("Failed to connect to system dbus: {}", err);
+            }
+            Message::SystemConnection(Ok(conn)) => {
+                self.system_connection = Some(conn.clone());
+                // the first device update may have come before the connection
+                return cosmic::iced::Task::perform(get_charge_state(conn), |state| {
+                    cosmic::app::Message::App(Message::ChargeState(state))
+                });
+            }
             Message::SettingsDaemon(event) => match event {
                 settings_daemon::Event::Sender(tx) => {
                     self.settings_daemon_sender = Some(tx);
@@ -514,8 +655,27 @@ impl cosmic::Application for CosmicBatteryApplet {
             space_xxs, space_s, ..
         } = theme::active().cosmic().spacing;
 
-        let name = text::body(fl!("battery"));
-        let description = text::caption(if !self.on_battery {
+        let name = text::body(if self.no_battery {
+            fl!("no-battery")
+        } else {
+            fl!("battery")
+        });
+        let description = text::caption(if self.no_battery {
+            fl!("line-power")
+        } else if self.battery_state == BatteryState::FullyCharged {
+            fl!("fully-charged")
+        } else if self.battery_state == BatteryState::Charging {
+            if self.time_to_full.is_zero() {
+                format!("{} ({:.0}%)", fl!("charging"), self.battery_percent)
+            } else {
+                format!(
+                    "{} {} ({:.0}%)",
+                    format_duration(self.time_to_full),
+                    fl!("until-full"),
+                    self.battery_percent
+                )
+            }
+        } else if !self.on_battery {
             format!("{:.0}%", self.battery_percent)
         } else {
             format!(
@@ -526,16 +686,52 @@ impl cosmic::Application for CosmicBatteryApplet {
             )
         });
 
-        let mut content = vec![
-            padded_control(
-                row![
-                    icon::from_name(&*self.icon_name).size(24).symbolic(true),
-                    column![name, description]
-                ]
-                .spacing(8)
-                .align_y(Alignment::Center),
-            )
-            .into(),
+        let mut content: Vec<Element<_>> = vec![padded_control(
+            row![
+                icon::from_name(&*self.icon_name).size(24).symbolic(true),
+                column![name, description].push_maybe(self.battery_health.map(|health| {
+                    let percent = format!("{:.0}", health.percent);
+                    text::caption(match health.energy {
+                        Some((full, design)) => fl!(
+                            "battery-health-energy",
+                            percent = percent,
+                            full = format!("{full:.1}"),
+                            design = format!("{design:.1}")
+                        ),
+                        None => fl!("battery-health", percent = percent),
+                    })
+                }))
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        )
+        .into()];
+
+        for peripheral in &self.peripherals {
+            let name = if peripheral.model.is_empty() {
+                fl!("unknown-device")
+            } else {
+                peripheral.model.clone()
+            };
+            content.push(
+                padded_control(
+                    row![
+                        icon::from_name(&*peripheral.icon_name)
+                            .size(24)
+                            .symbolic(true),
+                        column![
+                            text::body(name),
+                            text::caption(format!("{:.0}%", peripheral.percent))
+                        ]
+                    ]
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+                )
+                .into(),
+            );
+        }
+
+        content.extend([
             padded_control(divider::horizontal::default())
                 .padding([space_xxs, space_s])
                 .into(),
@@ -605,23 +801,30 @@ impl cosmic::Application for CosmicBatteryApplet {
             padded_control(divider::horizontal::default())
                 .padding([space_xxs, space_s])
                 .into(),
-            padded_control(
-                anim!(
-                    //toggler
-                    MAX_CHARGE,
-                    &self.timeline,
-                    fl!("max-charge"),
-                    self.charging_limit,
-                    Message::SetChargingLimit,
+        ]);
+
+        if !self.no_battery {
+            content.push(
+                padded_control(
+                    anim!(
+                        //toggler
+                        MAX_CHARGE,
+                        &self.timeline,
+                        fl!("max-charge"),
+                        self.charging_limit,
+                        Message::SetChargingLimit,
+                    )
+                    .text_size(14)
+                    .width(Length::Fill),
                 )
-                .text_size(14)
-                .width(Length::Fill),
-            )
-            .into(),
-            padded_control(divider::horizontal::default())
-                .padding([space_xxs, space_s])
                 .into(),
-        ];
+            );
+            content.push(
+                padded_control(divider::horizontal::default())
+                    .padding([space_xxs, space_s])
+                    .into(),
+            );
+        }
 
         if let Some(max_screen_brightness) = self.max_screen_brightness {
             if let Some(screen_brightness) = self.screen_brightness {
@@ -816,6 +1019,7 @@ impl cosmic::Application for CosmicBatteryApplet {
     fn subscription(&self) -> Subscription<Message> {
         let mut subscriptions = vec![
             device_subscription(0).map(Message::UpowerDevice),
+            peripherals_subscription(0).map(Message::Peripherals),
             kbd_backlight_subscription(0).map(Message::KeyboardBacklight),
             power_profile_subscription(0).map(|event| match event {
                 PowerProfileUpdate::Update { profile } => Message::Profile(profile),
@@ -830,6 +1034,12 @@ impl cosmic::Application for CosmicBatteryApplet {
                 .as_subscription()
                 .map(|(_, now)| Message::Frame(now)),
             activation_token_subscription(0).map(Message::Token),
+            self.core.watch_config(config::APP_ID).map(|u| {
+                for err in u.errors {
+                    tracing::error!(?err, "Error watching config");
+                }
+                Message::ConfigChanged(u.config)
+            }),
         ];
         if let Some(conn) = self.zbus_connection.clone() {
             subscriptions.push(settings_daemon::subscription(conn).map(Message::SettingsDaemon));
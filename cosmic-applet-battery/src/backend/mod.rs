@@ -2,17 +2,37 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use cosmic::{
-    iced::{self, futures::SinkExt, Subscription},
+    iced::{
+        self,
+        futures::{stream::BoxStream, SinkExt, StreamExt},
+        Subscription,
+    },
     iced_futures::stream,
 };
-use std::{fmt::Debug, hash::Hash};
+use std::{fmt::Debug, hash::Hash, time::Duration};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use zbus::{Connection, Result};
 
-use self::{power_daemon::PowerDaemonProxy, power_profiles::PowerProfilesProxy};
+use self::{
+    notifications::NotificationsProxy,
+    power_daemon::PowerDaemonProxy,
+    power_profiles::PowerProfilesProxy,
+    upower::{DeviceProxy, UPowerProxy},
+};
 
+mod notifications;
 mod power_daemon;
 mod power_profiles;
+mod upower;
+
+/// UPower device type of a battery.
+const UPOWER_DEVICE_TYPE_BATTERY: u32 = 2;
+
+/// UPower device type of an AC adapter.
+const UPOWER_DEVICE_TYPE_LINE_POWER: u32 = 1;
+
+/// Path of the UPower device combining the batteries powering the system.
+const UPOWER_DISPLAY_DEVICE: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
 
 #[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
 pub enum Power {
@@ -44,10 +64,10 @@ impl BackendType {
     }
 }
 
-pub async fn get_power_backend<'a>(
-    conn: &'a Connection,
+pub async fn get_power_backend(
+    conn: &Connection,
     backend_type: &BackendType,
-) -> Result<Backend<'a>> {
+) -> Result<Backend<'static>> {
     match backend_type {
         BackendType::S76PowerDaemon => PowerDaemonProxy::new(conn)
             .await
@@ -62,24 +82,62 @@ pub async fn get_power_profile(daemon: Backend<'_>) -> Result<Power> {
     match daemon {
         Backend::S76PowerDaemon(p) => {
             let power = p.get_profile().await?;
-            match power.as_str() {
-                "Battery" => Ok(Power::Battery),
-                "Balanced" => Ok(Power::Balanced),
-                "Performance" => Ok(Power::Performance),
-                _ => panic!("Unknown power profile: {}", power),
-            }
+            Ok(s76_power_profile(&power))
         }
         Backend::PowerProfilesDaemon(ppd) => {
             let power = ppd.active_profile().await?;
-            match power.as_str() {
-                "power-saver" => Ok(Power::Battery),
-                "performance" => Ok(Power::Performance),
-                _ => Ok(Power::Balanced),
-            }
+            Ok(ppd_power_profile(&power))
         }
     }
 }
 
+fn s76_power_profile(power: &str) -> Power {
+    match power {
+        "Battery" => Power::Battery,
+        "Balanced" => Power::Balanced,
+        "Performance" => Power::Performance,
+        _ => {
+            tracing::warn!("Unknown power profile: {}", power);
+            Power::Balanced
+        }
+    }
+}
+
+fn ppd_power_profile(power: &str) -> Power {
+    match power {
+        "power-saver" => Power::Battery,
+        "performance" => Power::Performance,
+        _ => Power::Balanced,
+    }
+}
+
+/// Profiles the daemon switches to, including by other programs.
+pub async fn power_profile_changes(daemon: &Backend<'static>) -> Result<BoxStream<'static, Power>> {
+    match daemon {
+        Backend::S76PowerDaemon(p) => Ok(p
+            .receive_power_profile_switch()
+            .await?
+            .filter_map(|signal| async move {
+                signal
+                    .args()
+                    .ok()
+                    .map(|args| s76_power_profile(args.profile()))
+            })
+            .boxed()),
+        Backend::PowerProfilesDaemon(ppd) => Ok(ppd
+            .receive_active_profile_changed()
+            .await
+            .filter_map(|change| async move {
+                change
+                    .get()
+                    .await
+                    .ok()
+                    .map(|power| ppd_power_profile(&power))
+            })
+            .boxed()),
+    }
+}
+
 pub async fn set_power_profile(daemon: Backend<'_>, power: Power) -> Result<()> {
     match daemon {
         Backend::S76PowerDaemon(p) => match power {
@@ -110,7 +168,6 @@ pub fn power_profile_subscription<I: 'static + Hash + Copy + Send + Sync + Debug
     )
 }
 
-#[derive(Debug)]
 pub enum State {
     Ready,
     Connecting(BackendType, Connection),
@@ -118,6 +175,7 @@ pub enum State {
         Connection,
         UnboundedReceiver<PowerProfileRequest>,
         BackendType,
+        BoxStream<'static, Power>,
     ),
     Finished,
 }
@@ -154,6 +212,13 @@ async fn start_listening(
                 }
             };
             // Successful connection
+            let changes = match power_profile_changes(&backend).await {
+                Ok(changes) => changes,
+                Err(e) => {
+                    tracing::warn!("Failed to watch for power profile changes: {e}");
+                    futures::stream::pending().boxed()
+                }
+            };
             let profile = match get_power_profile(backend).await.map_err(|e| e.to_string()) {
                 Ok(p) => p,
                 Err(e) => {
@@ -167,9 +232,9 @@ async fn start_listening(
             };
             let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
             _ = output.send(PowerProfileUpdate::Init(profile, tx)).await;
-            State::Waiting(conn, rx, backend_type)
+            State::Waiting(conn, rx, backend_type, changes)
         }
-        State::Waiting(conn, mut rx, backend_type) => {
+        State::Waiting(conn, mut rx, backend_type, mut changes) => {
             let backend = match get_power_backend(&conn, &backend_type)
                 .await
                 .map_err(|e| e.to_string())
@@ -181,19 +246,25 @@ async fn start_listening(
                 }
             };
 
-            match rx.recv().await {
-                Some(PowerProfileRequest::Get) => {
-                    if let Ok(profile) = get_power_profile(backend).await {
+            tokio::select! {
+                request = rx.recv() => match request {
+                    Some(PowerProfileRequest::Get) => {
+                        if let Ok(profile) = get_power_profile(backend).await {
+                            _ = output.send(PowerProfileUpdate::Update { profile }).await;
+                        }
+                        State::Waiting(conn, rx, backend_type, changes)
+                    }
+                    Some(PowerProfileRequest::Set(profile)) => {
+                        let _ = set_power_profile(backend, profile).await;
                         _ = output.send(PowerProfileUpdate::Update { profile }).await;
+                        State::Waiting(conn, rx, backend_type, changes)
                     }
-                    State::Waiting(conn, rx, backend_type)
-                }
-                Some(PowerProfileRequest::Set(profile)) => {
-                    let _ = set_power_profile(backend, profile).await;
+                    None => State::Finished,
+                },
+                Some(profile) = changes.next() => {
                     _ = output.send(PowerProfileUpdate::Update { profile }).await;
-                    State::Waiting(conn, rx, backend_type)
+                    State::Waiting(conn, rx, backend_type, changes)
                 }
-                None => State::Finished,
             }
         }
         State::Finished => iced::futures::future::pending().await,
@@ -252,3 +323,205 @@ pub async fn set_charging_limit() -> Result<()> {
     }
     Ok(())
 }
+
+/// Health of a battery, as reported by UPower.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryHealth {
+    /// Current full capacity as a percentage of the design capacity.
+    pub percent: f64,
+    /// Energy in Wh when fully charged now and when new, if the battery reports both.
+    pub energy: Option<(f64, f64)>,
+}
+
+/// Health of the first battery powering the system.
+pub async fn get_battery_health() -> Option<BatteryHealth> {
+    let conn = Connection::system().await.ok()?;
+    let upower = UPowerProxy::new(&conn).await.ok()?;
+    for path in upower.enumerate_devices().await.ok()? {
+        let Ok(device) = DeviceProxy::builder(&conn).path(path).ok()?.build().await else {
+            continue;
+        };
+        if device.type_().await.ok() != Some(UPOWER_DEVICE_TYPE_BATTERY)
+            || !device.power_supply().await.unwrap_or_default()
+        {
+            continue;
+        }
+
+        // 0 when the battery doesn't report its design capacity
+        let capacity = device.capacity().await.ok()?;
+        if capacity <= 0.0 {
+            return None;
+        }
+        let energy_full = device.energy_full().await.unwrap_or_default();
+        let energy_full_design = device.energy_full_design().await.unwrap_or_default();
+        return Some(BatteryHealth {
+            percent: capacity.min(100.0),
+            energy: (energy_full > 0.0 && energy_full_design > 0.0)
+                .then_some((energy_full, energy_full_design)),
+        });
+    }
+    None
+}
+
+/// Charging state of a battery, as reported by UPower.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatteryState {
+    #[default]
+    Unknown,
+    Charging,
+    Discharging,
+    Empty,
+    FullyCharged,
+    PendingCharge,
+    PendingDischarge,
+}
+
+impl From<u32> for BatteryState {
+    fn from(state: u32) -> Self {
+        match state {
+            1 => Self::Charging,
+            2 => Self::Discharging,
+            3 => Self::Empty,
+            4 => Self::FullyCharged,
+            5 => Self::PendingCharge,
+            6 => Self::PendingDischarge,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+// get the charging state and time until fully charged of the system batteries
+pub async fn get_charge_state(conn: Connection) -> Option<(BatteryState, Duration)> {
+    // read once per update, so there's no point in caching the properties
+    let device = DeviceProxy::builder(&conn)
+        .path(UPOWER_DISPLAY_DEVICE)
+        .ok()?
+        .cache_properties(zbus::CacheProperties::No)
+        .build()
+        .await
+        .ok()?;
+    let state = BatteryState::from(device.state().await.ok()?);
+    let time_to_full = device.time_to_full().await.unwrap_or_default();
+    Some((state, Duration::from_secs(time_to_full.max(0) as u64)))
+}
+
+/// A battery powered device connected to the system, like a wireless mouse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Peripheral {
+    pub path: String,
+    /// Model name, which may be empty.
+    pub model: String,
+    pub icon_name: String,
+    pub percent: f64,
+}
+
+// get the devices with a battery that doesn't power the system
+async fn get_peripherals(
+    conn: &Connection,
+    upower: &UPowerProxy<'_>,
+) -> Vec<(Peripheral, DeviceProxy<'static>)> {
+    let mut peripherals = Vec::new();
+    for path in upower.enumerate_devices().await.unwrap_or_default() {
+        let Ok(builder) = DeviceProxy::builder(conn).path(path.clone()) else {
+            continue;
+        };
+        let Ok(device) = builder.build().await else {
+            continue;
+        };
+        if device.power_supply().await.unwrap_or(true)
+            || device.type_().await.ok() == Some(UPOWER_DEVICE_TYPE_LINE_POWER)
+        {
+            continue;
+        }
+        let Ok(percent) = device.percentage().await else {
+            continue;
+        };
+
+        let peripheral = Peripheral {
+            path: path.to_string(),
+            model: device.model().await.unwrap_or_default(),
+            icon_name: device.icon_name().await.unwrap_or_default(),
+            percent,
+        };
+        peripherals.push((peripheral, device));
+    }
+    peripherals
+}
+
+/// Lists the peripherals again whenever one is added, removed or its charge changes.
+pub fn peripherals_subscription<I: 'static + Hash + Copy + Send + Sync + Debug>(
+    id: I,
+) -> iced::Subscription<Vec<Peripheral>> {
+    Subscription::run_with_id(
+        id,
+        stream::channel(10, move |mut output| async move {
+            let Ok(conn) = Connection::system().await else {
+                return iced::futures::future::pending().await;
+            };
+            let Ok(upower) = UPowerProxy::new(&conn).await else {
+                return iced::futures::future::pending().await;
+            };
+            let (Ok(mut added), Ok(mut removed)) = (
+                upower.receive_device_added().await,
+                upower.receive_device_removed().await,
+            ) else {
+                tracing::error!("Failed to watch for UPower devices");
+                return iced::futures::future::pending().await;
+            };
+
+            loop {
+                let peripherals = get_peripherals(&conn, &upower).await;
+                let changes = futures::future::join_all(
+                    peripherals
+                        .iter()
+                        .map(|(_, device)| device.receive_percentage_changed()),
+                )
+                .await;
+                let mut changes =
+                    futures::stream::select_all(changes.into_iter().map(|c| c.map(|_| ()).boxed()));
+                _ = output
+                    .send(peripherals.into_iter().map(|(p, _)| p).collect())
+                    .await;
+
+                tokio::select! {
+                    Some(_) = added.next() => {}
+                    Some(_) = removed.next() => {}
+                    Some(_) = changes.next() => {}
+                    else => break,
+                }
+            }
+
+            iced::futures::future::pending().await
+        }),
+    )
+}
+
+/// Shows a desktop notification, replacing the one with `replaces_id` if it's
+/// still open. Returns the id of the new notification.
+pub async fn send_notification(
+    conn: Connection,
+    replaces_id: u32,
+    icon: &str,
+    summary: String,
+    body: String,
+    critical: bool,
+) -> Option<u32> {
+    let proxy = NotificationsProxy::new(&conn).await.ok()?;
+    // urgency levels are 0 for low, 1 for normal and 2 for critical
+    let urgency = zbus::zvariant::Value::U8(if critical { 2 } else { 1 });
+    let hints = std::collections::HashMap::from([("urgency", &urgency)]);
+    proxy
+        .notify(
+            "cosmic-applet-battery",
+            replaces_id,
+            icon,
+            &summary,
+            &body,
+            &[],
+            hints,
+            -1,
+        )
+        .await
+        .inspect_err(|err| tracing::error!("Failed to send notification: {err}"))
+        .ok()
+}
@@ -0,0 +1,69 @@
+//! # DBus interface proxies for: `org.freedesktop.UPower` and `org.freedesktop.UPower.Device`
+//!
+//! Only the members used by the applet are declared.
+
+use zbus::{proxy, zvariant::OwnedObjectPath};
+
+#[proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower"
+)]
+trait UPower {
+    /// EnumerateDevices method
+    fn enumerate_devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    /// DeviceAdded signal
+    #[zbus(signal)]
+    fn device_added(&self, device: OwnedObjectPath) -> zbus::Result<()>;
+
+    /// DeviceRemoved signal
+    #[zbus(signal)]
+    fn device_removed(&self, device: OwnedObjectPath) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.UPower.Device",
+    default_service = "org.freedesktop.UPower"
+)]
+trait Device {
+    /// Type property
+    #[zbus(property, name = "Type")]
+    fn type_(&self) -> zbus::Result<u32>;
+
+    /// PowerSupply property
+    #[zbus(property)]
+    fn power_supply(&self) -> zbus::Result<bool>;
+
+    /// Model property
+    #[zbus(property)]
+    fn model(&self) -> zbus::Result<String>;
+
+    /// IconName property
+    #[zbus(property)]
+    fn icon_name(&self) -> zbus::Result<String>;
+
+    /// Percentage property
+    #[zbus(property)]
+    fn percentage(&self) -> zbus::Result<f64>;
+
+    /// Capacity property
+    #[zbus(property)]
+    fn capacity(&self) -> zbus::Result<f64>;
+
+    /// EnergyFull property
+    #[zbus(property)]
+    fn energy_full(&self) -> zbus::Result<f64>;
+
+    /// EnergyFullDesign property
+    #[zbus(property)]
+    fn energy_full_design(&self) -> zbus::Result<f64>;
+
+    /// State property
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<u32>;
+
+    /// TimeToFull property
+    #[zbus(property)]
+    fn time_to_full(&self) -> zbus::Result<i64>;
+}
@@ -1,4 +1,28 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
 pub const APP_ID: &str = "com.system76.CosmicAppletButton";
+
+/// What the applet shows on systems without a battery.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NoBatteryDisplay {
+    /// Remove the applet from the panel.
+    #[default]
+    Hide,
+    /// Show a plug icon, keeping the power profile and brightness controls.
+    LinePower,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct BatteryAppletConfig {
+    pub no_battery: NoBatteryDisplay,
+    /// Charge percentages at which to warn that the battery is running low
+    /// while discharging, e.g. `[15, 5]`. Empty, the default, to never warn.
+    pub low_battery_warnings: Vec<u8>,
+    /// Notify when the charger is plugged back in. Off by default.
+    pub notify_charging: bool,
+}
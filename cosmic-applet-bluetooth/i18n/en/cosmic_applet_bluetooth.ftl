@@ -2,6 +2,7 @@ bluetooth = Bluetooth
 other-devices = Other Bluetooth devices
 settings = Bluetooth settings...
 connected = Connected
+connect-failed = Couldn't connect
 confirm-pin = Please confirm that the following PIN matches the one displayed on {$deviceName}
 confirm = Confirm
 cancel = Cancel
@@ -10,3 +11,6 @@ check-device = Make sure {$deviceName} is turned on, in range, and is ready to p
 try-again = Try Again
 discoverable = Discoverable
 pairable = Pairable
+discoverable-remaining = Visible as "{ $name }" for { $minutes } min
+rename = Rename
+use-as-audio-output = Use as audio output
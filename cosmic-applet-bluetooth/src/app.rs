@@ -1,7 +1,8 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::bluetooth::{BluerDeviceStatus, BluerRequest, BluerState, DeviceProperty};
+use crate::bluetooth::{BluerDeviceStatus, BluerRequest, BluerState};
+use bluer::Address;
 use cosmic::{
     applet::token::subscription::{activation_token_subscription, TokenRequest, TokenUpdate},
     cctk::sctk::reexports::calloop,
@@ -9,6 +10,7 @@ use cosmic::{
 
 use cosmic::{
     applet::{menu_button, padded_control},
+    cosmic_config::{self, CosmicConfigEntry},
     cosmic_theme::Spacing,
     iced::{
         self,
@@ -18,19 +20,26 @@ use cosmic::{
     },
     iced_runtime::core::{layout::Limits, window},
     theme,
-    widget::{button, divider, icon, text},
+    widget::{button, divider, icon, mouse_area, text, text_input, tooltip},
     Element, Task,
 };
 use cosmic_time::{anim, chain, id, once_cell::sync::Lazy, Instant, Timeline};
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 use tokio::sync::mpsc::Sender;
 
 use crate::{
-    bluetooth::{bluetooth_subscription, BluerDevice, BluerEvent},
-    config, fl,
+    bluetooth::{bluetooth_subscription, set_default_sink, BluerDevice, BluerEvent},
+    config::{self, BluetoothAppletConfig},
+    fl,
 };
 
 static BLUETOOTH_ENABLED: Lazy<id::Toggler> = Lazy::new(id::Toggler::unique);
+static DISCOVERABLE: Lazy<id::Toggler> = Lazy::new(id::Toggler::unique);
+static RENAME_INPUT_ID: Lazy<cosmic::widget::Id> =
+    Lazy::new(|| cosmic::widget::Id::new("rename-input"));
 
 pub fn run() -> cosmic::iced::Result {
     cosmic::applet::run::<CosmicBluetoothApplet>(())
@@ -46,8 +55,16 @@ struct CosmicBluetoothApplet {
     // UI state
     show_visible_devices: bool,
     request_confirmation: Option<(BluerDevice, String, Sender<bool>)>,
+    /// Devices with a connection attempt in progress, which BlueZ doesn't report.
+    connecting: HashSet<Address>,
+    /// Devices whose last connection attempt failed.
+    connect_failed: HashSet<Address>,
+    /// Device being renamed, with the alias typed so far.
+    renaming: Option<(Address, String)>,
     token_tx: Option<calloop::channel::Sender<TokenRequest>>,
     timeline: Timeline,
+    discoverable_since: Option<std::time::Instant>,
+    config: BluetoothAppletConfig,
 }
 
 impl CosmicBluetoothApplet {
@@ -59,6 +76,81 @@ impl CosmicBluetoothApplet {
         }
         .to_string();
     }
+
+    fn update_state(&mut self, state: BluerState) {
+        if self.bluer_state.bluetooth_enabled != state.bluetooth_enabled {
+            self.timeline
+                .set_chain(if state.bluetooth_enabled {
+                    chain::Toggler::on(BLUETOOTH_ENABLED.clone(), 1.0)
+                } else {
+                    chain::Toggler::off(BLUETOOTH_ENABLED.clone(), 1.0)
+                })
+                .start();
+        }
+        if self.bluer_state.discoverable != state.discoverable {
+            self.timeline
+                .set_chain(if state.discoverable {
+                    chain::Toggler::on(DISCOVERABLE.clone(), 1.0)
+                } else {
+                    chain::Toggler::off(DISCOVERABLE.clone(), 1.0)
+                })
+                .start();
+        }
+        if !state.discoverable {
+            self.discoverable_since = None;
+        } else if self.discoverable_since.is_none() {
+            self.discoverable_since = Some(std::time::Instant::now());
+        }
+        self.bluer_state = state;
+        for d in &mut self.bluer_state.devices {
+            if !self.connecting.contains(&d.address) {
+                continue;
+            }
+            if d.status == BluerDeviceStatus::Connected {
+                self.connecting.remove(&d.address);
+            } else {
+                d.status = BluerDeviceStatus::Connecting;
+            }
+        }
+    }
+
+    /// Entry replacing a known device's row while it's being renamed.
+    fn rename_view<'a>(&self, dev: &BluerDevice, alias: &'a str) -> Element<'a, Message> {
+        let Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+        padded_control(
+            column![
+                row![
+                    icon::from_name(dev.icon.as_str()).size(16).symbolic(true),
+                    text_input("", alias)
+                        .id(RENAME_INPUT_ID.clone())
+                        .on_input(Message::RenameInput)
+                        .on_submit(Message::SubmitRename),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(12),
+                row![
+                    iced::widget::horizontal_space(),
+                    button::standard(fl!("cancel")).on_press(Message::CancelRename),
+                    button::suggested(fl!("rename")).on_press(Message::SubmitRename),
+                ]
+                .spacing(space_xxs),
+            ]
+            .spacing(space_xxs),
+        )
+        .into()
+    }
+
+    /// Whole minutes, rounded up, until the adapter stops being discoverable.
+    fn discoverable_minutes_remaining(&self) -> Option<u64> {
+        let since = self.discoverable_since?;
+        let timeout = Duration::from_secs(self.bluer_state.discoverable_timeout.into());
+        if timeout.is_zero() {
+            return None;
+        }
+        let remaining = timeout.saturating_sub(since.elapsed()).as_secs();
+        Some(remaining.div_ceil(60).max(1))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -75,6 +167,13 @@ enum Message {
     OpenSettings,
     Frame(Instant),
     ToggleBluetooth(chain::Toggler, bool),
+    ToggleDiscoverable(chain::Toggler, bool),
+    ConfigChanged(BluetoothAppletConfig),
+    StartRename(Address),
+    RenameInput(String),
+    SubmitRename,
+    CancelRename,
+    UseAsAudioOutput(Address),
 }
 
 impl cosmic::Application for CosmicBluetoothApplet {
@@ -92,6 +191,10 @@ impl cosmic::Application for CosmicBluetoothApplet {
                 core,
                 icon_name: "bluetooth-symbolic".to_string(),
                 token_tx: None,
+                config: cosmic_config::Config::new(config::APP_ID, BluetoothAppletConfig::VERSION)
+                    .ok()
+                    .and_then(|c| BluetoothAppletConfig::get_entry(&c).ok())
+                    .unwrap_or_default(),
                 ..Default::default()
             },
             Task::none(),
@@ -113,6 +216,8 @@ impl cosmic::Application for CosmicBluetoothApplet {
         match message {
             Message::TogglePopup => {
                 if let Some(p) = self.popup.take() {
+                    self.renaming = None;
+                    self.connect_failed.clear();
                     return destroy_popup(p);
                 } else {
                     // TODO request update of state maybe
@@ -157,20 +262,29 @@ impl cosmic::Application for CosmicBluetoothApplet {
                     state,
                     err_msg,
                 } => {
+                    let connected = err_msg.is_none();
                     if let Some(err_msg) = err_msg {
                         eprintln!("bluetooth request error: {}", err_msg);
                     }
-                    if self.bluer_state.bluetooth_enabled != state.bluetooth_enabled {
-                        self.timeline
-                            .set_chain(if state.bluetooth_enabled {
-                                chain::Toggler::on(BLUETOOTH_ENABLED.clone(), 1.0)
-                            } else {
-                                chain::Toggler::off(BLUETOOTH_ENABLED.clone(), 1.0)
-                            })
-                            .start();
+                    if let BluerRequest::ConnectDevice(address) = &req {
+                        // automatic reconnects aren't tracked or reported
+                        if self.connecting.remove(address) && !connected {
+                            self.connect_failed.insert(*address);
+                        }
+                    }
+                    self.update_state(state);
+                    if let BluerRequest::ConnectDevice(address) = &req {
+                        if connected
+                            && self.config.set_audio_output_on_connect
+                            && self
+                                .bluer_state
+                                .devices
+                                .iter()
+                                .any(|d| d.address == *address && d.is_audio())
+                        {
+                            tokio::spawn(set_default_sink(*address));
+                        }
                     }
-
-                    self.bluer_state = state;
                     // TODO special handling for some requests
                     match req {
                         BluerRequest::StateUpdate
@@ -188,10 +302,10 @@ impl cosmic::Application for CosmicBluetoothApplet {
                 }
                 BluerEvent::Init { sender, state } => {
                     self.bluer_sender.replace(sender);
-                    self.bluer_state = state;
+                    self.update_state(state);
                 }
                 BluerEvent::DevicesChanged { state } => {
-                    self.bluer_state = state;
+                    self.update_state(state);
                 }
                 BluerEvent::Finished => {
                     // TODO should this exit with an error causing a restart?
@@ -237,6 +351,8 @@ impl cosmic::Application for CosmicBluetoothApplet {
                         self.bluer_state.bluetooth_enabled = *enabled;
                     }
                     BluerRequest::ConnectDevice(add) => {
+                        self.connecting.insert(*add);
+                        self.connect_failed.remove(add);
                         if let Some(d) = self
                             .bluer_state
                             .devices
@@ -266,6 +382,20 @@ impl cosmic::Application for CosmicBluetoothApplet {
                             d.status = BluerDeviceStatus::Pairing;
                         }
                     }
+                    BluerRequest::CancelConnect(add) => {
+                        self.connecting.remove(add);
+                    }
+                    BluerRequest::SetAlias(add, alias) => {
+                        self.renaming = None;
+                        if let Some(d) = self
+                            .bluer_state
+                            .devices
+                            .iter_mut()
+                            .find(|d| d.address == *add)
+                        {
+                            d.name = alias.clone();
+                        }
+                    }
                     _ => {} // TODO
                 }
                 if let Some(tx) = self.bluer_sender.as_mut().cloned() {
@@ -291,8 +421,40 @@ impl cosmic::Application for CosmicBluetoothApplet {
             Message::CloseRequested(id) => {
                 if Some(id) == self.popup {
                     self.popup = None;
+                    self.renaming = None;
+                }
+            }
+            Message::UseAsAudioOutput(address) => {
+                tokio::spawn(set_default_sink(address));
+            }
+            Message::StartRename(address) => {
+                if let Some(d) = self
+                    .bluer_state
+                    .devices
+                    .iter()
+                    .find(|d| d.address == address)
+                {
+                    self.renaming = Some((address, d.name.clone()));
+                    return text_input::focus(RENAME_INPUT_ID.clone());
+                }
+            }
+            Message::RenameInput(alias) => {
+                if let Some((_, input)) = self.renaming.as_mut() {
+                    *input = alias;
+                }
+            }
+            Message::SubmitRename => {
+                if let Some((address, alias)) = self.renaming.as_ref() {
+                    let alias = alias.trim();
+                    if !alias.is_empty() {
+                        let request = BluerRequest::SetAlias(*address, alias.to_string());
+                        return self.update(Message::Request(request));
+                    }
                 }
             }
+            Message::CancelRename => {
+                self.renaming = None;
+            }
             Message::OpenSettings => {
                 let exec = "cosmic-settings bluetooth".to_string();
                 if let Some(tx) = self.token_tx.as_ref() {
@@ -320,6 +482,9 @@ impl cosmic::Application for CosmicBluetoothApplet {
                 }
             },
             Message::Frame(instant) => self.timeline.now(instant),
+            Message::ConfigChanged(config) => {
+                self.config = config;
+            }
             Message::ToggleBluetooth(chain, enabled) => {
                 if self.bluer_state.bluetooth_enabled == enabled {
                     return Task::none();
@@ -332,6 +497,19 @@ impl cosmic::Application for CosmicBluetoothApplet {
                     });
                 }
             }
+            Message::ToggleDiscoverable(chain, enabled) => {
+                if self.bluer_state.discoverable == enabled {
+                    return Task::none();
+                }
+                self.timeline.set_chain(chain).start();
+                self.bluer_state.discoverable = enabled;
+                self.discoverable_since = enabled.then(std::time::Instant::now);
+                if let Some(tx) = self.bluer_sender.clone() {
+                    tokio::spawn(async move {
+                        let _ = tx.send(BluerRequest::SetDiscoverable(enabled)).await;
+                    });
+                }
+            }
         }
         self.update_icon();
         Task::none()
@@ -357,6 +535,11 @@ impl cosmic::Application for CosmicBluetoothApplet {
                 .as_ref()
                 .map_or(false, |(dev, _, _)| d.address == dev.address)
         }) {
+            if let Some((_, alias)) = self.renaming.as_ref().filter(|(a, _)| *a == dev.address) {
+                known_bluetooth.push(self.rename_view(dev, alias));
+                continue;
+            }
+
             let mut row = row![
                 icon::from_name(dev.icon.as_str()).size(16).symbolic(true),
                 text::body(dev.name.clone())
@@ -367,12 +550,8 @@ impl cosmic::Application for CosmicBluetoothApplet {
             .align_y(Alignment::Center)
             .spacing(12);
 
-            if let Some(DeviceProperty::BatteryPercentage(battery)) = dev
-                .properties
-                .iter()
-                .find(|p| matches!(p, DeviceProperty::BatteryPercentage(_)))
-            {
-                let icon = match *battery {
+            if let Some(battery) = dev.battery_percentage() {
+                let icon = match battery {
                     b if b >= 20 && b < 40 => "battery-low",
                     b if b < 20 => "battery-caution",
                     _ => "battery",
@@ -394,12 +573,27 @@ impl cosmic::Application for CosmicBluetoothApplet {
 
             match &dev.status {
                 BluerDeviceStatus::Connected => {
+                    if dev.is_audio() {
+                        row = row.push(tooltip(
+                            button::icon(icon::from_name("audio-speakers-symbolic").size(16))
+                                .on_press(Message::UseAsAudioOutput(dev.address)),
+                            text::body(fl!("use-as-audio-output")),
+                            tooltip::Position::Bottom,
+                        ));
+                    }
                     row = row.push(
                         text::body(fl!("connected"))
                             .align_x(Alignment::End)
                             .align_y(Alignment::Center),
                     );
                 }
+                BluerDeviceStatus::Paired if self.connect_failed.contains(&dev.address) => {
+                    row = row.push(
+                        text::caption(fl!("connect-failed"))
+                            .align_x(Alignment::End)
+                            .align_y(Alignment::Center),
+                    );
+                }
                 BluerDeviceStatus::Paired => {}
                 BluerDeviceStatus::Connecting | BluerDeviceStatus::Disconnecting => {
                     row = row.push(
@@ -412,24 +606,24 @@ impl cosmic::Application for CosmicBluetoothApplet {
             };
 
             known_bluetooth.push(
-                menu_button(row)
-                    .on_press(match dev.status {
-                        BluerDeviceStatus::Connected => {
-                            Message::Request(BluerRequest::DisconnectDevice(dev.address))
-                        }
-                        BluerDeviceStatus::Disconnected => {
-                            Message::Request(BluerRequest::PairDevice(dev.address))
-                        }
-                        BluerDeviceStatus::Paired => {
-                            Message::Request(BluerRequest::ConnectDevice(dev.address))
-                        }
-                        BluerDeviceStatus::Connecting => {
-                            Message::Request(BluerRequest::CancelConnect(dev.address))
-                        }
-                        BluerDeviceStatus::Disconnecting => Message::Ignore, // Start connecting?
-                        BluerDeviceStatus::Pairing => Message::Ignore,       // Cancel pairing?
-                    })
-                    .into(),
+                mouse_area(menu_button(row).on_press(match dev.status {
+                    BluerDeviceStatus::Connected => {
+                        Message::Request(BluerRequest::DisconnectDevice(dev.address))
+                    }
+                    BluerDeviceStatus::Disconnected => {
+                        Message::Request(BluerRequest::PairDevice(dev.address))
+                    }
+                    BluerDeviceStatus::Paired => {
+                        Message::Request(BluerRequest::ConnectDevice(dev.address))
+                    }
+                    BluerDeviceStatus::Connecting => {
+                        Message::Request(BluerRequest::CancelConnect(dev.address))
+                    }
+                    BluerDeviceStatus::Disconnecting => Message::Ignore, // Start connecting?
+                    BluerDeviceStatus::Pairing => Message::Ignore,       // Cancel pairing?
+                }))
+                .on_right_press(Message::StartRename(dev.address))
+                .into(),
             );
         }
 
@@ -447,6 +641,31 @@ impl cosmic::Application for CosmicBluetoothApplet {
         ),],]
         .align_x(Alignment::Center)
         .padding([8, 0]);
+        if self.bluer_state.bluetooth_enabled {
+            let description = match self.discoverable_minutes_remaining() {
+                Some(minutes) => fl!(
+                    "discoverable-remaining",
+                    name = self.bluer_state.adapter_name.as_str(),
+                    minutes = minutes
+                ),
+                None => self.bluer_state.adapter_name.clone(),
+            };
+            content = content.push(padded_control(
+                column![
+                    anim!(
+                        DISCOVERABLE,
+                        &self.timeline,
+                        fl!("discoverable"),
+                        self.bluer_state.discoverable,
+                        Message::ToggleDiscoverable,
+                    )
+                    .text_size(14)
+                    .width(Length::Fill),
+                    text::caption(description),
+                ]
+                .spacing(space_xxs),
+            ));
+        }
         if !known_bluetooth.is_empty() {
             content = content
                 .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]));
@@ -565,9 +784,24 @@ impl cosmic::Application for CosmicBluetoothApplet {
     }
 
     fn subscription(&self) -> Subscription<Message> {
+        // keep the remaining discoverable time current
+        let discoverable = if self.popup.is_some() && self.discoverable_since.is_some() {
+            iced::time::every(Duration::from_secs(5))
+                .map(|_| Message::Request(BluerRequest::ReadDiscoverable))
+        } else {
+            Subscription::none()
+        };
+
         Subscription::batch(vec![
+            discoverable,
             activation_token_subscription(0).map(Message::Token),
             bluetooth_subscription(0).map(Message::BluetoothEvent),
+            self.core.watch_config(config::APP_ID).map(|u| {
+                for err in u.errors {
+                    tracing::error!(?err, "Error watching config");
+                }
+                Message::ConfigChanged(u.config)
+            }),
             self.timeline
                 .as_subscription()
                 .map(|(_, now)| Message::Frame(now)),
@@ -6,7 +6,7 @@ use std::{collections::HashMap, fmt::Debug, hash::Hash, mem, sync::Arc, time::Du
 pub use bluer::DeviceProperty;
 use bluer::{
     agent::{Agent, AgentHandle},
-    Adapter, Address, Session, Uuid,
+    Adapter, Address, DeviceEvent, Session, Uuid,
 };
 
 use cosmic::{
@@ -29,6 +29,22 @@ use tokio::{
     time::timeout,
 };
 
+/// Seconds the adapter stays discoverable after being made discoverable from the applet.
+const DISCOVERABLE_TIMEOUT: u32 = 180;
+
+/// Time to wait for a device to connect before giving up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A2DP sink, headset and hands-free profiles, the ones that play audio.
+const AUDIO_SERVICES: [Uuid; 3] = [
+    Uuid::from_u128(0x0000110b_0000_1000_8000_00805f9b34fb),
+    Uuid::from_u128(0x00001108_0000_1000_8000_00805f9b34fb),
+    Uuid::from_u128(0x0000111e_0000_1000_8000_00805f9b34fb),
+];
+
+/// How often the device event streams are rebuilt to pick up added and removed devices.
+const DEVICE_EVENTS_REFRESH: Duration = Duration::from_secs(30);
+
 pub fn bluetooth_subscription<I: 'static + Hash + Copy + Send + Sync + Debug>(
     id: I,
 ) -> iced::Subscription<BluerEvent> {
@@ -144,7 +160,11 @@ pub enum BluerRequest {
     ConnectDevice(Address),
     DisconnectDevice(Address),
     CancelConnect(Address),
+    SetAlias(Address, String),
     StateUpdate,
+    /// Re-reads the adapter state, e.g. whether it is still discoverable,
+    /// without scheduling further updates like `StateUpdate`.
+    ReadDiscoverable,
 }
 
 #[derive(Debug, Clone)]
@@ -171,6 +191,9 @@ pub struct BluerState {
     pub bluetooth_enabled: bool,
     pub discoverable: bool,
     pub pairable: bool,
+    pub adapter_name: String,
+    /// Seconds after which the adapter stops being discoverable. 0 means never.
+    pub discoverable_timeout: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -224,11 +247,15 @@ impl PartialEq for BluerDevice {
 
 impl BluerDevice {
     pub async fn from_device(device: &bluer::Device) -> Self {
-        let mut name = device
-            .name()
-            .await
-            .unwrap_or_default()
-            .unwrap_or_else(|| device.address().to_string());
+        // the alias is the name chosen by the user, or the device's own name
+        let mut name = match device.alias().await {
+            Ok(alias) if !alias.is_empty() => alias,
+            _ => device
+                .name()
+                .await
+                .unwrap_or_default()
+                .unwrap_or_else(|| device.address().to_string()),
+        };
         if name.is_empty() {
             name = device.address().to_string();
         };
@@ -262,6 +289,25 @@ impl BluerDevice {
         }
     }
 
+    /// Charge reported through the device's `Battery1` interface.
+    pub fn battery_percentage(&self) -> Option<u8> {
+        self.properties.iter().find_map(|p| match p {
+            DeviceProperty::BatteryPercentage(battery) => Some(*battery),
+            _ => None,
+        })
+    }
+
+    /// Whether the device is an audio output, such as a headset or speaker,
+    /// going by its advertised services or else its device class.
+    pub fn is_audio(&self) -> bool {
+        self.properties.iter().any(|p| match p {
+            DeviceProperty::Uuids(uuids) => uuids.iter().any(|uuid| AUDIO_SERVICES.contains(uuid)),
+            // major device class "Audio/Video"
+            DeviceProperty::Class(class) => (class >> 8) & 0x1f == 0x04,
+            _ => false,
+        })
+    }
+
     fn paired_and_trusted(&self) -> bool {
         self.properties
             .iter()
@@ -504,6 +550,7 @@ impl BluerSessionState {
         self_.process_requests(req_rx);
         self_.process_changes();
         self_.listen_bluetooth_power_changes();
+        self_.listen_battery_changes();
 
         Ok(self_)
     }
@@ -524,6 +571,11 @@ impl BluerSessionState {
                         bluetooth_enabled: status,
                         discoverable: adapter_clone.is_discoverable().await.unwrap_or_default(),
                         pairable: adapter_clone.is_pairable().await.unwrap_or_default(),
+                        adapter_name: adapter_clone.alias().await.unwrap_or_default(),
+                        discoverable_timeout: adapter_clone
+                            .discoverable_timeout()
+                            .await
+                            .unwrap_or_default(),
                     };
                     if state.bluetooth_enabled {
                         for d in &state.devices {
@@ -539,6 +591,44 @@ impl BluerSessionState {
         });
     }
 
+    /// The adapter's change stream doesn't include device properties, so the
+    /// devices' own events are watched for battery level changes.
+    fn listen_battery_changes(&self) {
+        let tx = self.tx.clone();
+        let adapter_clone = self.adapter.clone();
+        let _handle: JoinHandle<anyhow::Result<()>> = spawn(async move {
+            loop {
+                let mut streams = Vec::new();
+                for address in adapter_clone.device_addresses().await.unwrap_or_default() {
+                    let Ok(device) = adapter_clone.device(address) else {
+                        continue;
+                    };
+                    if let Ok(events) = device.events().await {
+                        streams.push(Box::pin(events));
+                    }
+                }
+                if streams.is_empty() {
+                    tokio::time::sleep(DEVICE_EVENTS_REFRESH).await;
+                    continue;
+                }
+
+                let mut events = futures::stream::select_all(streams);
+                let _ = timeout(DEVICE_EVENTS_REFRESH, async {
+                    while let Some(event) = events.next().await {
+                        if matches!(
+                            event,
+                            DeviceEvent::PropertyChanged(DeviceProperty::BatteryPercentage(_))
+                        ) {
+                            let state = adapter_state(&adapter_clone).await;
+                            let _ = tx.send(BluerSessionEvent::ChangesProcessed(state)).await;
+                        }
+                    }
+                })
+                .await;
+            }
+        });
+    }
+
     // Note: For some reason, this doesn't actually seem to work so well. it seems unreliable...
     pub(crate) fn process_changes(&self) {
         let tx = self.tx.clone();
@@ -582,6 +672,11 @@ impl BluerSessionState {
                                     .await
                                     .unwrap_or_default(),
                                 pairable: adapter_clone.is_pairable().await.unwrap_or_default(),
+                                adapter_name: adapter_clone.alias().await.unwrap_or_default(),
+                                discoverable_timeout: adapter_clone
+                                    .discoverable_timeout()
+                                    .await
+                                    .unwrap_or_default(),
                             }))
                             .await;
                         // reset timeout
@@ -639,12 +734,17 @@ impl BluerSessionState {
                             if let Err(err) = res {
                                 err_msg = Some(err.to_string());
                             } else if let Ok(device) = res {
-                                let res = device.connect().await;
-                                if let Err(err) = res {
-                                    err_msg = Some(err.to_string());
-                                } else {
-                                    if let Err(err) = device.set_trusted(true).await {
-                                        tracing::error!(?err, "Failed to trust device.");
+                                match timeout(CONNECT_TIMEOUT, device.connect()).await {
+                                    Err(_) => {
+                                        err_msg = Some("connecting timed out".to_string());
+                                    }
+                                    Ok(Err(err)) => {
+                                        err_msg = Some(err.to_string());
+                                    }
+                                    Ok(Ok(())) => {
+                                        if let Err(err) = device.set_trusted(true).await {
+                                            tracing::error!(?err, "Failed to trust device.");
+                                        }
                                     }
                                 }
                             }
@@ -668,7 +768,17 @@ impl BluerSessionState {
                                 err_msg = Some("No active connection request found".to_string());
                             }
                         }
-                        BluerRequest::StateUpdate => {}
+                        BluerRequest::SetAlias(address, alias) => {
+                            let res = adapter_clone.device(*address);
+                            if let Err(err) = res {
+                                err_msg = Some(err.to_string());
+                            } else if let Ok(device) = res {
+                                if let Err(err) = device.set_alias(alias.clone()).await {
+                                    err_msg = Some(err.to_string());
+                                }
+                            }
+                        }
+                        BluerRequest::StateUpdate | BluerRequest::ReadDiscoverable => {}
                         BluerRequest::SetPairable(enabled) => {
                             let res = adapter_clone.set_pairable(*enabled).await;
                             if let Err(e) = res {
@@ -676,6 +786,12 @@ impl BluerSessionState {
                             }
                         }
                         BluerRequest::SetDiscoverable(enabled) => {
+                            // make sure discoverability reverts on its own
+                            if *enabled {
+                                let _ = adapter_clone
+                                    .set_discoverable_timeout(DISCOVERABLE_TIMEOUT)
+                                    .await;
+                            }
                             let res = adapter_clone.set_discoverable(*enabled).await;
                             if let Err(e) = res {
                                 err_msg = Some(e.to_string());
@@ -688,6 +804,11 @@ impl BluerSessionState {
                         bluetooth_enabled: adapter_clone.is_powered().await.unwrap_or_default(),
                         discoverable: adapter_clone.is_discoverable().await.unwrap_or_default(),
                         pairable: adapter_clone.is_pairable().await.unwrap_or_default(),
+                        adapter_name: adapter_clone.alias().await.unwrap_or_default(),
+                        discoverable_timeout: adapter_clone
+                            .discoverable_timeout()
+                            .await
+                            .unwrap_or_default(),
                     };
 
                     let _ = tx_clone
@@ -710,13 +831,19 @@ impl BluerSessionState {
     }
 
     pub(crate) async fn bluer_state(&self) -> BluerState {
-        BluerState {
-            devices: build_device_list(&self.adapter).await,
-            // TODO is this a proper way of checking if bluetooth is enabled?
-            bluetooth_enabled: self.adapter.is_powered().await.unwrap_or_default(),
-            discoverable: self.adapter.is_discoverable().await.unwrap_or_default(),
-            pairable: self.adapter.is_pairable().await.unwrap_or_default(),
-        }
+        adapter_state(&self.adapter).await
+    }
+}
+
+async fn adapter_state(adapter: &Adapter) -> BluerState {
+    BluerState {
+        devices: build_device_list(adapter).await,
+        // TODO is this a proper way of checking if bluetooth is enabled?
+        bluetooth_enabled: adapter.is_powered().await.unwrap_or_default(),
+        discoverable: adapter.is_discoverable().await.unwrap_or_default(),
+        pairable: adapter.is_pairable().await.unwrap_or_default(),
+        adapter_name: adapter.alias().await.unwrap_or_default(),
+        discoverable_timeout: adapter.discoverable_timeout().await.unwrap_or_default(),
     }
 }
 
@@ -735,3 +862,17 @@ async fn build_device_list(adapter: &Adapter) -> Vec<BluerDevice> {
     devices.sort();
     devices
 }
+
+/// Make the sound server's sink for a Bluetooth device the default output.
+pub async fn set_default_sink(address: Address) {
+    let address = address.to_string();
+    let result = tokio::task::spawn_blocking(move || {
+        cosmic_applet_audio::set_bluetooth_default_sink(&address).map_err(anyhow::Error::msg)
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result);
+    if let Err(err) = result {
+        tracing::error!(?err, "Failed to set the default audio sink.");
+    }
+}
@@ -1,4 +1,14 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
 pub const APP_ID: &str = "com.system76.CosmicAppletBluetooth";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct BluetoothAppletConfig {
+    /// Make an audio device the default output after connecting to it from the applet.
+    pub set_audio_output_on_connect: bool,
+}
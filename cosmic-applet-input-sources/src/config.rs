@@ -0,0 +1,31 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
+/// How the current layout is shown on the panel.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PanelLabel {
+    /// The xkb layout code, e.g. `us`.
+    #[default]
+    Code,
+    /// The flag of the layout's country, or the code when there is none.
+    Flag,
+    /// The layout description, e.g. "English (US)".
+    Description,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct InputSourcesAppletConfig {
+    /// Append a short abbreviation of the layout variant to the panel label,
+    /// e.g. `us-dvo` instead of `us`.
+    pub show_variant: bool,
+    /// Left-clicking the panel button switches to the next layout, and
+    /// right-clicking opens the popup instead.
+    pub cycle_on_click: bool,
+    /// What the panel button shows for the current layout. `show_variant`
+    /// applies wherever the layout code is shown.
+    pub panel_label: PanelLabel,
+}
@@ -1,8 +1,10 @@
 // Copyright 2024 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
+mod config;
 mod localize;
 
+use config::{InputSourcesAppletConfig, PanelLabel};
 use cosmic::iced::{Alignment, Length};
 use cosmic::{
     app::Core,
@@ -10,6 +12,7 @@ use cosmic::{
     cosmic_config::{self, ConfigSet, CosmicConfigEntry},
     cosmic_theme::Spacing,
     iced::{
+        mouse::ScrollDelta,
         platform_specific::shell::commands::popup::{destroy_popup, get_popup},
         widget::{column, row},
         window::Id,
@@ -21,6 +24,7 @@ use cosmic::{
     theme,
     widget::{self, horizontal_space, vertical_space},
 };
+use cosmic_applet_scroll::ScrollSteps;
 use cosmic_comp_config::CosmicCompConfig;
 use xkb_data::KeyboardLayout;
 
@@ -67,6 +71,67 @@ pub struct ActiveLayout {
     layout: String,
     description: String,
     variant: String,
+    /// Description of the variant alone, e.g. "Dvorak" for `us(dvorak)`.
+    variant_description: Option<String>,
+}
+
+impl ActiveLayout {
+    /// Full name shown in the popup, e.g. "English (US) — Dvorak".
+    fn title(&self) -> String {
+        match &self.variant_description {
+            Some(variant) => format!("{} — {variant}", self.description),
+            None => self.description.clone(),
+        }
+    }
+
+    /// Layout code shown below the title, e.g. `us (dvorak)`.
+    fn code(&self) -> String {
+        if self.variant.is_empty() {
+            self.layout.clone()
+        } else {
+            format!("{} ({})", self.layout, self.variant)
+        }
+    }
+
+    /// Label shown on the panel.
+    fn panel_label(&self, config: &InputSourcesAppletConfig) -> String {
+        match config.panel_label {
+            PanelLabel::Code => self.short_code(config.show_variant),
+            PanelLabel::Flag => {
+                flag(&self.layout).unwrap_or_else(|| self.short_code(config.show_variant))
+            }
+            PanelLabel::Description if !self.description.is_empty() => self.description.clone(),
+            PanelLabel::Description => self.short_code(config.show_variant),
+        }
+    }
+
+    /// Layout code, optionally followed by an abbreviation of the variant.
+    fn short_code(&self, show_variant: bool) -> String {
+        if !show_variant || self.variant.is_empty() {
+            return self.layout.clone();
+        }
+
+        let abbreviation: String = self
+            .variant
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .take(3)
+            .collect();
+        format!("{}-{abbreviation}", self.layout)
+    }
+}
+
+/// Flag emoji for layouts named after a country, e.g. `de`. Layouts named
+/// after a language or region, such as `ara` or `latam`, have none.
+fn flag(layout: &str) -> Option<String> {
+    if layout.len() != 2 || !layout.bytes().all(|b| b.is_ascii_lowercase()) {
+        return None;
+    }
+
+    layout
+        .bytes()
+        .map(|b| char::from_u32(0x1F1E6 + u32::from(b - b'a')))
+        .collect()
 }
 
 pub struct Window {
@@ -76,6 +141,9 @@ pub struct Window {
     comp_config_handler: Option<cosmic_config::Config>,
     layouts: Vec<KeyboardLayout>,
     active_layouts: Vec<ActiveLayout>,
+    config: InputSourcesAppletConfig,
+    /// Smooth scrolling not yet turned into a layout switch.
+    scroll: ScrollSteps,
 }
 
 #[derive(Clone, Debug)]
@@ -83,7 +151,14 @@ pub enum Message {
     TogglePopup,
     PopupClosed(Id),
     CompConfig(Box<CosmicCompConfig>),
+    ConfigChanged(InputSourcesAppletConfig),
     SetActiveLayout(usize),
+    /// Make the next active layout current, moving the current one to the end.
+    NextLayout,
+    /// Make the last active layout current, moving the others down.
+    PreviousLayout,
+    /// Scroll over the panel button; up goes forward, down goes back.
+    Scroll(ScrollDelta),
     KeyboardSettings,
 }
 
@@ -117,6 +192,11 @@ impl cosmic::Application for Window {
             popup: None,
             comp_config: flags.comp_config,
             active_layouts: Vec::new(),
+            config: cosmic_config::Config::new(ID, InputSourcesAppletConfig::VERSION)
+                .ok()
+                .and_then(|c| InputSourcesAppletConfig::get_entry(&c).ok())
+                .unwrap_or_default(),
+            scroll: ScrollSteps::default(),
         };
         (window, Task::none())
     }
@@ -160,6 +240,10 @@ impl cosmic::Application for Window {
                 self.active_layouts = self.update_xkb();
             }
 
+            Message::ConfigChanged(config) => {
+                self.config = config;
+            }
+
             Message::KeyboardSettings => {
                 let mut cmd = std::process::Command::new("cosmic-settings");
                 cmd.arg("keyboard");
@@ -172,27 +256,35 @@ impl cosmic::Application for Window {
                 }
 
                 self.active_layouts.swap(0, pos);
-                let mut new_layout = String::new();
-                let mut new_variant = String::new();
-
-                for layout in &self.active_layouts {
-                    new_layout.push_str(&layout.layout);
-                    new_layout.push(',');
-                    new_variant.push_str(&layout.variant);
-                    new_variant.push(',');
+                self.write_active_layouts();
+            }
+
+            Message::NextLayout => {
+                if self.active_layouts.len() < 2 {
+                    return Task::none();
                 }
 
-                let _excess_comma = new_layout.pop();
-                let _excess_comma = new_variant.pop();
+                self.active_layouts.rotate_left(1);
+                self.write_active_layouts();
+            }
 
-                self.comp_config.xkb_config.layout = new_layout;
-                self.comp_config.xkb_config.variant = new_variant;
-                if let Some(comp_config_handler) = &self.comp_config_handler {
-                    if let Err(err) =
-                        comp_config_handler.set("xkb_config", &self.comp_config.xkb_config)
-                    {
-                        tracing::error!("Failed to set config 'xkb_config' {err}");
-                    }
+            Message::PreviousLayout => {
+                if self.active_layouts.len() < 2 {
+                    return Task::none();
+                }
+
+                self.active_layouts.rotate_right(1);
+                self.write_active_layouts();
+            }
+
+            Message::Scroll(delta) => {
+                let (x, y) = self.scroll.steps(delta);
+                let steps = (x + y).signum();
+
+                if steps > 0.0 {
+                    return self.update(Message::NextLayout);
+                } else if steps < 0.0 {
+                    return self.update(Message::PreviousLayout);
                 }
             }
         }
@@ -204,10 +296,11 @@ impl cosmic::Application for Window {
         let input_source_text = self.core.applet.text(
             self.active_layouts
                 .first()
-                .map_or("", |l| l.layout.as_str()),
+                .map(|l| l.panel_label(&self.config))
+                .unwrap_or_default(),
         );
 
-        cosmic::widget::button::custom(
+        let button = cosmic::widget::button::custom(
             row!(
                 column!(
                     input_source_text,
@@ -229,9 +322,18 @@ impl cosmic::Application for Window {
             .width(Length::Shrink)
             .height(Length::Shrink),
         )
-        .on_press_down(Message::TogglePopup)
-        .class(cosmic::theme::Button::AppletIcon)
-        .into()
+        .class(cosmic::theme::Button::AppletIcon);
+
+        if self.config.cycle_on_click {
+            cosmic::iced::widget::mouse_area(button.on_press_down(Message::NextLayout))
+                .on_right_press(Message::TogglePopup)
+                .on_scroll(Message::Scroll)
+                .into()
+        } else {
+            cosmic::iced::widget::mouse_area(button.on_press_down(Message::TogglePopup))
+                .on_scroll(Message::Scroll)
+                .into()
+        }
     }
 
     fn view_window(&self, _id: Id) -> Element<Self::Message> {
@@ -243,8 +345,8 @@ impl cosmic::Application for Window {
             widget::column::with_capacity(4 + self.active_layouts.len()).padding([8, 0]);
         for (id, layout) in self.active_layouts.iter().enumerate() {
             let group = widget::column::with_capacity(2)
-                .push(widget::text::body(layout.description.clone()))
-                .push(widget::text::caption(layout.layout.clone()));
+                .push(widget::text::body(layout.title()))
+                .push(widget::text::caption(layout.code()));
             content_list = content_list
                 .push(applet::menu_button(group).on_press(Message::SetActiveLayout(id)));
         }
@@ -274,9 +376,20 @@ impl cosmic::Application for Window {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        self.core
-            .watch_config("com.system76.CosmicComp")
-            .map(|update| {
+        Subscription::batch([
+            self.core
+                .watch_config("com.system76.CosmicComp")
+                .map(|update| {
+                    if !update.errors.is_empty() {
+                        tracing::error!(
+                            "errors loading config {:?}: {:?}",
+                            update.keys,
+                            update.errors
+                        );
+                    }
+                    Message::CompConfig(Box::new(update.config))
+                }),
+            self.core.watch_config(ID).map(|update| {
                 if !update.errors.is_empty() {
                     tracing::error!(
                         "errors loading config {:?}: {:?}",
@@ -284,8 +397,9 @@ impl cosmic::Application for Window {
                         update.errors
                     );
                 }
-                Message::CompConfig(Box::new(update.config))
-            })
+                Message::ConfigChanged(update.config)
+            }),
+        ])
     }
 
     fn style(&self) -> Option<Appearance> {
@@ -293,6 +407,31 @@ impl cosmic::Application for Window {
     }
 }
 impl Window {
+    /// Store the order of `active_layouts` in the compositor's xkb config,
+    /// the first one being the current layout.
+    fn write_active_layouts(&mut self) {
+        let mut new_layout = String::new();
+        let mut new_variant = String::new();
+
+        for layout in &self.active_layouts {
+            new_layout.push_str(&layout.layout);
+            new_layout.push(',');
+            new_variant.push_str(&layout.variant);
+            new_variant.push(',');
+        }
+
+        let _excess_comma = new_layout.pop();
+        let _excess_comma = new_variant.pop();
+
+        self.comp_config.xkb_config.layout = new_layout;
+        self.comp_config.xkb_config.variant = new_variant;
+        if let Some(comp_config_handler) = &self.comp_config_handler {
+            if let Err(err) = comp_config_handler.set("xkb_config", &self.comp_config.xkb_config) {
+                tracing::error!("Failed to set config 'xkb_config' {err}");
+            }
+        }
+    }
+
     fn update_xkb(&self) -> Vec<ActiveLayout> {
         let mut active_layouts = Vec::new();
         let xkb = &self.comp_config.xkb_config;
@@ -316,6 +455,7 @@ impl Window {
                         description: xkb_layout.description().to_owned(),
                         layout: layout.to_owned(),
                         variant: variant.to_owned(),
+                        variant_description: None,
                     };
 
                     active_layouts.push(active_layout);
@@ -332,9 +472,10 @@ impl Window {
                     }
 
                     let active_layout = ActiveLayout {
-                        description: xkb_variant.description().to_owned(),
+                        description: xkb_layout.description().to_owned(),
                         layout: layout.to_owned(),
                         variant: variant.to_owned(),
+                        variant_description: Some(variant_description(xkb_variant.description())),
                     };
 
                     active_layouts.push(active_layout);
@@ -346,3 +487,15 @@ impl Window {
         active_layouts
     }
 }
+
+/// Strips the layout name from an xkb variant description, so that
+/// "English (Dvorak)" becomes "Dvorak". Descriptions without a parenthesized
+/// suffix are returned unchanged.
+fn variant_description(description: &str) -> String {
+    description
+        .strip_suffix(')')
+        .and_then(|d| d.split_once('('))
+        .map_or(description, |(_, variant)| variant)
+        .trim()
+        .to_owned()
+}
@@ -0,0 +1,3 @@
+restore = Restore
+restore-all = Restore all
+close = Close
@@ -18,23 +18,37 @@ use cosmic::{
     iced::{
         self,
         id::Id as WidgetId,
+        mouse::ScrollDelta,
         platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup},
+        time,
         widget::text,
         window::{self},
         Length, Limits, Subscription,
     },
     widget::{autosize::autosize, mouse_area},
-    Task,
+    Apply, Task,
 };
 
-use cosmic::iced_widget::{Column, Row};
+use cosmic::iced_widget::{column, Column, Row};
 
-use cosmic::{widget::tooltip, Element};
+use cosmic::{
+    applet::menu_button,
+    widget::{divider, tooltip},
+    Element,
+};
+use cosmic_applet_scroll::ScrollSteps;
 use once_cell::sync::Lazy;
+use std::time::{Duration, Instant};
 use wayland_subscription::{
     ToplevelRequest, ToplevelUpdate, WaylandImage, WaylandRequest, WaylandUpdate,
 };
 
+/// How often the thumbnail of the hovered window is captured again.
+const THUMBNAIL_REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+/// How long to wait for a thumbnail before asking again, as failed captures
+/// send nothing back.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(2);
+
 static AUTOSIZE_MAIN_ID: Lazy<WidgetId> = Lazy::new(|| WidgetId::new("autosize-main"));
 
 pub fn run() -> cosmic::iced::Result {
@@ -53,6 +67,15 @@ struct Minimize {
     )>,
     tx: Option<calloop::channel::Sender<WaylandRequest>>,
     overflow_popup: Option<window::Id>,
+    /// The window last activated by scrolling, and where it was in `apps`.
+    scrolled: Option<(ZcosmicToplevelHandleV1, usize)>,
+    scroll: ScrollSteps,
+    /// The window whose thumbnail is kept up to date while the pointer is over it.
+    hovered: Option<ZcosmicToplevelHandleV1>,
+    /// Windows with a thumbnail capture in flight, and when it was asked for.
+    capturing: Vec<(ZcosmicToplevelHandleV1, Instant)>,
+    /// The context menu popup and the window it was opened for.
+    context_menu: Option<(window::Id, ZcosmicToplevelHandleV1)>,
 }
 
 impl Minimize {
@@ -78,6 +101,64 @@ impl Minimize {
         }
         index
     }
+
+    /// Number of apps shown in the panel, the rest are in the overflow popup.
+    fn panel_icon_count(&self) -> usize {
+        self.max_icon_count()
+            .map(|n| {
+                if n < self.apps.len() {
+                    n - 1
+                } else {
+                    self.apps.len()
+                }
+            })
+            .unwrap_or(self.apps.len())
+    }
+
+    /// Position of the button at `index` in a row or column of app buttons.
+    fn icon_rectangle(&self, index: usize) -> iced::Rectangle<i32> {
+        let icon_size = self.core.applet.suggested_size(true).0 as u32
+            + 2 * self.core.applet.suggested_padding(true) as u32;
+        let spacing = self.core.system_theme().cosmic().space_xxs() as u32;
+        let major_axis_len = (icon_size + spacing) * (index as u32);
+        match self.core.applet.anchor {
+            PanelAnchor::Top | PanelAnchor::Bottom => iced::Rectangle {
+                x: major_axis_len as i32,
+                y: 0,
+                width: icon_size as i32,
+                height: icon_size as i32,
+            },
+            PanelAnchor::Left | PanelAnchor::Right => iced::Rectangle {
+                x: 0,
+                y: major_axis_len as i32,
+                width: icon_size as i32,
+                height: icon_size as i32,
+            },
+        }
+    }
+
+    fn close_context_menu(&mut self) -> app::Task<Message> {
+        match self.context_menu.take() {
+            Some((id, _)) => destroy_popup(id),
+            None => Task::none(),
+        }
+    }
+
+    /// Asks for a new thumbnail of a window, unless one is still on its way.
+    /// Windows that can't be captured keep their previous image, or their icon.
+    fn capture_image(&mut self, handle: ZcosmicToplevelHandleV1) {
+        self.capturing
+            .retain(|(_, requested)| requested.elapsed() < CAPTURE_TIMEOUT);
+        if self.capturing.iter().any(|(h, _)| *h == handle) {
+            return;
+        }
+        if let Some(tx) = self.tx.as_ref() {
+            self.capturing.push((handle.clone(), Instant::now()));
+            let _ = tx.send(WaylandRequest::Toplevel(ToplevelRequest::CaptureImage(
+                handle,
+            )));
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +168,15 @@ enum Message {
     Closed(window::Id),
     OpenOverflowPopup,
     CloseOverflowPopup,
+    /// Scroll over the applet; down activates the next window, up the previous one.
+    Scroll(ScrollDelta),
+    HoverEnter(ZcosmicToplevelHandleV1),
+    HoverLeave(ZcosmicToplevelHandleV1),
+    RefreshThumbnail,
+    /// Open the context menu of a window from its button in the given parent window.
+    ContextMenu(window::Id, ZcosmicToplevelHandleV1),
+    RestoreAll,
+    Close(ZcosmicToplevelHandleV1),
 }
 
 impl cosmic::Application for Minimize {
@@ -148,25 +238,86 @@ impl cosmic::Application for Minimize {
                         }
                     }
                     ToplevelUpdate::Remove(handle) => {
+                        if self.hovered.as_ref() == Some(&handle) {
+                            self.hovered = None;
+                        }
+                        self.capturing.retain(|(h, _)| *h != handle);
                         self.apps.retain(|a| a.0 != handle);
                         self.apps.shrink_to_fit();
                     }
                 },
                 WaylandUpdate::Image(handle, img) => {
+                    self.capturing.retain(|(h, _)| *h != handle);
                     if let Some(pos) = self.apps.iter().position(|a| a.0 == handle) {
                         self.apps[pos].3 = Some(img);
                     }
                 }
             },
+            Message::HoverEnter(handle) => {
+                self.hovered = Some(handle.clone());
+                self.capture_image(handle);
+            }
+            Message::HoverLeave(handle) => {
+                if self.hovered.as_ref() == Some(&handle) {
+                    self.hovered = None;
+                }
+            }
+            Message::RefreshThumbnail => {
+                if let Some(handle) = self.hovered.clone() {
+                    self.capture_image(handle);
+                }
+            }
             Message::Activate(handle) => {
                 if let Some(tx) = self.tx.as_ref() {
                     let _ = tx.send(WaylandRequest::Toplevel(ToplevelRequest::Activate(handle)));
                 }
+                return self.close_context_menu();
+            }
+            Message::RestoreAll => {
+                if let Some(tx) = self.tx.as_ref() {
+                    for (handle, ..) in &self.apps {
+                        let _ = tx.send(WaylandRequest::Toplevel(ToplevelRequest::Unminimize(
+                            handle.clone(),
+                        )));
+                    }
+                }
+                return self.close_context_menu();
+            }
+            Message::Close(handle) => {
+                // the window leaves `apps` once its `Remove` event arrives
+                if let Some(tx) = self.tx.as_ref() {
+                    let _ = tx.send(WaylandRequest::Toplevel(ToplevelRequest::Close(handle)));
+                }
+                return self.close_context_menu();
+            }
+            Message::ContextMenu(parent, handle) => {
+                let close = self.close_context_menu();
+                let Some(pos) = self.apps.iter().position(|a| a.0 == handle) else {
+                    return close;
+                };
+                let index = if Some(parent) == self.core.main_window_id() {
+                    pos
+                } else {
+                    pos.saturating_sub(self.panel_icon_count())
+                };
+
+                let new_id = window::Id::unique();
+                self.context_menu = Some((new_id, handle));
+                let mut popup_settings = self
+                    .core
+                    .applet
+                    .get_popup_settings(parent, new_id, None, None, None);
+                popup_settings.positioner.anchor_rect = self.icon_rectangle(index);
+
+                return Task::batch([close, get_popup(popup_settings)]);
             }
             Message::Closed(id) => {
                 if self.overflow_popup.is_some_and(|i| i == id) {
                     self.overflow_popup = None;
                 }
+                if self.context_menu.as_ref().is_some_and(|(i, _)| *i == id) {
+                    self.context_menu = None;
+                }
             }
             Message::OpenOverflowPopup => {
                 if let Some(id) = self.overflow_popup.take() {
@@ -176,24 +327,7 @@ impl cosmic::Application for Minimize {
                     let pos = self.max_icon_count().unwrap_or_default();
 
                     self.overflow_popup = Some(new_id);
-                    let icon_size = self.core.applet.suggested_size(true).0 as u32
-                        + 2 * self.core.applet.suggested_padding(true) as u32;
-                    let spacing = self.core.system_theme().cosmic().space_xxs() as u32;
-                    let major_axis_len = (icon_size + spacing) * (pos.saturating_sub(1) as u32);
-                    let rectangle = match self.core.applet.anchor {
-                        PanelAnchor::Top | PanelAnchor::Bottom => iced::Rectangle {
-                            x: major_axis_len as i32,
-                            y: 0,
-                            width: icon_size as i32,
-                            height: icon_size as i32,
-                        },
-                        PanelAnchor::Left | PanelAnchor::Right => iced::Rectangle {
-                            x: 0,
-                            y: major_axis_len as i32,
-                            width: icon_size as i32,
-                            height: icon_size as i32,
-                        },
-                    };
+                    let rectangle = self.icon_rectangle(pos.saturating_sub(1));
                     let mut popup_settings = self.core.applet.get_popup_settings(
                         self.core.main_window_id().unwrap(),
                         new_id,
@@ -207,25 +341,46 @@ impl cosmic::Application for Minimize {
                 }
             }
             Message::CloseOverflowPopup => todo!(),
+            Message::Scroll(delta) => {
+                let (x, y) = self.scroll.steps(delta);
+                let steps = -(x + y).signum();
+                let len = self.apps.len();
+                if steps == 0.0 || len == 0 {
+                    return Task::none();
+                }
+
+                // an activated window is no longer minimized and leaves the
+                // list, which moves the next window into its place
+                let index = match &self.scrolled {
+                    Some((handle, i)) => match self.apps.iter().position(|a| &a.0 == handle) {
+                        Some(pos) if steps > 0.0 => (pos + 1) % len,
+                        Some(pos) => (pos + len - 1) % len,
+                        None if steps > 0.0 => i % len,
+                        None => (i + len - 1) % len,
+                    },
+                    None if steps > 0.0 => 0,
+                    None => len - 1,
+                };
+                let handle = self.apps[index].0.clone();
+                self.scrolled = Some((handle.clone(), index));
+                return self.update(Message::Activate(handle));
+            }
         };
         Task::none()
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        wayland_subscription::wayland_subscription().map(Message::Wayland)
+        let mut subscriptions =
+            vec![wayland_subscription::wayland_subscription().map(Message::Wayland)];
+        if self.hovered.is_some() {
+            subscriptions
+                .push(time::every(THUMBNAIL_REFRESH_INTERVAL).map(|_| Message::RefreshThumbnail));
+        }
+        Subscription::batch(subscriptions)
     }
 
     fn view(&self) -> Element<Message> {
-        let max_icon_count = self
-            .max_icon_count()
-            .map(|n| {
-                if n < self.apps.len() {
-                    n - 1
-                } else {
-                    self.apps.len()
-                }
-            })
-            .unwrap_or(self.apps.len());
+        let max_icon_count = self.panel_icon_count();
         let (width, _) = self.core.applet.suggested_size(false);
         let padding = self.core.applet.suggested_padding(false);
         let theme = self.core.system_theme().cosmic();
@@ -253,6 +408,13 @@ impl cosmic::Application for Minimize {
                     },
                 )
                 .snap_within_viewport(false)
+                .apply(cosmic::iced::widget::mouse_area)
+                .on_enter(Message::HoverEnter(handle.clone()))
+                .on_exit(Message::HoverLeave(handle.clone()))
+                .on_right_press(Message::ContextMenu(
+                    self.core.main_window_id().unwrap(),
+                    handle.clone(),
+                ))
                 .into()
             });
         let overflow_btn = if max_icon_count < self.apps.len() {
@@ -306,6 +468,11 @@ impl cosmic::Application for Minimize {
             }
         }
 
+        // the iced mouse area, as it reports scrolling
+        let content: Element<_> = cosmic::iced::widget::mouse_area(content)
+            .on_scroll(Message::Scroll)
+            .into();
+
         autosize(
             if self.overflow_popup.is_some() {
                 mouse_area(content)
@@ -320,17 +487,19 @@ impl cosmic::Application for Minimize {
         .into()
     }
 
-    fn view_window(&self, _id: window::Id) -> Element<Self::Message> {
-        let max_icon_count = self
-            .max_icon_count()
-            .map(|n| {
-                if n < self.apps.len() {
-                    n - 1
-                } else {
-                    self.apps.len()
-                }
-            })
-            .unwrap_or(self.apps.len());
+    fn view_window(&self, id: window::Id) -> Element<Self::Message> {
+        if let Some((_, handle)) = self.context_menu.as_ref().filter(|(i, _)| *i == id) {
+            let content = column![
+                menu_button(text(fl!("restore"))).on_press(Message::Activate(handle.clone())),
+                menu_button(text(fl!("restore-all"))).on_press(Message::RestoreAll),
+                divider::horizontal::default(),
+                menu_button(text(fl!("close"))).on_press(Message::Close(handle.clone())),
+            ]
+            .padding([8, 0]);
+            return self.core.applet.popup_container(content).into();
+        }
+
+        let max_icon_count = self.panel_icon_count();
         let (width, _) = self.core.applet.suggested_size(false);
         let padding = self.core.applet.suggested_padding(false);
         let theme = self.core.system_theme().cosmic();
@@ -358,6 +527,10 @@ impl cosmic::Application for Minimize {
                     },
                 )
                 .snap_within_viewport(false)
+                .apply(cosmic::iced::widget::mouse_area)
+                .on_enter(Message::HoverEnter(handle.clone()))
+                .on_exit(Message::HoverLeave(handle.clone()))
+                .on_right_press(Message::ContextMenu(id, handle.clone()))
                 .into()
             });
 
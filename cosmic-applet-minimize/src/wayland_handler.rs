@@ -459,6 +459,17 @@ pub(crate) fn wayland_handler(
                             manager.activate(&handle, &seat);
                         }
                     }
+                    ToplevelRequest::CaptureImage(handle) => {
+                        state.send_image(handle);
+                    }
+                    ToplevelRequest::Unminimize(handle) => {
+                        let manager = &state.toplevel_manager_state.manager;
+                        manager.unset_minimized(&handle);
+                    }
+                    ToplevelRequest::Close(handle) => {
+                        let manager = &state.toplevel_manager_state.manager;
+                        manager.close(&handle);
+                    }
                 },
             },
             calloop::channel::Event::Closed => {
@@ -86,4 +86,8 @@ pub enum WaylandRequest {
 #[derive(Debug, Clone)]
 pub enum ToplevelRequest {
     Activate(ZcosmicToplevelHandleV1),
+    /// Capture a new thumbnail of the window, sent back as [`WaylandUpdate::Image`].
+    CaptureImage(ZcosmicToplevelHandleV1),
+    Unminimize(ZcosmicToplevelHandleV1),
+    Close(ZcosmicToplevelHandleV1),
 }
@@ -1,12 +1,18 @@
-use std::collections::HashSet;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use cosmic::{
     app,
     applet::{
+        cosmic_panel_config::PanelAnchor,
         menu_button, menu_control_padding, padded_control,
         token::subscription::{activation_token_subscription, TokenRequest, TokenUpdate},
     },
     cctk::sctk::reexports::calloop,
+    cosmic_config::{self, CosmicConfigEntry},
     cosmic_theme::Spacing,
     iced::{
         platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup},
@@ -19,7 +25,7 @@ use cosmic::{
     widget::{
         button, container, divider,
         icon::{self, from_name},
-        scrollable, text, text_input, Column,
+        mouse_area, scrollable, text, text_input, toggler, Column,
     },
     Element, Task,
 };
@@ -32,13 +38,18 @@ use futures::channel::mpsc::UnboundedSender;
 use zbus::Connection;
 
 use crate::{
-    config, fl,
+    config::{self, NetworkAppletConfig},
+    fl,
     network_manager::{
-        active_conns::active_conns_subscription, available_wifi::AccessPoint,
-        current_networks::ActiveConnectionInfo, devices::devices_subscription,
-        hw_address::HwAddress, network_manager_subscription,
-        wireless_enabled::wireless_enabled_subscription, NetworkManagerEvent,
-        NetworkManagerRequest, NetworkManagerState,
+        active_conns::active_conns_subscription,
+        available_wifi::{band, AccessPoint},
+        current_networks::ActiveConnectionInfo,
+        devices::devices_subscription,
+        hw_address::HwAddress,
+        network_manager_subscription,
+        throughput::{format_rate, throughput_subscription, Throughput},
+        wireless_enabled::wireless_enabled_subscription,
+        NetworkManagerEvent, NetworkManagerRequest, NetworkManagerState,
     },
 };
 
@@ -94,6 +105,11 @@ impl From<NewConnectionState> for AccessPoint {
     }
 }
 
+/// Signal strength a network has to move past a signal level boundary
+/// before it's ranked at the new level, so that the list doesn't reorder
+/// while the signal fluctuates.
+const STRENGTH_HYSTERESIS: u8 = 5;
+
 static WIFI: Lazy<id::Toggler> = Lazy::new(id::Toggler::unique);
 static AIRPLANE_MODE: Lazy<id::Toggler> = Lazy::new(id::Toggler::unique);
 
@@ -113,6 +129,51 @@ struct CosmicNetworkApplet {
     token_tx: Option<calloop::channel::Sender<TokenRequest>>,
     failed_known_ssids: HashSet<String>,
     hw_device_to_show: Option<HwAddress>,
+    config: NetworkAppletConfig,
+    config_handler: Option<cosmic_config::Config>,
+    /// Signal level, from 0 to 3, each known network is ranked at.
+    strength_levels: HashMap<String, u8>,
+    /// SSIDs of the known networks in the order they were last ranked.
+    known_order: Vec<String>,
+    throughput: Option<Throughput>,
+    /// Saved network whose reconnect and forget actions are shown.
+    network_actions: Option<(String, HwAddress)>,
+}
+
+/// Signal strength as a percentage, followed by the band when it's known.
+fn signal_caption(strength: u8, frequency: u32) -> String {
+    match band(frequency) {
+        Some(band) => format!("{strength}% · {band}"),
+        None => format!("{strength}%"),
+    }
+}
+
+/// Signal level of a network, keeping the previous one unless the strength
+/// is clearly past the boundary between them.
+fn strength_level(previous: Option<u8>, strength: u8) -> u8 {
+    let level = strength.min(99) / 25;
+    match previous {
+        Some(previous) if previous != level => {
+            let boundary = previous.max(level) * 25;
+            if strength.abs_diff(boundary) < STRENGTH_HYSTERESIS {
+                previous
+            } else {
+                level
+            }
+        }
+        _ => level,
+    }
+}
+
+/// Ranking bonus for a network used recently, in the same unit as signal strength.
+fn recency_bonus(seconds_since: u64) -> u32 {
+    const DAY: u64 = 24 * 60 * 60;
+    match seconds_since {
+        s if s < DAY => 50,
+        s if s < 7 * DAY => 30,
+        s if s < 30 * DAY => 10,
+        _ => 0,
+    }
 }
 
 fn wifi_icon(strength: u8) -> &'static str {
@@ -160,10 +221,100 @@ impl CosmicNetworkApplet {
                 self.failed_known_ssids.insert(new_s.name());
             }
         }
+        self.record_connections(&new_state);
         self.nm_state = new_state;
+        self.rank_known_networks();
         self.update_icon_name();
     }
 
+    /// Remembers when WiFi networks that just finished connecting were used,
+    /// and forgets the networks that are no longer saved.
+    fn record_connections(&mut self, new_state: &NetworkManagerState) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let mut changed = false;
+        // an empty list more likely means the connections couldn't be read
+        if !new_state.known_ssids.is_empty() {
+            let len = self.config.last_connected.len();
+            self.config
+                .last_connected
+                .retain(|ssid, _| new_state.known_ssids.contains(ssid));
+            changed = self.config.last_connected.len() != len;
+        }
+        for conn in &new_state.active_conns {
+            let ActiveConnectionInfo::WiFi {
+                name,
+                state: ActiveConnectionState::Activated,
+                ..
+            } = conn
+            else {
+                continue;
+            };
+            let was_activated = self.nm_state.active_conns.iter().any(|old| {
+                matches!(
+                    old,
+                    ActiveConnectionInfo::WiFi {
+                        name: old_name,
+                        state: ActiveConnectionState::Activated,
+                        ..
+                    } if old_name == name
+                )
+            });
+            if !was_activated {
+                self.config.last_connected.insert(name.clone(), now);
+                changed = true;
+            }
+        }
+
+        if changed {
+            if let Some(handler) = &self.config_handler {
+                if let Err(err) = self.config.write_entry(handler) {
+                    tracing::error!("Failed to save the last connected networks. {err:?}");
+                }
+            }
+        }
+    }
+
+    /// Orders the known networks by signal level and recent use, keeping
+    /// their previous order between networks that rank the same.
+    fn rank_known_networks(&mut self) {
+        if !self.config.rank_known_networks {
+            return;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        for ap in &self.nm_state.known_access_points {
+            let previous = self.strength_levels.get(&ap.ssid).copied();
+            self.strength_levels
+                .insert(ap.ssid.clone(), strength_level(previous, ap.strength));
+        }
+
+        let order = std::mem::take(&mut self.known_order);
+        let config = &self.config;
+        let levels = &self.strength_levels;
+        self.nm_state.known_access_points.sort_by_cached_key(|ap| {
+            let level = u32::from(levels.get(&ap.ssid).copied().unwrap_or_default());
+            let bonus = config
+                .last_connected
+                .get(&ap.ssid)
+                .map_or(0, |&time| recency_bonus(now.saturating_sub(time)));
+            let previous = order
+                .iter()
+                .position(|ssid| ssid == &ap.ssid)
+                .unwrap_or(usize::MAX);
+            (Reverse(level * 25 + bonus), previous)
+        });
+        self.known_order = self
+            .nm_state
+            .known_access_points
+            .iter()
+            .map(|ap| ap.ssid.clone())
+            .collect();
+    }
+
     fn update_icon_name(&mut self) {
         self.icon_name = self
             .nm_state
@@ -214,6 +365,32 @@ impl CosmicNetworkApplet {
             timeline.start();
         }
     }
+    /// Reconnect and forget buttons for a saved network, if they were
+    /// requested for it.
+    fn network_actions_view<'a>(
+        &self,
+        ssid: &str,
+        hw_address: HwAddress,
+    ) -> Option<Element<'a, Message>> {
+        let (actions_ssid, actions_hw_address) = self.network_actions.as_ref()?;
+        if actions_ssid != ssid || *actions_hw_address != hw_address {
+            return None;
+        }
+        Some(
+            padded_control(
+                row![
+                    button::standard(fl!("reconnect"))
+                        .on_press(Message::Reconnect(ssid.to_string(), hw_address)),
+                    button::destructive(fl!("forget"))
+                        .on_press(Message::Forget(ssid.to_string(), hw_address)),
+                ]
+                .spacing(8),
+            )
+            .align_x(Alignment::End)
+            .into(),
+        )
+    }
+
     fn view_window_return<'a>(&self, mut content: Column<'a, Message>) -> Element<'a, Message> {
         let Spacing {
             space_xxs, space_s, ..
@@ -251,6 +428,12 @@ pub(crate) enum Message {
     OpenSettings,
     ResetFailedKnownSsid(String, HwAddress),
     OpenHwDevice(Option<HwAddress>),
+    ConfigChanged(NetworkAppletConfig),
+    ToggleVpn(String, bool),
+    Throughput(Throughput),
+    ToggleNetworkActions(String, HwAddress),
+    Reconnect(String, HwAddress),
+    Forget(String, HwAddress),
     // Errored(String),
 }
 
@@ -261,11 +444,18 @@ impl cosmic::Application for CosmicNetworkApplet {
     const APP_ID: &'static str = config::APP_ID;
 
     fn init(core: cosmic::app::Core, _flags: ()) -> (Self, app::Task<Message>) {
+        let config_handler =
+            cosmic_config::Config::new(config::APP_ID, NetworkAppletConfig::VERSION).ok();
         (
             Self {
                 core,
                 icon_name: "network-offline-symbolic".to_string(),
                 token_tx: None,
+                config: config_handler
+                    .as_ref()
+                    .and_then(|c| NetworkAppletConfig::get_entry(c).ok())
+                    .unwrap_or_default(),
+                config_handler,
                 ..Default::default()
             },
             Task::none(),
@@ -283,6 +473,13 @@ impl cosmic::Application for CosmicNetworkApplet {
     fn update(&mut self, message: Message) -> app::Task<Message> {
         match message {
             Message::Frame(now) => self.timeline.now(now),
+            Message::ConfigChanged(config) => {
+                let rank = config.rank_known_networks && !self.config.rank_known_networks;
+                self.config = config;
+                if rank {
+                    self.rank_known_networks();
+                }
+            }
             Message::TogglePopup => {
                 if let Some(p) = self.popup.take() {
                     self.show_visible_networks = false;
@@ -373,6 +570,10 @@ impl cosmic::Application for CosmicNetworkApplet {
                         {
                             self.failed_known_ssids.insert(ssid.clone());
                         }
+                    } else if let NetworkManagerRequest::Reconnect(ssid, _) = &req {
+                        if !success {
+                            self.failed_known_ssids.insert(ssid.clone());
+                        }
                     } else if let NetworkManagerRequest::Password(ssid, _, hw_address) = &req {
                         if let Some(NewConnectionState::Waiting(access_point)) =
                             self.new_connection.clone()
@@ -434,6 +635,57 @@ impl cosmic::Application for CosmicNetworkApplet {
                     password: String::new(),
                 });
             }
+            Message::ToggleVpn(name, enabled) => {
+                if let Some(tx) = self.nm_sender.as_ref() {
+                    let _ = tx.unbounded_send(NetworkManagerRequest::SetVpn(name, enabled));
+                }
+            }
+            Message::Throughput(throughput) => self.throughput = Some(throughput),
+            Message::ToggleNetworkActions(ssid, hw_address) => {
+                let network = Some((ssid, hw_address));
+                self.network_actions = if self.network_actions == network {
+                    None
+                } else {
+                    network
+                };
+            }
+            Message::Reconnect(ssid, hw_address) => {
+                self.network_actions = None;
+                let Some(tx) = self.nm_sender.as_ref() else {
+                    return Task::none();
+                };
+                self.failed_known_ssids.remove(&ssid);
+                if let Some(ActiveConnectionInfo::WiFi { state, .. }) = self
+                    .nm_state
+                    .active_conns
+                    .iter_mut()
+                    .find(|c| c.name() == ssid && c.hw_address() == hw_address)
+                {
+                    *state = ActiveConnectionState::Activating;
+                } else if let Some(known) = self
+                    .nm_state
+                    .known_access_points
+                    .iter_mut()
+                    .find(|ap| ap.ssid == ssid && ap.hw_address == hw_address)
+                {
+                    known.working = true;
+                }
+                let _ = tx.unbounded_send(NetworkManagerRequest::Reconnect(ssid, hw_address));
+            }
+            Message::Forget(ssid, hw_address) => {
+                self.network_actions = None;
+                let Some(tx) = self.nm_sender.as_ref() else {
+                    return Task::none();
+                };
+                self.failed_known_ssids.remove(&ssid);
+                self.nm_state
+                    .known_access_points
+                    .retain(|ap| ap.ssid != ssid || ap.hw_address != hw_address);
+                self.nm_state
+                    .active_conns
+                    .retain(|c| c.name() != ssid || c.hw_address() != hw_address);
+                let _ = tx.unbounded_send(NetworkManagerRequest::Forget(ssid, hw_address));
+            }
             Message::ToggleVisibleNetworks => {
                 self.new_connection = None;
                 self.show_visible_networks = !self.show_visible_networks;
@@ -575,11 +827,39 @@ impl cosmic::Application for CosmicNetworkApplet {
     }
 
     fn view(&self) -> Element<Message> {
-        self.core
-            .applet
-            .icon_button(&self.icon_name)
-            .on_press_down(Message::TogglePopup)
-            .into()
+        let horizontal = matches!(
+            self.core.applet.anchor,
+            PanelAnchor::Top | PanelAnchor::Bottom
+        );
+        let throughput = self
+            .throughput
+            .filter(|_| self.config.show_throughput_on_panel && horizontal);
+        let Some(throughput) = throughput else {
+            return self
+                .core
+                .applet
+                .icon_button(&self.icon_name)
+                .on_press_down(Message::TogglePopup)
+                .into();
+        };
+
+        button::custom(
+            row![
+                icon::from_name(self.icon_name.as_str())
+                    .size(self.core.applet.suggested_size(true).0)
+                    .symbolic(true),
+                column![
+                    text::caption(format!("↓ {}", format_rate(throughput.rx))),
+                    text::caption(format!("↑ {}", format_rate(throughput.tx))),
+                ],
+            ]
+            .align_y(Alignment::Center)
+            .spacing(4),
+        )
+        .padding([0, self.core.applet.suggested_padding(true)])
+        .on_press_down(Message::TogglePopup)
+        .class(cosmic::theme::Button::AppletIcon)
+        .into()
     }
 
     fn view_window(&self, _id: window::Id) -> Element<Message> {
@@ -591,35 +871,8 @@ impl cosmic::Application for CosmicNetworkApplet {
         let mut known_wifi = Vec::new();
         for conn in &self.nm_state.active_conns {
             match conn {
-                ActiveConnectionInfo::Vpn { name, ip_addresses } => {
-                    if self.hw_device_to_show.is_some() {
-                        continue;
-                    }
-                    let mut ipv4 = Vec::with_capacity(ip_addresses.len() + 1);
-                    ipv4.push(text::body(name).into());
-                    for addr in ip_addresses {
-                        ipv4.push(text::caption(format!("{}: {}", fl!("ipv4"), addr)).into());
-                    }
-                    vpn_ethernet_col = vpn_ethernet_col.push(column![
-                        row![
-                            icon::icon(
-                                icon::from_name(self.icon_name.clone())
-                                    .symbolic(true)
-                                    .into()
-                            )
-                            .size(40),
-                            Column::with_children(ipv4),
-                            text::body(fl!("connected"))
-                                .width(Length::Fill)
-                                .align_x(Alignment::End),
-                        ]
-                        .align_y(Alignment::Center)
-                        .spacing(8)
-                        .padding(menu_control_padding()),
-                        padded_control(divider::horizontal::default())
-                            .padding([space_xxs, space_s]),
-                    ]);
-                }
+                // shown with their toggle in the VPN list below
+                ActiveConnectionInfo::Vpn { .. } => {}
                 ActiveConnectionInfo::Wired {
                     name,
                     hw_address,
@@ -666,6 +919,7 @@ impl cosmic::Application for CosmicNetworkApplet {
                     ip_addresses,
                     state,
                     strength,
+                    frequency,
                     hw_address,
                 } => {
                     if self.hw_device_to_show.is_some()
@@ -682,9 +936,13 @@ impl cosmic::Application for CosmicNetworkApplet {
                             .size(24)
                             .symbolic(true)
                             .into(),
-                        column![text::body(name), Column::with_children(ipv4)]
-                            .width(Length::Fill)
-                            .into(),
+                        column![
+                            text::body(name),
+                            text::caption(signal_caption(*strength, *frequency)),
+                            Column::with_children(ipv4)
+                        ]
+                        .width(Length::Fill)
+                        .into(),
                     ];
                     match state {
                         ActiveConnectionState::Activating | ActiveConnectionState::Deactivating => {
@@ -715,18 +973,68 @@ impl cosmic::Application for CosmicNetworkApplet {
                     }
 
                     known_wifi.push(Element::from(
-                        column![menu_button(
-                            Row::with_children(btn_content)
-                                .align_y(Alignment::Center)
-                                .spacing(8)
+                        column![mouse_area(
+                            menu_button(
+                                Row::with_children(btn_content)
+                                    .align_y(Alignment::Center)
+                                    .spacing(8)
+                            )
+                            .on_press(Message::Disconnect(name.clone(), *hw_address))
                         )
-                        .on_press(Message::Disconnect(name.clone(), *hw_address))]
+                        .on_right_press(Message::ToggleNetworkActions(name.clone(), *hw_address))]
+                        .push_maybe(self.network_actions_view(name, *hw_address))
                         .align_x(Alignment::Center),
                     ));
                 }
             };
         }
 
+        let mut throughput_col = column![];
+        if let Some(throughput) = self
+            .throughput
+            .filter(|_| !self.nm_state.active_conns.is_empty())
+        {
+            throughput_col = throughput_col
+                .push(padded_control(column![
+                    text::body(format!(
+                        "{}: {}",
+                        fl!("download"),
+                        format_rate(throughput.rx)
+                    )),
+                    text::body(format!("{}: {}", fl!("upload"), format_rate(throughput.tx))),
+                ]))
+                .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]));
+        }
+
+        let mut vpn_col = column![];
+        for name in &self.nm_state.known_vpns {
+            let active = self.nm_state.active_conns.iter().any(
+                |c| matches!(c, ActiveConnectionInfo::Vpn { name: active, .. } if active == name),
+            );
+            let icon_name = if active {
+                "network-vpn-symbolic"
+            } else {
+                "network-vpn-disabled-symbolic"
+            };
+            let name = name.clone();
+            vpn_col = vpn_col.push(padded_control(
+                row![
+                    icon::from_name(icon_name).size(24).symbolic(true),
+                    toggler(active)
+                        .label(name.clone())
+                        .text_size(14)
+                        .width(Length::Fill)
+                        .on_toggle(move |enable| Message::ToggleVpn(name.clone(), enable)),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(8),
+            ));
+        }
+        if !self.nm_state.known_vpns.is_empty() {
+            vpn_col = vpn_col
+                .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]));
+        }
+
         let mut content = if let Some(hw_device_to_show) = self.hw_device_to_show {
             column![
                 vpn_ethernet_col,
@@ -753,6 +1061,7 @@ impl cosmic::Application for CosmicNetworkApplet {
                 Element::from(
                     column![
                         vpn_ethernet_col,
+                        throughput_col,
                         padded_control(
                             anim!(
                                 //toggler
@@ -770,6 +1079,7 @@ impl cosmic::Application for CosmicNetworkApplet {
                     ]
                     .align_x(Alignment::Center)
                 ),
+                vpn_col,
                 padded_control(
                     anim!(
                         //toggler
@@ -867,7 +1177,11 @@ impl cosmic::Application for CosmicNetworkApplet {
                 }
             }
             let mut btn_content = Vec::with_capacity(2);
-            let ssid = text::body(&known.ssid).width(Length::Fill);
+            let ssid = column![
+                text::body(&known.ssid),
+                text::caption(signal_caption(known.strength, known.frequency)),
+            ]
+            .width(Length::Fill);
             if known.working {
                 btn_content.push(
                     icon::from_name("network-wireless-acquiring-symbolic")
@@ -931,7 +1245,15 @@ impl cosmic::Application for CosmicNetworkApplet {
                 }
                 _ => btn,
             };
-            known_wifi.push(Element::from(row![btn].align_y(Alignment::Center)));
+            known_wifi.push(Element::from(
+                column![
+                    mouse_area(btn).on_right_press(Message::ToggleNetworkActions(
+                        known.ssid.clone(),
+                        known.hw_address,
+                    ))
+                ]
+                .push_maybe(self.network_actions_view(&known.ssid, known.hw_address)),
+            ));
         }
         let has_known_wifi = !known_wifi.is_empty();
         content = content.push(Column::with_children(known_wifi));
@@ -1072,7 +1394,10 @@ impl cosmic::Application for CosmicNetworkApplet {
                         icon::from_name(wifi_icon(ap.strength))
                             .size(16)
                             .symbolic(true),
-                        text::body(&ap.ssid).align_y(Alignment::Center)
+                        column![
+                            text::body(&ap.ssid),
+                            text::caption(signal_caption(ap.strength, ap.frequency)),
+                        ]
                     ]
                     .align_y(Alignment::Center)
                     .spacing(12),
@@ -1094,13 +1419,26 @@ impl cosmic::Application for CosmicNetworkApplet {
             .as_subscription()
             .map(|(_, now)| Message::Frame(now));
         let token_sub = activation_token_subscription(0).map(Message::Token);
+        let config_sub = self.core.watch_config(config::APP_ID).map(|u| {
+            for err in u.errors {
+                tracing::error!(?err, "Error watching config");
+            }
+            Message::ConfigChanged(u.config)
+        });
 
         if let Some(conn) = self.conn.as_ref() {
             let has_popup = self.popup.is_some();
+            let throughput_sub = if has_popup || self.config.show_throughput_on_panel {
+                throughput_subscription(0, conn.clone()).map(Message::Throughput)
+            } else {
+                Subscription::none()
+            };
             Subscription::batch(vec![
+                throughput_sub,
                 timeline,
                 network_sub,
                 token_sub,
+                config_sub,
                 active_conns_subscription(self.toggle_wifi_ctr, conn.clone())
                     .map(Message::NetworkManagerEvent),
                 devices_subscription(self.toggle_wifi_ctr, has_popup, conn.clone())
@@ -1109,7 +1447,7 @@ impl cosmic::Application for CosmicNetworkApplet {
                     .map(Message::NetworkManagerEvent),
             ])
         } else {
-            Subscription::batch(vec![timeline, network_sub, token_sub])
+            Subscription::batch(vec![timeline, network_sub, token_sub, config_sub])
         }
     }
 
@@ -1 +1,29 @@
+use std::collections::BTreeMap;
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
 pub const APP_ID: &str = "com.system76.CosmicAppletNetwork";
+
+#[derive(Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct NetworkAppletConfig {
+    /// List known networks in range by how recently they were used as well as
+    /// their signal strength, instead of in the order NetworkManager reports them.
+    pub rank_known_networks: bool,
+    /// When each known network was last connected, in seconds since the Unix
+    /// epoch, by SSID. Updated by the applet.
+    pub last_connected: BTreeMap<String, u64>,
+    /// Show the current download and upload rates next to the panel icon.
+    pub show_throughput_on_panel: bool,
+}
+
+impl Default for NetworkAppletConfig {
+    fn default() -> Self {
+        Self {
+            rank_known_networks: true,
+            last_connected: BTreeMap::new(),
+            show_throughput_on_panel: false,
+        }
+    }
+}
@@ -34,6 +34,7 @@ pub async fn handle_wireless_device(
     for ap in access_points {
         let ssid = String::from_utf8_lossy(&ap.ssid().await?.clone()).into_owned();
         let strength = ap.strength().await?;
+        let frequency = ap.frequency().await.unwrap_or_default();
         if let Some(access_point) = aps.get(&ssid) {
             if access_point.strength > strength {
                 continue;
@@ -44,6 +45,7 @@ pub async fn handle_wireless_device(
             AccessPoint {
                 ssid,
                 strength,
+                frequency,
                 state,
                 working: false,
                 path: ap.inner().path().to_owned(),
@@ -65,8 +67,20 @@ pub async fn handle_wireless_device(
 pub struct AccessPoint {
     pub ssid: String,
     pub strength: u8,
+    /// Frequency in MHz.
+    pub frequency: u32,
     pub state: DeviceState,
     pub working: bool,
     pub path: ObjectPath<'static>,
     pub hw_address: HwAddress,
 }
+
+/// Name of the Wi-Fi band a frequency in MHz belongs to.
+pub fn band(frequency: u32) -> Option<&'static str> {
+    match frequency {
+        2400..=2500 => Some("2.4 GHz"),
+        4900..=5900 => Some("5 GHz"),
+        5925..=7125 => Some("6 GHz"),
+        _ => None,
+    }
+}
@@ -59,6 +59,7 @@ pub async fn active_connections(
                             .unwrap_or_default(),
                             state,
                             strength: access_point.strength().await.unwrap_or_default(),
+                            frequency: access_point.frequency().await.unwrap_or_default(),
                         });
                     }
                 }
@@ -99,6 +100,7 @@ pub enum ActiveConnectionInfo {
         hw_address: HwAddress,
         state: ActiveConnectionState,
         strength: u8,
+        frequency: u32,
     },
     Vpn {
         name: String,
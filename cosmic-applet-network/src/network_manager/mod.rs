@@ -3,6 +3,8 @@ pub mod available_wifi;
 pub mod current_networks;
 pub mod devices;
 pub mod hw_address;
+pub mod throughput;
+pub mod vpn;
 pub mod wireless_enabled;
 
 use std::{collections::HashMap, fmt::Debug, time::Duration};
@@ -35,6 +37,7 @@ use zbus::{
 use self::{
     available_wifi::{handle_wireless_device, AccessPoint},
     current_networks::{active_connections, ActiveConnectionInfo},
+    vpn::{activate_vpn, deactivate_vpn, vpn_name},
 };
 
 #[derive(Debug)]
@@ -234,6 +237,27 @@ async fn start_listening(
                         })
                         .await;
                 }
+                Some(NetworkManagerRequest::Reconnect(ssid, hw_address)) => {
+                    // connecting to the saved profile deactivates the current
+                    // connection to the network first
+                    let state = NetworkManagerState::new(&conn).await.unwrap_or_default();
+                    let success = if let Err(err) =
+                        state.connect_wifi(&conn, &ssid, None, hw_address).await
+                    {
+                        tracing::error!("Failed to reconnect to access point: {:?}", err);
+                        false
+                    } else {
+                        true
+                    };
+
+                    _ = output
+                        .send(NetworkManagerEvent::RequestResponse {
+                            req: NetworkManagerRequest::Reconnect(ssid, hw_address),
+                            success,
+                            state: NetworkManagerState::new(&conn).await.unwrap_or_default(),
+                        })
+                        .await;
+                }
                 Some(NetworkManagerRequest::Reload) => {
                     let state = NetworkManagerState::new(&conn).await.unwrap_or_default();
                     _ = output
@@ -272,6 +296,24 @@ async fn start_listening(
                         })
                         .await;
                 }
+                Some(NetworkManagerRequest::SetVpn(name, active)) => {
+                    let res = if active {
+                        activate_vpn(&conn, &name).await
+                    } else {
+                        deactivate_vpn(&conn, &name).await
+                    };
+                    if let Err(err) = &res {
+                        tracing::error!("Failed to toggle VPN {name}: {:?}", err);
+                    }
+                    let state = NetworkManagerState::new(&conn).await.unwrap_or_default();
+                    _ = output
+                        .send(NetworkManagerEvent::RequestResponse {
+                            req: NetworkManagerRequest::SetVpn(name, active),
+                            success: res.is_ok(),
+                            state,
+                        })
+                        .await;
+                }
                 _ => {
                     return State::Finished;
                 }
@@ -289,8 +331,10 @@ pub enum NetworkManagerRequest {
     SetWiFi(bool),
     SelectAccessPoint(String, HwAddress),
     Disconnect(String, HwAddress),
+    Reconnect(String, HwAddress),
     Password(String, String, HwAddress),
     Forget(String, HwAddress),
+    SetVpn(String, bool),
     Reload,
 }
 
@@ -316,6 +360,9 @@ pub struct NetworkManagerState {
     pub wireless_access_points: Vec<AccessPoint>,
     pub active_conns: Vec<ActiveConnectionInfo>,
     pub known_access_points: Vec<AccessPoint>,
+    /// SSIDs of all saved WiFi connections, in range or not.
+    pub known_ssids: Vec<String>,
+    pub known_vpns: Vec<String>,
     pub wifi_enabled: bool,
     pub airplane_mode: bool,
     pub connectivity: NmConnectivityState,
@@ -327,6 +374,8 @@ impl Default for NetworkManagerState {
             wireless_access_points: Vec::new(),
             active_conns: Vec::new(),
             known_access_points: Vec::new(),
+            known_ssids: Vec::new(),
+            known_vpns: Vec::new(),
             wifi_enabled: false,
             airplane_mode: false,
             connectivity: NmConnectivityState::Unknown,
@@ -388,8 +437,12 @@ impl NetworkManagerState {
             wireless_access_points.append(&mut access_points);
         }
         let mut known_ssid = Vec::with_capacity(known_conns.len());
+        let mut known_vpns = Vec::new();
         for c in known_conns {
             let s = c.get_settings().await.unwrap();
+            if let Some(name) = vpn_name(&s) {
+                known_vpns.push(name);
+            }
             let s = Settings::new(s);
             if let Some(cur_ssid) = s
                 .wifi
@@ -414,6 +467,9 @@ impl NetworkManagerState {
         self_.wireless_access_points = wireless_access_points;
         self_.active_conns = active_conns;
         self_.known_access_points = known_access_points;
+        self_.known_ssids = known_ssid;
+        known_vpns.sort();
+        self_.known_vpns = known_vpns;
         self_.connectivity = network_manager.connectivity().await?;
 
         Ok(self_)
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::Debug,
+    hash::Hash,
+    time::Duration,
+};
+
+use cosmic::iced::{self, stream, Subscription};
+use cosmic_dbus_networkmanager::nm::NetworkManager;
+use futures::SinkExt;
+use tokio::time::Instant;
+use zbus::{zvariant::OwnedObjectPath, CacheProperties, Connection};
+
+/// How often the device statistics are sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[zbus::proxy(
+    interface = "org.freedesktop.NetworkManager.Device.Statistics",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Statistics {
+    #[zbus(property)]
+    fn refresh_rate_ms(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_refresh_rate_ms(&self, value: u32) -> zbus::Result<()>;
+    #[zbus(property)]
+    fn rx_bytes(&self) -> zbus::Result<u64>;
+    #[zbus(property)]
+    fn tx_bytes(&self) -> zbus::Result<u64>;
+}
+
+/// Bytes per second received and sent over the active connections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Throughput {
+    pub rx: u64,
+    pub tx: u64,
+}
+
+pub fn throughput_subscription<I: 'static + Hash + Copy + Send + Sync + Debug>(
+    id: I,
+    conn: Connection,
+) -> iced::Subscription<Throughput> {
+    Subscription::run_with_id(
+        id,
+        stream::channel(50, move |mut output| async move {
+            let mut statistics = DeviceStatistics::new(conn);
+            let mut previous: Option<(Instant, HashSet<OwnedObjectPath>, u64, u64)> = None;
+            let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Ok((devices, rx, tx)) = statistics.sample().await else {
+                    previous = None;
+                    continue;
+                };
+                let now = Instant::now();
+                if let Some((then, prev_devices, prev_rx, prev_tx)) = previous.take() {
+                    // Counters of different devices can't be compared.
+                    if prev_devices == devices {
+                        let secs = now.duration_since(then).as_secs_f64().max(f64::EPSILON);
+                        let throughput = Throughput {
+                            rx: (rx.saturating_sub(prev_rx) as f64 / secs) as u64,
+                            tx: (tx.saturating_sub(prev_tx) as f64 / secs) as u64,
+                        };
+                        _ = output.send(throughput).await;
+                    }
+                }
+                previous = Some((now, devices, rx, tx));
+            }
+        }),
+    )
+}
+
+/// Statistics proxies of the devices being sampled.
+///
+/// NetworkManager only updates the counters while a refresh rate is set, so
+/// the rate is set on devices that had none and cleared again on drop.
+struct DeviceStatistics {
+    conn: Connection,
+    proxies: HashMap<OwnedObjectPath, StatisticsProxy<'static>>,
+    /// Devices whose refresh rate was set here.
+    enabled: HashSet<OwnedObjectPath>,
+}
+
+impl DeviceStatistics {
+    fn new(conn: Connection) -> Self {
+        Self {
+            conn,
+            proxies: HashMap::new(),
+            enabled: HashSet::new(),
+        }
+    }
+
+    /// Total bytes received and sent by the devices of the active connections,
+    /// leaving out VPNs whose traffic is already counted on the underlying device.
+    async fn sample(&mut self) -> zbus::Result<(HashSet<OwnedObjectPath>, u64, u64)> {
+        let network_manager = NetworkManager::new(&self.conn).await?;
+        let mut paths = HashSet::new();
+        for c in network_manager
+            .active_connections()
+            .await
+            .unwrap_or_default()
+        {
+            if c.vpn().await.unwrap_or_default() {
+                continue;
+            }
+            for device in c.devices().await.unwrap_or_default() {
+                paths.insert(OwnedObjectPath::from(device.inner().path().to_owned()));
+            }
+        }
+        self.proxies.retain(|path, _| paths.contains(path));
+
+        let (mut rx, mut tx) = (0, 0);
+        for path in &paths {
+            let statistics = match self.proxies.entry(path.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let statistics = StatisticsProxy::builder(&self.conn)
+                        .path(path.clone())?
+                        .cache_properties(CacheProperties::No)
+                        .build()
+                        .await?;
+                    if statistics.refresh_rate_ms().await.unwrap_or_default() == 0
+                        && statistics
+                            .set_refresh_rate_ms(SAMPLE_INTERVAL.as_millis() as u32)
+                            .await
+                            .is_ok()
+                    {
+                        self.enabled.insert(path.clone());
+                    }
+                    entry.insert(statistics)
+                }
+            };
+            rx += statistics.rx_bytes().await.unwrap_or_default();
+            tx += statistics.tx_bytes().await.unwrap_or_default();
+        }
+
+        Ok((paths, rx, tx))
+    }
+}
+
+impl Drop for DeviceStatistics {
+    fn drop(&mut self) {
+        if self.enabled.is_empty() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let conn = self.conn.clone();
+        let paths = std::mem::take(&mut self.enabled);
+        runtime.spawn(async move {
+            for path in paths {
+                let Ok(builder) = StatisticsProxy::builder(&conn).path(path) else {
+                    continue;
+                };
+                if let Ok(statistics) = builder.build().await {
+                    _ = statistics.set_refresh_rate_ms(0).await;
+                }
+            }
+        });
+    }
+}
+
+/// Formats a rate in bytes per second with a human readable unit.
+pub fn format_rate(bytes_per_sec: u64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut rate = bytes_per_sec as f64;
+    let mut unit = 0;
+    while rate >= 1000. && unit < UNITS.len() - 1 {
+        rate /= 1000.;
+        unit += 1;
+    }
+    if unit == 0 || rate >= 100. {
+        format!("{rate:.0} {}", UNITS[unit])
+    } else {
+        format!("{rate:.1} {}", UNITS[unit])
+    }
+}
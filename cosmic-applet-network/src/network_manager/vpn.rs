@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+
+use cosmic_dbus_networkmanager::{nm::NetworkManager, settings::NetworkManagerSettings};
+use zbus::{
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue},
+    Connection,
+};
+
+#[zbus::proxy(
+    interface = "org.freedesktop.NetworkManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager"
+)]
+trait Activation {
+    fn activate_connection(
+        &self,
+        connection: &ObjectPath<'_>,
+        device: &ObjectPath<'_>,
+        specific_object: &ObjectPath<'_>,
+    ) -> zbus::Result<OwnedObjectPath>;
+}
+
+/// Name of a saved connection if its settings describe a VPN.
+pub fn vpn_name(settings: &HashMap<String, HashMap<String, OwnedValue>>) -> Option<String> {
+    let connection = settings.get("connection")?;
+    let kind = <&str>::try_from(connection.get("type")?).ok()?;
+    if kind != "vpn" && kind != "wireguard" {
+        return None;
+    }
+    let id = <&str>::try_from(connection.get("id")?).ok()?;
+    Some(id.to_string())
+}
+
+pub async fn activate_vpn(conn: &Connection, name: &str) -> anyhow::Result<()> {
+    let settings = NetworkManagerSettings::new(conn).await?;
+    for c in settings.list_connections().await.unwrap_or_default() {
+        let s = c.get_settings().await.ok().unwrap_or_default();
+        if vpn_name(&s).as_deref() != Some(name) {
+            continue;
+        }
+        // VPNs aren't bound to a device, NetworkManager picks the one
+        // providing the default route.
+        let none = ObjectPath::from_static_str_unchecked("/");
+        ActivationProxy::new(conn)
+            .await?
+            .activate_connection(c.inner().path(), &none, &none)
+            .await?;
+        return Ok(());
+    }
+
+    Err(anyhow::anyhow!("VPN connection not found"))
+}
+
+pub async fn deactivate_vpn(conn: &Connection, name: &str) -> anyhow::Result<()> {
+    let nm = NetworkManager::new(conn).await?;
+    for c in nm.active_connections().await.unwrap_or_default() {
+        if c.id().await.unwrap_or_default() == name {
+            nm.deactivate_connection(&c).await?;
+            return Ok(());
+        }
+    }
+
+    Err(anyhow::anyhow!("VPN connection is not active"))
+}
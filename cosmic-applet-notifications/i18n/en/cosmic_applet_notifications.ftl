@@ -12,4 +12,16 @@ clear-group = Clear group
 clear-all = Clear all notifications
 do-not-disturb = Do Not Disturb
 notification-settings = Notification settings...
-no-notifications = No notifications
\ No newline at end of file
+no-notifications = No notifications
+clear-all-confirm = Clear { $count } notifications?
+clear = Clear
+cancel = Cancel
+notification-count = { $count ->
+    [0] No notifications
+    [one] 1 notification
+   *[other] { $count } notifications
+}
+do-not-disturb-on = Do Not Disturb on
+mute-app = Mute { $app }
+history = History ({ $count })
+clear-history = Clear history
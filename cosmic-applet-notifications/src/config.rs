@@ -0,0 +1,62 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
+/// Count shown as a badge over the panel icon.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PanelBadge {
+    #[default]
+    None,
+    /// Number of applications with notifications.
+    Groups,
+    /// Number of notifications.
+    Total,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct NotificationsAppletConfig {
+    /// Listen for toggle requests sent by `cosmic-applet-notifications --toggle`.
+    ///
+    /// Bind that command to a custom shortcut in the keyboard settings to open
+    /// and close the popup from the keyboard. Disable this if the shortcut
+    /// conflicts with another applet instance on a multi-panel setup.
+    pub toggle_shortcut: bool,
+    pub panel_badge: PanelBadge,
+    /// Horizontal and vertical offset in pixels applied to the popup position.
+    ///
+    /// Only needed on panel layouts where the popup ends up partly under
+    /// another applet or off screen.
+    pub popup_offset: (i32, i32),
+    /// Ask for confirmation before clearing more than this many notifications
+    /// at once. 0 never asks.
+    pub clear_all_confirm_threshold: usize,
+    /// Keep at most this many notifications per application, dismissing the
+    /// oldest ones beyond it. Critical notifications are always kept. 0 keeps
+    /// everything.
+    pub max_per_app: usize,
+    /// Applications whose notifications are left out of the applet.
+    pub muted_apps: Vec<String>,
+    /// Play the sound theme's message sound when a notification arrives,
+    /// unless Do Not Disturb is on.
+    pub play_sound: bool,
+    /// Don't play a sound for low urgency notifications.
+    pub silent_low_urgency: bool,
+}
+
+impl Default for NotificationsAppletConfig {
+    fn default() -> Self {
+        Self {
+            toggle_shortcut: true,
+            panel_badge: PanelBadge::None,
+            popup_offset: (0, 0),
+            clear_all_confirm_threshold: 10,
+            max_per_app: 0,
+            muted_apps: Vec::new(),
+            play_sound: false,
+            silent_low_urgency: true,
+        }
+    }
+}
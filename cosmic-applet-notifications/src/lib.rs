@@ -1,10 +1,12 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
+mod config;
 mod localize;
 mod subscriptions;
 use cosmic::{
     applet::{
+        cosmic_panel_config::PanelAnchor,
         menu_control_padding, padded_control,
         token::subscription::{activation_token_subscription, TokenRequest, TokenUpdate},
     },
@@ -13,36 +15,76 @@ use cosmic::{
     cosmic_theme::Spacing,
     iced::{
         platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup},
-        widget::{column, row},
-        window, Alignment, Length, Limits, Subscription,
+        widget::{column, row, stack},
+        window, Alignment, Background, Length, Limits, Subscription,
     },
+    iced_core::Border,
     iced_widget::{scrollable, Column},
     theme,
-    widget::{button, container, divider, icon, text},
+    widget::{button, container, divider, icon, text, tooltip},
     Element, Task,
 };
 
 use cosmic::iced_futures::futures::executor::block_on;
 
+use config::{NotificationsAppletConfig, PanelBadge};
 use cosmic_notifications_config::NotificationsConfig;
-use cosmic_notifications_util::{ActionId, Image, Notification};
+use cosmic_notifications_util::{ActionId, Hint, Image, Notification};
 use cosmic_time::{anim, chain, id, once_cell::sync::Lazy, Instant, Timeline};
-use std::{borrow::Cow, collections::HashMap, path::PathBuf};
+use freedesktop_desktop_entry::{get_languages_from_env, DesktopEntry};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+    time::Duration,
+};
 use subscriptions::notifications::{self, NotificationsAppletProxy};
 use tokio::sync::mpsc::Sender;
 use tracing::info;
 
 pub fn run() -> cosmic::iced::Result {
+    if std::env::args().any(|arg| arg == "--toggle") {
+        if let Err(err) = request_toggle() {
+            tracing::error!("Failed to toggle the notifications popup: {:?}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     localize::localize();
     cosmic::applet::run::<Notifications>(())
 }
 
+/// Asks the running applet to toggle its popup.
+fn request_toggle() -> anyhow::Result<()> {
+    // Callers of `run` normally set up tracing already, this is a no-op then.
+    let _ = tracing_subscriber::fmt::try_init();
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    rt.block_on(subscriptions::toggle::request_toggle())?;
+    Ok(())
+}
+
+/// Notifications from an application within this long of its last sound are silent.
+const SOUND_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// Plays the message sound from the sound theme, or the freedesktop one
+/// when libcanberra isn't installed.
+const SOUND_COMMAND: &str = "canberra-gtk-play -i message-new-instant || \
+    paplay /usr/share/sounds/freedesktop/stereo/message-new-instant.oga";
+
+/// Number of dismissed notifications kept in the history.
+const HISTORY_LEN: usize = 50;
+
 static DO_NOT_DISTURB: Lazy<id::Toggler> = Lazy::new(id::Toggler::unique);
 
 struct Notifications {
     core: cosmic::app::Core,
     config: NotificationsConfig,
+    applet_config: NotificationsAppletConfig,
     config_helper: Option<Config>,
+    applet_config_helper: Option<Config>,
     icon_name: String,
     popup: Option<window::Id>,
     // notifications: Vec<Notification>,
@@ -52,9 +94,61 @@ struct Notifications {
     token_tx: Option<calloop::channel::Sender<TokenRequest>>,
     proxy: NotificationsAppletProxy<'static>,
     notifications_tx: Option<Sender<notifications::Input>>,
+    confirm_clear_all: bool,
+    /// Application whose group was right-clicked, offering to mute it.
+    mute_prompt: Option<String>,
+    /// Dismissed notifications, most recent first.
+    history: VecDeque<Notification>,
+    show_history: bool,
+    /// When a sound was last played for each application.
+    last_sound: HashMap<String, Instant>,
+    /// Icons of the shown notifications by id, resolved when they arrive.
+    icons: HashMap<u32, icon::Handle>,
 }
 
 impl Notifications {
+    /// Keeps dismissed notifications in the history, dropping the oldest ones.
+    fn archive(&mut self, notifications: impl IntoIterator<Item = Notification>) {
+        for n in notifications {
+            self.history.push_front(n);
+        }
+        self.history.truncate(HISTORY_LEN);
+    }
+
+    /// Plays the notification sound for a new notification if enabled.
+    fn play_sound(&mut self, n: &Notification) {
+        if !self.applet_config.play_sound || self.config.do_not_disturb {
+            return;
+        }
+        if self.applet_config.silent_low_urgency
+            && n.hints.iter().any(|hint| matches!(hint, Hint::Urgency(0)))
+        {
+            return;
+        }
+
+        let now = Instant::now();
+        if let Some(last) = self.last_sound.get(&n.app_name) {
+            if now.duration_since(*last) < SOUND_DEBOUNCE {
+                return;
+            }
+        }
+        self.last_sound.insert(n.app_name.clone(), now);
+
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg(SOUND_COMMAND);
+        tokio::spawn(cosmic::process::spawn(cmd));
+    }
+
+    /// Removes a notification from its card, returning it if it was shown.
+    fn remove_notification(&mut self, id: u32) -> Option<Notification> {
+        let removed = self.cards.iter_mut().find_map(|c| {
+            let pos = c.1.iter().position(|n| n.id == id)?;
+            Some(c.1.remove(pos))
+        });
+        self.cards.retain(|c| !c.1.is_empty());
+        removed
+    }
+
     fn update_cards(&mut self, id: id::Cards) {
         if let Some((id, _, card_value, ..)) = self.cards.iter_mut().find(|c| c.0 == id) {
             let chain = if *card_value {
@@ -77,6 +171,81 @@ impl Notifications {
         }
         .to_string();
     }
+
+    fn notification_count(&self) -> usize {
+        self.cards.iter().map(|c| c.1.len()).sum()
+    }
+
+    /// Summary of the notification count and Do Not Disturb state.
+    fn tooltip_text(&self) -> String {
+        let count = fl!("notification-count", count = self.notification_count());
+        if self.config.do_not_disturb {
+            format!("{count} • {}", fl!("do-not-disturb-on"))
+        } else {
+            count
+        }
+    }
+
+    fn panel_button(&self) -> Element<Message> {
+        let btn = self
+            .core
+            .applet
+            .icon_button(&self.icon_name)
+            .on_press_down(Message::TogglePopup);
+
+        let Some(count) = self.badge_count() else {
+            return btn.into();
+        };
+
+        // Keep the badge on the side of the icon facing away from the screen edge
+        let (align_x, align_y) = match self.core.applet.anchor {
+            PanelAnchor::Top => (Alignment::End, Alignment::End),
+            PanelAnchor::Bottom => (Alignment::End, Alignment::Start),
+            PanelAnchor::Left => (Alignment::End, Alignment::End),
+            PanelAnchor::Right => (Alignment::Start, Alignment::End),
+        };
+
+        let badge = container(
+            text(if count > 99 {
+                String::from("99+")
+            } else {
+                count.to_string()
+            })
+            .size(10),
+        )
+        .padding([0, 4])
+        .class(theme::Container::custom(|theme| {
+            let cosmic = theme.cosmic();
+            cosmic::iced::widget::container::Style {
+                text_color: Some(cosmic.accent.on.into()),
+                background: Some(Background::Color(cosmic.accent_color().into())),
+                border: Border {
+                    radius: cosmic.radius_xl().into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        }));
+
+        stack![
+            btn,
+            container(badge)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(align_x)
+                .align_y(align_y)
+        ]
+        .into()
+    }
+
+    fn badge_count(&self) -> Option<usize> {
+        let count = match self.applet_config.panel_badge {
+            PanelBadge::None => return None,
+            PanelBadge::Groups => self.cards.len(),
+            PanelBadge::Total => self.notification_count(),
+        };
+        (count > 0).then_some(count)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -85,17 +254,54 @@ enum Message {
     CloseRequested(window::Id),
     DoNotDisturb(chain::Toggler, bool),
     Frame(Instant),
+    /// Redraw the popup so that relative timestamps stay current.
+    Tick,
     NotificationEvent(notifications::Output),
     Config(NotificationsConfig),
+    AppletConfig(NotificationsAppletConfig),
     DbusEvent(subscriptions::dbus::Output),
     Dismissed(u32),
     ActivateNotification(u32),
+    /// Invoke one of the notification's own actions by its key.
+    InvokeAction(u32, String),
     ClearAll(Option<String>),
+    RequestClearAll,
+    CancelClearAll,
     CardsToggled(String, bool),
+    MutePrompt(String),
+    CancelMute,
+    MuteApp(String),
+    ToggleHistory,
+    ClearHistory,
     Token(TokenUpdate),
     OpenSettings,
 }
 
+/// Action invoked when a notification is clicked: the default action if the
+/// notification has one, otherwise its first action. `None` means the
+/// notification should be dismissed instead.
+fn activation_action(notification: &Notification) -> Option<String> {
+    if notification
+        .actions
+        .iter()
+        .any(|a| matches!(a.0, ActionId::Default))
+    {
+        Some(ActionId::Default.to_string())
+    } else {
+        notification.actions.first().map(|a| a.0.to_string())
+    }
+}
+
+/// Actions shown as buttons on a card: all but the default action, which is
+/// invoked by clicking the card, and inline replies, which aren't supported.
+fn button_actions(notification: &Notification) -> impl Iterator<Item = (&ActionId, &str)> {
+    notification
+        .actions
+        .iter()
+        .filter(|a| !matches!(a.0, ActionId::Default) && a.0.to_string() != "inline-reply")
+        .map(|a| (&a.0, a.1.as_str()))
+}
+
 impl cosmic::Application for Notifications {
     type Message = Message;
     type Executor = cosmic::SingleThreadExecutor;
@@ -126,10 +332,17 @@ impl cosmic::Application for Notifications {
                 })
             })
             .unwrap_or_default();
+        let applet_helper = Config::new(Self::APP_ID, NotificationsAppletConfig::VERSION).ok();
+        let applet_config = applet_helper
+            .as_ref()
+            .and_then(|helper| NotificationsAppletConfig::get_entry(helper).ok())
+            .unwrap_or_default();
         let mut _self = Self {
             core,
             config_helper: helper,
+            applet_config_helper: applet_helper,
             config,
+            applet_config,
             icon_name: Default::default(),
             popup: None,
             timeline: Default::default(),
@@ -139,6 +352,12 @@ impl cosmic::Application for Notifications {
             proxy: block_on(crate::subscriptions::notifications::get_proxy())
                 .expect("Failed to get proxy"),
             notifications_tx: None,
+            confirm_clear_all: false,
+            mute_prompt: None,
+            history: VecDeque::new(),
+            show_history: false,
+            last_sound: HashMap::new(),
+            icons: HashMap::new(),
         };
         _self.update_icon();
         (_self, Task::none())
@@ -157,7 +376,7 @@ impl cosmic::Application for Notifications {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        Subscription::batch(vec![
+        let mut subscriptions = vec![
             self.core
                 .watch_config(cosmic_notifications_config::ID)
                 .map(|res| {
@@ -173,7 +392,25 @@ impl cosmic::Application for Notifications {
             subscriptions::notifications::notifications(self.proxy.clone())
                 .map(Message::NotificationEvent),
             activation_token_subscription(0).map(Message::Token),
-        ])
+            self.core.watch_config(Self::APP_ID).map(|res| {
+                for err in res.errors {
+                    tracing::error!("{:?}", err);
+                }
+                Message::AppletConfig(res.config)
+            }),
+        ];
+
+        if self.popup.is_some() {
+            subscriptions
+                .push(cosmic::iced::time::every(Duration::from_secs(30)).map(|_| Message::Tick));
+        }
+
+        if self.applet_config.toggle_shortcut {
+            subscriptions
+                .push(subscriptions::toggle::toggle_requests().map(|()| Message::TogglePopup));
+        }
+
+        Subscription::batch(subscriptions)
     }
 
     fn update(
@@ -184,6 +421,7 @@ impl cosmic::Application for Notifications {
             Message::Frame(now) => {
                 self.timeline.now(now);
             }
+            Message::Tick => {}
             Message::TogglePopup => {
                 if let Some(p) = self.popup.take() {
                     return destroy_popup(p);
@@ -191,6 +429,7 @@ impl cosmic::Application for Notifications {
                     let new_id = window::Id::unique();
                     self.popup.replace(new_id);
                     self.timeline = Timeline::new();
+                    self.mute_prompt = None;
 
                     let mut popup_settings = self.core.applet.get_popup_settings(
                         self.core.main_window_id().unwrap(),
@@ -204,6 +443,9 @@ impl cosmic::Application for Notifications {
                         .max_width(444.0)
                         .min_height(100.0)
                         .max_height(900.0);
+                    let (x, y) = self.applet_config.popup_offset;
+                    popup_settings.positioner.offset.0 += x;
+                    popup_settings.positioner.offset.1 += y;
                     return get_popup(popup_settings);
                 }
             }
@@ -217,7 +459,26 @@ impl cosmic::Application for Notifications {
                 }
             }
             Message::NotificationEvent(event) => match event {
-                notifications::Output::Notification(n) => {
+                notifications::Output::Notification(mut n, sender) => {
+                    if n.app_name.is_empty() {
+                        n.app_name = fallback_app_name(&n, sender);
+                    }
+                    if self.applet_config.muted_apps.contains(&n.app_name) {
+                        info!("Ignoring notification {} from muted {}", n.id, n.app_name);
+                        return Task::none();
+                    }
+                    if !self
+                        .cards
+                        .iter()
+                        .any(|c| c.1.iter().any(|notif| notif.id == n.id))
+                    {
+                        self.play_sound(&n);
+                    }
+                    let cards = &self.cards;
+                    self.icons
+                        .retain(|id, _| cards.iter().any(|c| c.1.iter().any(|n| n.id == *id)));
+                    self.icons.insert(n.id, notification_icon(&n));
+                    let mut overflowed = Vec::new();
                     if let Some(c) = self
                         .cards
                         .iter_mut()
@@ -227,6 +488,21 @@ impl cosmic::Application for Notifications {
                             *notif = n;
                         } else {
                             c.1.push(n);
+                            for id in overflowing(&c.1, self.applet_config.max_per_app) {
+                                if let Some(pos) = c.1.iter().position(|n| n.id == id) {
+                                    overflowed.push(c.1.remove(pos));
+                                }
+                                if let Some(tx) = &self.dbus_sender {
+                                    let tx = tx.clone();
+                                    tokio::spawn(async move {
+                                        if let Err(err) =
+                                            tx.send(subscriptions::dbus::Input::Dismiss(id)).await
+                                        {
+                                            tracing::error!("{:?}", err);
+                                        }
+                                    });
+                                }
+                            }
                             c.3 = fl!(
                                 "show-more",
                                 HashMap::from_iter(vec![("more", c.1.len().saturating_sub(1))])
@@ -242,6 +518,7 @@ impl cosmic::Application for Notifications {
                             fl!("clear-group"),
                         ));
                     }
+                    self.archive(overflowed);
                 }
                 notifications::Output::Ready(tx) => {
                     self.notifications_tx = Some(tx);
@@ -250,12 +527,13 @@ impl cosmic::Application for Notifications {
             Message::Config(config) => {
                 self.config = config;
             }
+            Message::AppletConfig(config) => {
+                self.applet_config = config;
+            }
             Message::Dismissed(id) => {
                 info!("Dismissed {}", id);
-                for c in &mut self.cards {
-                    c.1.retain(|n| n.id != id);
-                }
-                self.cards.retain(|c| !c.1.is_empty());
+                let removed = self.remove_notification(id);
+                self.archive(removed);
 
                 if let Some(tx) = &self.dbus_sender {
                     let tx = tx.clone();
@@ -271,8 +549,9 @@ impl cosmic::Application for Notifications {
                     self.dbus_sender.replace(tx);
                 }
                 subscriptions::dbus::Output::CloseEvent(id) => {
+                    let removed = self.remove_notification(id);
+                    self.archive(removed);
                     for c in &mut self.cards {
-                        c.1.retain(|n| n.id != id);
                         c.3 = fl!(
                             "show-more",
                             HashMap::from_iter(vec![("more", c.1.len().saturating_sub(1))])
@@ -287,33 +566,72 @@ impl cosmic::Application for Notifications {
                     .iter_mut()
                     .position(|c| c.1.iter().any(|notif| app_name == notif.app_name))
                 {
-                    for n in self.cards.remove(pos).1 {
+                    let removed = self.cards.remove(pos).1;
+                    for id in removed.iter().map(|n| n.id) {
                         if let Some(tx) = &self.dbus_sender {
                             let tx = tx.clone();
                             tokio::spawn(async move {
                                 if let Err(err) =
-                                    tx.send(subscriptions::dbus::Input::Dismiss(n.id)).await
+                                    tx.send(subscriptions::dbus::Input::Dismiss(id)).await
                                 {
                                     tracing::error!("{:?}", err);
                                 }
                             });
                         }
                     }
+                    self.archive(removed);
                 }
             }
+            Message::RequestClearAll => {
+                let threshold = self.applet_config.clear_all_confirm_threshold;
+                if threshold > 0 && self.notification_count() > threshold {
+                    self.confirm_clear_all = true;
+                } else {
+                    return self.update(Message::ClearAll(None));
+                }
+            }
+            Message::CancelClearAll => {
+                self.confirm_clear_all = false;
+            }
             Message::ClearAll(None) => {
-                for n in self.cards.drain(..).map(|n| n.1).flatten() {
+                self.confirm_clear_all = false;
+                let removed: Vec<_> = self.cards.drain(..).flat_map(|n| n.1).collect();
+                for id in removed.iter().map(|n| n.id) {
                     if let Some(tx) = &self.dbus_sender {
                         let tx = tx.clone();
                         tokio::spawn(async move {
-                            if let Err(err) =
-                                tx.send(subscriptions::dbus::Input::Dismiss(n.id)).await
+                            if let Err(err) = tx.send(subscriptions::dbus::Input::Dismiss(id)).await
                             {
                                 tracing::error!("{:?}", err);
                             }
                         });
                     }
                 }
+                self.archive(removed);
+            }
+            Message::ToggleHistory => {
+                self.show_history = !self.show_history;
+            }
+            Message::ClearHistory => {
+                self.history.clear();
+                self.show_history = false;
+            }
+            Message::MutePrompt(app_name) => {
+                self.mute_prompt = Some(app_name);
+            }
+            Message::CancelMute => {
+                self.mute_prompt = None;
+            }
+            Message::MuteApp(app_name) => {
+                self.mute_prompt = None;
+                if !self.applet_config.muted_apps.contains(&app_name) {
+                    self.applet_config.muted_apps.push(app_name);
+                    if let Some(helper) = &self.applet_config_helper {
+                        if let Err(err) = self.applet_config.write_entry(helper) {
+                            tracing::error!("{:?}", err);
+                        }
+                    }
+                }
             }
             Message::CardsToggled(name, expanded) => {
                 let id = if let Some((id, _, n_expanded, ..)) = self
@@ -331,6 +649,7 @@ impl cosmic::Application for Notifications {
             Message::CloseRequested(id) => {
                 if Some(id) == self.popup {
                     self.popup = None;
+                    self.confirm_clear_all = false;
                 }
             }
             Message::OpenSettings => {
@@ -360,7 +679,7 @@ impl cosmic::Application for Notifications {
                 }
             },
             Message::ActivateNotification(id) => {
-                tracing::error!("Received notification action Message");
+                tracing::trace!("Received notification action message");
                 let Some(notification) = self
                     .cards
                     .iter()
@@ -368,33 +687,23 @@ impl cosmic::Application for Notifications {
                 else {
                     return cosmic::task::message(Message::Dismissed(id));
                 };
-                tracing::error!("Found notification for id");
 
-                let maybe_action = if notification
-                    .actions
-                    .iter()
-                    .any(|a| matches!(a.0, ActionId::Default))
-                {
-                    Some(ActionId::Default.to_string())
-                } else {
-                    notification.actions.get(0).map(|a| a.0.to_string())
-                };
-
-                let Some(action) = maybe_action else {
+                let Some(action) = activation_action(notification) else {
                     return cosmic::task::message(Message::Dismissed(id));
                 };
-                tracing::error!("Found default action for notification");
+                return self.update(Message::InvokeAction(id, action));
+            }
+            Message::InvokeAction(id, action) => {
+                tracing::debug!(id, %action, "Activating notification");
 
                 if let Some(tx) = &self.notifications_tx {
-                    tracing::error!("Sending notification action");
-
                     let tx = tx.clone();
                     tokio::spawn(async move {
                         if let Err(err) = tx.send(notifications::Input::Activated(id, action)).await
                         {
                             tracing::error!("{:?}", err);
                         } else {
-                            tracing::error!("Sent notification action");
+                            tracing::trace!("Sent notification action");
                         }
                     });
                 }
@@ -405,10 +714,14 @@ impl cosmic::Application for Notifications {
     }
 
     fn view(&self) -> Element<Message> {
-        self.core
-            .applet
-            .icon_button(&self.icon_name)
-            .on_press_down(Message::TogglePopup)
+        let position = match self.core.applet.anchor {
+            PanelAnchor::Left => tooltip::Position::Right,
+            PanelAnchor::Right => tooltip::Position::Left,
+            PanelAnchor::Top => tooltip::Position::Bottom,
+            PanelAnchor::Bottom => tooltip::Position::Top,
+        };
+        tooltip(self.panel_button(), text(self.tooltip_text()), position)
+            .snap_within_viewport(false)
             .into()
     }
 
@@ -441,15 +754,26 @@ impl cosmic::Application for Notifications {
             .spacing(12)
         } else {
             let mut notifs: Vec<Element<_>> = Vec::with_capacity(self.cards.len());
-            notifs.push(
+            notifs.push(if self.confirm_clear_all {
+                row![
+                    text::body(fl!("clear-all-confirm", count = self.notification_count()))
+                        .width(Length::Fill),
+                    cosmic::widget::button::text(fl!("cancel")).on_press(Message::CancelClearAll),
+                    cosmic::widget::button::destructive(fl!("clear"))
+                        .on_press(Message::ClearAll(None)),
+                ]
+                .spacing(space_xxs)
+                .align_y(Alignment::Center)
+                .into()
+            } else {
                 container(
                     cosmic::widget::button::text(fl!("clear-all"))
-                        .on_press(Message::ClearAll(None)),
+                        .on_press(Message::RequestClearAll),
                 )
                 .width(Length::Fill)
                 .align_x(Alignment::End)
-                .into(),
-            );
+                .into()
+            });
             for c in self.cards.iter().rev() {
                 if c.1.is_empty() {
                     continue;
@@ -480,6 +804,16 @@ impl cosmic::Application for Notifications {
                         )
                         .on_press(Message::Dismissed(n.id))
                         .class(cosmic::theme::Button::Text);
+
+                        let actions = button_actions(n).fold(
+                            row![].spacing(space_xxs),
+                            |row, (key, label)| {
+                                row.push(
+                                    button::text(label)
+                                        .on_press(Message::InvokeAction(n.id, key.to_string())),
+                                )
+                            },
+                        );
                         (
                             n.id,
                             Element::from(
@@ -537,39 +871,15 @@ impl cosmic::Application for Notifications {
                                         text(n.body.lines().next().unwrap_or_default())
                                             .width(Length::Fill)
                                             .size(12)
-                                    ]
+                                    ],
+                                    actions,
                                 )
                                 .width(Length::Fill),
                             ),
                         )
                     })
                     .unzip();
-                let show_more_icon = c.1.last().and_then(|n| {
-                    info!("app_icon: {:?}", &n.app_icon);
-                    if n.app_icon.is_empty() {
-                        match n.image().cloned() {
-                            Some(Image::File(p)) => Some(cosmic::widget::icon::from_path(p)),
-                            Some(Image::Name(name)) => {
-                                Some(cosmic::widget::icon::from_name(name).handle())
-                            }
-                            Some(Image::Data {
-                                width,
-                                height,
-                                data,
-                            }) => Some(cosmic::widget::icon::from_raster_pixels(
-                                width, height, data,
-                            )),
-                            None => None,
-                        }
-                    } else if let Some(path) = url::Url::parse(&n.app_icon)
-                        .ok()
-                        .and_then(|u| u.to_file_path().ok())
-                    {
-                        Some(cosmic::widget::icon::from_path(path))
-                    } else {
-                        Some(cosmic::widget::icon::from_name(n.app_icon.clone()).handle())
-                    }
-                });
+                let show_more_icon = c.1.last().and_then(|n| self.icons.get(&n.id).cloned());
                 let card_list = anim!(
                     //cards
                     c.0.clone(),
@@ -584,7 +894,25 @@ impl cosmic::Application for Notifications {
                     show_more_icon,
                     c.2,
                 );
-                notifs.push(card_list.into());
+                let app_name = c.1[0].app_name.clone();
+                if self.mute_prompt.as_ref() == Some(&app_name) {
+                    notifs.push(
+                        row![
+                            cosmic::widget::button::text(fl!("cancel"))
+                                .on_press(Message::CancelMute),
+                            cosmic::widget::button::text(fl!("mute-app", app = app_name.as_str()))
+                                .on_press(Message::MuteApp(app_name.clone())),
+                        ]
+                        .spacing(space_xxs)
+                        .align_y(Alignment::Center)
+                        .into(),
+                    );
+                }
+                notifs.push(
+                    cosmic::widget::mouse_area(card_list)
+                        .on_right_press(Message::MutePrompt(app_name))
+                        .into(),
+                );
             }
 
             row!(scrollable(
@@ -596,10 +924,52 @@ impl cosmic::Application for Notifications {
             .padding(menu_control_padding())
         };
 
+        let history = (!self.history.is_empty()).then(|| {
+            let toggle = row![
+                cosmic::widget::button::text(fl!("history", count = self.history.len()))
+                    .trailing_icon(
+                        icon::from_name(if self.show_history {
+                            "go-up-symbolic"
+                        } else {
+                            "go-down-symbolic"
+                        })
+                        .handle()
+                    )
+                    .on_press(Message::ToggleHistory),
+                cosmic::iced::widget::horizontal_space(),
+                cosmic::widget::button::text(fl!("clear-history")).on_press(Message::ClearHistory),
+            ]
+            .align_y(Alignment::Center);
+
+            let entries = self.show_history.then(|| {
+                scrollable(
+                    Column::with_children(self.history.iter().map(|n| {
+                        column![
+                            row![
+                                text::caption(n.app_name.as_str()).width(Length::Fill),
+                                text::caption(duration_ago_msg(n)),
+                            ]
+                            .spacing(8),
+                            text::body(n.summary.lines().next().unwrap_or_default()),
+                        ]
+                        .into()
+                    }))
+                    .spacing(space_xxs),
+                )
+                .height(Length::Shrink)
+            });
+
+            column![
+                padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+                padded_control(column![toggle].push_maybe(entries).spacing(space_xxs)),
+            ]
+        });
+
         let main_content = column![
             padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
             notifications,
-        ];
+        ]
+        .push_maybe(history);
 
         let content = column![do_not_disturb, main_content]
             .align_x(Alignment::Start)
@@ -617,6 +987,99 @@ impl cosmic::Application for Notifications {
     }
 }
 
+/// Resolves the icon of a notification from its image or `app_icon`, then the
+/// icon of its `desktop-entry` hint, then an icon named after the application,
+/// falling back to a bell.
+fn notification_icon(n: &Notification) -> icon::Handle {
+    info!("app_icon: {:?}", &n.app_icon);
+    if !n.app_icon.is_empty() {
+        return if let Some(path) = url::Url::parse(&n.app_icon)
+            .ok()
+            .and_then(|u| u.to_file_path().ok())
+        {
+            icon::from_path(path)
+        } else {
+            icon::from_name(n.app_icon.clone()).handle()
+        };
+    }
+
+    match n.image().cloned() {
+        Some(Image::File(p)) => return icon::from_path(p),
+        Some(Image::Name(name)) => return icon::from_name(name).handle(),
+        Some(Image::Data {
+            width,
+            height,
+            data,
+        }) => return icon::from_raster_pixels(width, height, data),
+        None => {}
+    }
+
+    if let Some(name) = desktop_entry_hint(n)
+        .and_then(|id| desktop_entry(id, |entry, _| entry.icon().map(ToOwned::to_owned)))
+    {
+        return icon::from_name(name).handle();
+    }
+
+    let app_name = n.app_name.to_lowercase().replace(' ', "-");
+    if let Some(path) = (!app_name.is_empty())
+        .then(|| icon::from_name(app_name).path())
+        .flatten()
+    {
+        return icon::from_path(path);
+    }
+
+    icon::from_name("cosmic-applet-notification-symbolic").handle()
+}
+
+/// The oldest non-critical notifications of a group beyond `max`, which
+/// should be dismissed. A `max` of 0 keeps everything.
+fn overflowing(notifications: &[Notification], max: usize) -> Vec<u32> {
+    if max == 0 {
+        return Vec::new();
+    }
+
+    let mut ids: Vec<u32> = notifications
+        .iter()
+        .filter(|n| !n.hints.iter().any(|hint| matches!(hint, Hint::Urgency(2))))
+        .map(|n| n.id)
+        .collect();
+    let excess = ids.len().saturating_sub(max);
+    ids.truncate(excess);
+    ids
+}
+
+/// Name to group and label a notification by when it has no `app_name`:
+/// the name of its `desktop-entry` hint, the hint itself, or the bus name
+/// of its sender.
+fn fallback_app_name(n: &Notification, sender: Option<String>) -> String {
+    let Some(id) = desktop_entry_hint(n) else {
+        return sender.unwrap_or_default();
+    };
+    desktop_entry(id, |entry, locales| {
+        entry.name(locales).map(|name| name.into_owned())
+    })
+    .unwrap_or_else(|| id.to_owned())
+}
+
+fn desktop_entry_hint(n: &Notification) -> Option<&str> {
+    n.hints.iter().find_map(|hint| match hint {
+        Hint::DesktopEntry(id) => Some(id.as_str()),
+        _ => None,
+    })
+}
+
+/// Reads a value from the desktop entry with the given id.
+fn desktop_entry<T>(id: &str, f: impl Fn(&DesktopEntry, &[String]) -> Option<T>) -> Option<T> {
+    let filename = format!("{}.desktop", id.trim_end_matches(".desktop"));
+    let locales = get_languages_from_env();
+    freedesktop_desktop_entry::default_paths().find_map(|mut path| {
+        path.push(&filename);
+        let bytes = std::fs::read_to_string(&path).ok()?;
+        let entry = DesktopEntry::from_str(&path, &bytes, Some(&locales)).ok()?;
+        f(&entry, &locales)
+    })
+}
+
 fn text_icon(name: &str, size: u16) -> cosmic::widget::Icon {
     icon::from_name(name).size(size).symbolic(true).icon()
 }
@@ -634,3 +1097,37 @@ fn duration_ago_msg(notification: &Notification) -> String {
         String::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(actions: Vec<&str>) -> Notification {
+        Notification::new("app", 1, "", "summary", "body", actions, HashMap::new(), -1)
+    }
+
+    #[test]
+    fn activation_prefers_default_action() {
+        let n = notification(vec!["open", "Open", "default", "Activate"]);
+        assert_eq!(activation_action(&n), Some(ActionId::Default.to_string()));
+    }
+
+    #[test]
+    fn activation_falls_back_to_first_action() {
+        let n = notification(vec!["open", "Open", "archive", "Archive"]);
+        assert_eq!(activation_action(&n), Some("open".to_string()));
+    }
+
+    #[test]
+    fn activation_without_actions_dismisses() {
+        let n = notification(Vec::new());
+        assert_eq!(activation_action(&n), None);
+    }
+
+    #[test]
+    fn fallback_name_without_desktop_entry_uses_sender() {
+        let n = notification(Vec::new());
+        assert_eq!(fallback_app_name(&n, Some(":1.42".to_string())), ":1.42");
+        assert_eq!(fallback_app_name(&n, None), "");
+    }
+}
@@ -4,3 +4,4 @@
 pub mod dbus;
 mod freedesktop_proxy;
 pub mod notifications;
+pub mod toggle;
@@ -37,7 +37,8 @@ pub enum Input {
 #[derive(Debug, Clone)]
 pub enum Output {
     Ready(mpsc::Sender<Input>),
-    Notification(Notification),
+    /// A notification, with the bus name of the connection that sent it.
+    Notification(Notification, Option<String>),
 }
 
 pub fn notifications(proxy: NotificationsAppletProxy<'static>) -> Subscription<Output> {
@@ -83,6 +84,11 @@ pub fn notifications(proxy: NotificationsAppletProxy<'static>) -> Subscription<O
                         cosmic::iced::futures::select! {
                             v = next_signal => {
                                 if let Some(msg) = v {
+                                    let sender = msg
+                                        .message()
+                                        .header()
+                                        .sender()
+                                        .map(|name| name.to_string());
                                     let Some(args) = msg.args().into_iter().next() else {
                                         break;
                                     };
@@ -96,7 +102,7 @@ pub fn notifications(proxy: NotificationsAppletProxy<'static>) -> Subscription<O
                                         args.hints,
                                         args.expire_timeout,
                                     );
-                                    _ = output.send(Output::Notification(notification)).await;
+                                    _ = output.send(Output::Notification(notification, sender)).await;
                                 } else {
                                     tracing::error!("Signal stream closed, ending notifications subscription");
                                     state = State::Finished;
@@ -0,0 +1,74 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Session bus interface used by the keyboard shortcut to toggle the popup.
+
+use cosmic::{
+    iced::futures::{self, SinkExt},
+    iced_futures::{stream, Subscription},
+};
+use tokio::sync::mpsc;
+use tracing::error;
+use zbus::{connection::Builder, interface, proxy};
+
+const NAME: &str = "com.system76.CosmicAppletNotifications";
+const PATH: &str = "/com/system76/CosmicAppletNotifications";
+
+struct Toggle {
+    tx: mpsc::Sender<()>,
+}
+
+#[interface(name = "com.system76.CosmicAppletNotifications")]
+impl Toggle {
+    async fn toggle_popup(&self) {
+        _ = self.tx.send(()).await;
+    }
+}
+
+#[proxy(
+    interface = "com.system76.CosmicAppletNotifications",
+    default_service = "com.system76.CosmicAppletNotifications",
+    default_path = "/com/system76/CosmicAppletNotifications"
+)]
+trait ToggleClient {
+    fn toggle_popup(&self) -> zbus::Result<()>;
+}
+
+/// Emits an event each time the popup toggle is requested over the session bus.
+pub fn toggle_requests() -> Subscription<()> {
+    struct SomeWorker;
+
+    Subscription::run_with_id(
+        std::any::TypeId::of::<SomeWorker>(),
+        stream::channel(10, |mut output| async move {
+            let (tx, mut rx) = mpsc::channel(10);
+            let conn = match Builder::session()
+                .and_then(|b| b.name(NAME))
+                .and_then(|b| b.serve_at(PATH, Toggle { tx }))
+            {
+                Ok(builder) => builder.build().await,
+                Err(err) => Err(err),
+            };
+
+            match conn {
+                Ok(_conn) => {
+                    while rx.recv().await.is_some() {
+                        _ = output.send(()).await;
+                    }
+                }
+                Err(err) => {
+                    error!("Failed to serve the toggle interface: {}", err);
+                }
+            }
+
+            let () = futures::future::pending().await;
+        }),
+    )
+}
+
+/// Asks the running applet to toggle its popup.
+pub async fn request_toggle() -> zbus::Result<()> {
+    let conn = zbus::Connection::session().await?;
+    let proxy = ToggleClientProxy::new(&conn).await?;
+    proxy.toggle_popup().await
+}
@@ -5,7 +5,9 @@ lock-screen-shortcut = Super + Escape
 log-out = Log Out
 log-out-shortcut = Super + Shift + Escape
 suspend = Suspend
+hibernate = Hibernate
 restart = Restart
+restart-to-firmware = Restart to UEFI firmware settings
 shutdown = Shutdown
 confirm = Confirm
 cancel = Cancel
@@ -15,6 +17,8 @@ confirm-button = {
         [suspend] { suspend}
         [shutdown] Power off
         [log-out] { log-out }
+        [hibernate] { hibernate }
+        [restart-to-firmware] { restart }
         *[other] { confirm}
 }
 confirm-title = 
@@ -23,6 +27,8 @@ confirm-title =
         [suspend] { suspend }
         [shutdown] { shutdown }
         [log-out] Quit all applications and log out
+        [hibernate] { hibernate }
+        [restart-to-firmware] { restart-to-firmware }
         *[other] Apply the selected action
     } now?
 confirm-body = 
@@ -32,6 +38,23 @@ confirm-body =
         [shutdown] power off
         [lock-screen] lock the screen
         [log-out] log out
+        [hibernate] hibernate
+        [restart-to-firmware] restart to the UEFI firmware settings
         *[other] apply the selected action
     } automatically in { $countdown } seconds.
 
+scheduled-shutdown = { $action ->
+        [restart] Restart
+        [halt] Halt
+        *[shutdown] Power off
+    } scheduled in { $minutes ->
+        [one] 1 minute
+        *[other] { $minutes } minutes
+    }
+
+log-out-others = Log Out Other Sessions
+log-out-others-confirm = Quit all applications in { $count ->
+        [one] 1 other session
+        *[other] { $count } other sessions
+    }?
+log-out-others-denied = Could not log out the other sessions
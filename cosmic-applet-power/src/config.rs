@@ -0,0 +1,17 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
+pub const APP_ID: &str = "com.system76.CosmicAppletPower";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct PowerAppletConfig {
+    /// Offer to log out the other sessions on this machine, for users allowed to manage them.
+    pub show_terminate_sessions: bool,
+    /// Seconds to wait for confirmation before logging out, restarting or
+    /// shutting down, which then happens automatically. Disabled if `None`.
+    pub confirm_countdown: Option<u32>,
+}
@@ -4,12 +4,14 @@
 use cosmic::{
     app,
     applet::{menu_button, padded_control},
+    cosmic_config::{self, CosmicConfigEntry},
     cosmic_theme::Spacing,
     iced::{
-        self,
+        self, event,
+        keyboard::{self, key::Named, Key},
         platform_specific::shell::commands::popup::{destroy_popup, get_popup},
         widget::{self, column, row},
-        window, Alignment, Length,
+        window, Alignment, Length, Subscription,
     },
     iced_runtime::core::layout::Limits,
     theme,
@@ -18,19 +20,33 @@ use cosmic::{
 };
 
 use logind_zbus::{
-    manager::ManagerProxy,
-    session::{SessionClass, SessionProxy, SessionType},
+    manager::{IsSupported, ManagerProxy},
+    session::{SessionClass, SessionProxy, SessionState, SessionType},
     user::UserProxy,
 };
 use rustix::process::getuid;
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use tokio::process;
-use zbus::Connection;
+use zbus::{zvariant::Value, Connection};
 
+mod config;
 pub mod cosmic_session;
 mod localize;
+pub mod logind;
+pub mod polkit;
 pub mod session_manager;
 
-use crate::{cosmic_session::CosmicSessionProxy, session_manager::SessionManagerProxy};
+use crate::{
+    config::PowerAppletConfig, cosmic_session::CosmicSessionProxy, logind::LogindManagerProxy,
+    polkit::PolkitAuthorityProxy, session_manager::SessionManagerProxy,
+};
+
+/// Seconds to confirm actions that cosmic-osd can't confirm, when the config
+/// has no countdown of its own. The same as cosmic-osd's.
+const DEFAULT_CONFIRM_COUNTDOWN: u32 = 60;
 
 pub fn run() -> cosmic::iced::Result {
     localize::localize();
@@ -43,6 +59,69 @@ struct Power {
     core: cosmic::app::Core,
     icon_name: String,
     popup: Option<window::Id>,
+    scheduled_shutdown: Option<ScheduledShutdown>,
+    can_restart_to_firmware: bool,
+    can_hibernate: bool,
+    config: PowerAppletConfig,
+    other_sessions: Vec<OtherSession>,
+    confirm_terminate_sessions: bool,
+    terminate_sessions_failed: bool,
+    /// Action waiting for confirmation and the seconds left until it's done.
+    confirm: Option<(PowerAction, u32)>,
+}
+
+/// A shutdown or reboot scheduled through logind's `ScheduleShutdown`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScheduledShutdown {
+    kind: String,
+    time: SystemTime,
+}
+
+impl ScheduledShutdown {
+    fn from_property((kind, usec): (String, u64)) -> Option<Self> {
+        if kind.is_empty() || usec == 0 {
+            return None;
+        }
+
+        Some(Self {
+            kind,
+            time: UNIX_EPOCH + Duration::from_micros(usec),
+        })
+    }
+
+    /// The fluent selector for the scheduled action.
+    fn action(&self) -> &'static str {
+        match self.kind.as_str() {
+            "reboot" | "dry-reboot" => "restart",
+            "halt" | "dry-halt" => "halt",
+            _ => "shutdown",
+        }
+    }
+
+    fn minutes_remaining(&self) -> u64 {
+        self.time
+            .duration_since(SystemTime::now())
+            .map(|d| d.as_secs().div_ceil(60))
+            .unwrap_or_default()
+    }
+}
+
+/// A session other than ours that logind can terminate on our behalf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OtherSession {
+    id: String,
+    user: String,
+    seat: String,
+}
+
+impl OtherSession {
+    fn label(&self) -> String {
+        if self.seat.is_empty() {
+            self.user.clone()
+        } else {
+            format!("{} ({})", self.user, self.seat)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -50,18 +129,35 @@ enum PowerAction {
     Lock,
     LogOut,
     Suspend,
+    Hibernate,
     Restart,
+    RestartToFirmware,
     Shutdown,
 }
 
 impl PowerAction {
+    /// The fluent selector for the confirmation dialog.
+    fn name(self) -> &'static str {
+        match self {
+            PowerAction::Lock => "lock-screen",
+            PowerAction::LogOut => "log-out",
+            PowerAction::Suspend => "suspend",
+            PowerAction::Restart => "restart",
+            PowerAction::Shutdown => "shutdown",
+            PowerAction::Hibernate => "hibernate",
+            PowerAction::RestartToFirmware => "restart-to-firmware",
+        }
+    }
+
     fn perform(self) -> iced::Task<cosmic::app::Message<Message>> {
         let msg = |m| cosmic::app::message::app(Message::Zbus(m));
         match self {
             PowerAction::Lock => iced::Task::perform(lock(), msg),
             PowerAction::LogOut => iced::Task::perform(log_out(), msg),
             PowerAction::Suspend => iced::Task::perform(suspend(), msg),
+            PowerAction::Hibernate => iced::Task::perform(hibernate(), msg),
             PowerAction::Restart => iced::Task::perform(restart(), msg),
+            PowerAction::RestartToFirmware => iced::Task::perform(restart_to_firmware(), msg),
             PowerAction::Shutdown => iced::Task::perform(shutdown(), msg),
         }
     }
@@ -74,13 +170,29 @@ enum Message {
     Settings,
     Zbus(Result<(), zbus::Error>),
     Closed(window::Id),
+    ScheduledShutdown(Option<ScheduledShutdown>),
+    RefreshScheduledShutdown,
+    CancelScheduledShutdown,
+    Confirm,
+    CancelConfirm,
+    ConfirmTick,
+    CanRestartToFirmware(bool),
+    CanHibernate(bool),
+    OtherSessions(Vec<OtherSession>),
+    TerminateSessions,
+    CancelTerminateSessions,
+    ConfirmTerminateSessions,
+    SessionsTerminated(Result<(), zbus::Error>),
+    ConfigChanged(PowerAppletConfig),
+    /// Move keyboard focus to the next action, or the previous one if `true`.
+    FocusNext(bool),
 }
 
 impl cosmic::Application for Power {
     type Executor = cosmic::SingleThreadExecutor;
     type Flags = ();
     type Message = Message;
-    const APP_ID: &'static str = "com.system76.CosmicAppletPower";
+    const APP_ID: &'static str = config::APP_ID;
 
     fn core(&self) -> &cosmic::app::Core {
         &self.core
@@ -95,6 +207,10 @@ impl cosmic::Application for Power {
             Self {
                 core,
                 icon_name: "system-shutdown-symbolic".to_string(),
+                config: cosmic_config::Config::new(config::APP_ID, PowerAppletConfig::VERSION)
+                    .ok()
+                    .and_then(|c| PowerAppletConfig::get_entry(&c).ok())
+                    .unwrap_or_default(),
                 ..Default::default()
             },
             Task::none(),
@@ -109,10 +225,13 @@ impl cosmic::Application for Power {
         match message {
             Message::TogglePopup => {
                 if let Some(p) = self.popup.take() {
+                    self.confirm = None;
                     destroy_popup(p)
                 } else {
                     let new_id = window::Id::unique();
                     self.popup.replace(new_id);
+                    self.confirm_terminate_sessions = false;
+                    self.terminate_sessions_failed = false;
 
                     let mut popup_settings = self.core.applet.get_popup_settings(
                         self.core.main_window_id().unwrap(),
@@ -126,14 +245,135 @@ impl cosmic::Application for Power {
                         .min_height(100.0)
                         .max_height(400.0)
                         .max_width(500.0);
-                    get_popup(popup_settings)
+                    let sessions = if self.config.show_terminate_sessions {
+                        iced::Task::perform(other_sessions(), |s| {
+                            cosmic::app::message::app(Message::OtherSessions(s))
+                        })
+                    } else {
+                        Task::none()
+                    };
+                    Task::batch([
+                        get_popup(popup_settings),
+                        sessions,
+                        iced::Task::perform(scheduled_shutdown(), |s| {
+                            cosmic::app::message::app(Message::ScheduledShutdown(s))
+                        }),
+                        iced::Task::perform(can_restart_to_firmware(), |can| {
+                            cosmic::app::message::app(Message::CanRestartToFirmware(can))
+                        }),
+                        iced::Task::perform(can_hibernate(), |can| {
+                            cosmic::app::message::app(Message::CanHibernate(can))
+                        }),
+                    ])
+                }
+            }
+            Message::ScheduledShutdown(scheduled) => {
+                self.scheduled_shutdown = scheduled;
+                Task::none()
+            }
+            Message::RefreshScheduledShutdown => iced::Task::perform(scheduled_shutdown(), |s| {
+                cosmic::app::message::app(Message::ScheduledShutdown(s))
+            }),
+            Message::CanRestartToFirmware(can) => {
+                self.can_restart_to_firmware = can;
+                Task::none()
+            }
+            Message::CanHibernate(can) => {
+                self.can_hibernate = can;
+                Task::none()
+            }
+            Message::OtherSessions(sessions) => {
+                if sessions.is_empty() {
+                    self.confirm_terminate_sessions = false;
+                }
+                self.other_sessions = sessions;
+                Task::none()
+            }
+            Message::TerminateSessions => {
+                self.confirm_terminate_sessions = true;
+                self.terminate_sessions_failed = false;
+                Task::none()
+            }
+            Message::CancelTerminateSessions => {
+                self.confirm_terminate_sessions = false;
+                Task::none()
+            }
+            Message::ConfirmTerminateSessions => {
+                self.confirm_terminate_sessions = false;
+                let ids = self.other_sessions.iter().map(|s| s.id.clone()).collect();
+                iced::Task::perform(terminate_sessions(ids), |r| {
+                    cosmic::app::message::app(Message::SessionsTerminated(r))
+                })
+            }
+            Message::SessionsTerminated(result) => {
+                if let Err(err) = result {
+                    tracing::error!("Failed to terminate other sessions. {err:?}");
+                    self.terminate_sessions_failed = true;
                 }
+                iced::Task::perform(other_sessions(), |s| {
+                    cosmic::app::message::app(Message::OtherSessions(s))
+                })
+            }
+            Message::ConfigChanged(config) => {
+                if !config.show_terminate_sessions {
+                    self.other_sessions.clear();
+                    self.confirm_terminate_sessions = false;
+                }
+                self.config = config;
+                Task::none()
+            }
+            Message::FocusNext(reverse) => {
+                if reverse {
+                    widget::focus_previous()
+                } else {
+                    widget::focus_next()
+                }
+            }
+            Message::CancelScheduledShutdown => {
+                self.scheduled_shutdown = None;
+                iced::Task::perform(cancel_scheduled_shutdown(), |m| {
+                    cosmic::app::message::app(Message::Zbus(m))
+                })
             }
             Message::Settings => {
                 let _ = process::Command::new("cosmic-settings").spawn();
                 Task::none()
             }
+            Message::Confirm => match self.confirm.take() {
+                Some((action, _)) => action.perform(),
+                None => Task::none(),
+            },
+            Message::CancelConfirm => {
+                self.confirm = None;
+                Task::none()
+            }
+            Message::ConfirmTick => match self.confirm {
+                Some((_, 0 | 1)) => self.update(Message::Confirm),
+                Some((_, ref mut countdown)) => {
+                    *countdown -= 1;
+                    Task::none()
+                }
+                None => Task::none(),
+            },
             Message::Action(action) => {
+                // cosmic-osd asks for confirmation itself, so it's skipped
+                // when the applet does
+                let countdown = match action {
+                    // cosmic-osd has no dialog for these, so the applet always asks
+                    PowerAction::Hibernate | PowerAction::RestartToFirmware => Some(
+                        self.config
+                            .confirm_countdown
+                            .unwrap_or(DEFAULT_CONFIRM_COUNTDOWN),
+                    ),
+                    PowerAction::LogOut | PowerAction::Restart | PowerAction::Shutdown => {
+                        self.config.confirm_countdown
+                    }
+                    PowerAction::Lock | PowerAction::Suspend => None,
+                };
+                if let Some(countdown) = countdown {
+                    self.confirm = Some((action, countdown));
+                    return Task::none();
+                }
                 match action {
                     PowerAction::LogOut => {
                         if let Err(err) = process::Command::new("cosmic-osd").arg("log-out").spawn()
@@ -170,6 +410,7 @@ impl cosmic::Application for Power {
             Message::Closed(id) => {
                 if self.popup == Some(id) {
                     self.popup = None;
+                    self.confirm = None;
                 }
                 Task::none()
             }
@@ -193,6 +434,16 @@ impl cosmic::Application for Power {
         } = theme::active().cosmic().spacing;
 
         if matches!(self.popup, Some(p) if p == id) {
+            if let Some(confirm) = self.confirm_view() {
+                return self
+                    .core
+                    .applet
+                    .popup_container(confirm)
+                    .max_height(400.)
+                    .max_width(500.)
+                    .into();
+            }
+
             let settings = menu_button(text::body(fl!("settings"))).on_press(Message::Settings);
 
             let session = column![
@@ -218,28 +469,67 @@ impl cosmic::Application for Power {
                     .spacing(space_xxs)
                 )
                 .on_press(Message::Action(PowerAction::LogOut)),
-            ];
-
-            let power = row![
-                power_buttons("system-suspend-symbolic", fl!("suspend"))
-                    .on_press(Message::Action(PowerAction::Suspend)),
+            ]
+            .push_maybe(self.terminate_sessions_view())
+            .push_maybe(self.can_restart_to_firmware.then(|| {
+                menu_button(
+                    row![
+                        text_icon("system-reboot-symbolic", 24),
+                        text::body(fl!("restart-to-firmware")),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(space_xxs),
+                )
+                .on_press(Message::Action(PowerAction::RestartToFirmware))
+            }));
+
+            let power = row![power_buttons("system-suspend-symbolic", fl!("suspend"))
+                .on_press(Message::Action(PowerAction::Suspend)),]
+            .push_maybe(self.can_hibernate.then(|| {
+                power_buttons("system-suspend-hibernate-symbolic", fl!("hibernate"))
+                    .on_press(Message::Action(PowerAction::Hibernate))
+            }))
+            .push(
                 power_buttons("system-reboot-symbolic", fl!("restart"))
                     .on_press(Message::Action(PowerAction::Restart)),
+            )
+            .push(
                 power_buttons("system-shutdown-symbolic", fl!("shutdown"))
                     .on_press(Message::Action(PowerAction::Shutdown)),
-            ]
+            )
             .spacing(space_m)
             .padding([0, space_m]);
 
-            let content = column![
-                settings,
-                padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
-                session,
-                padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
-                power
-            ]
-            .align_x(Alignment::Start)
-            .padding([8, 0]);
+            let scheduled = self.scheduled_shutdown.as_ref().map(|scheduled| {
+                column![
+                    padded_control(
+                        row![
+                            text_icon("alarm-symbolic", 24),
+                            text::body(fl!(
+                                "scheduled-shutdown",
+                                action = scheduled.action(),
+                                minutes = scheduled.minutes_remaining()
+                            ))
+                            .width(Length::Fill),
+                            button::standard(fl!("cancel"))
+                                .on_press(Message::CancelScheduledShutdown),
+                        ]
+                        .align_y(Alignment::Center)
+                        .spacing(space_xxs)
+                    ),
+                    padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+                ]
+            });
+
+            let content = column![]
+                .push_maybe(scheduled)
+                .push(settings)
+                .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]))
+                .push(session)
+                .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]))
+                .push(power)
+                .align_x(Alignment::Start)
+                .padding([8, 0]);
 
             self.core
                 .applet
@@ -253,14 +543,144 @@ impl cosmic::Application for Power {
         }
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        let config = self.core.watch_config(config::APP_ID).map(|u| {
+            for err in u.errors {
+                tracing::error!(?err, "Error watching config");
+            }
+            Message::ConfigChanged(u.config)
+        });
+
+        if self.popup.is_none() {
+            return config;
+        }
+
+        let countdown = if self.confirm.is_some() {
+            iced::time::every(Duration::from_secs(1)).map(|_| Message::ConfirmTick)
+        } else {
+            Subscription::none()
+        };
+
+        // keep the minutes until a scheduled shutdown current
+        let scheduled_shutdown = if self.scheduled_shutdown.is_some() {
+            iced::time::every(Duration::from_secs(60)).map(|_| Message::RefreshScheduledShutdown)
+        } else {
+            Subscription::none()
+        };
+
+        // Tab through the actions; the focused button is activated with Enter.
+        Subscription::batch([
+            config,
+            countdown,
+            scheduled_shutdown,
+            event::listen_with(|e, status, _| match e {
+                iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: Key::Named(Named::Tab),
+                    modifiers,
+                    ..
+                }) if status == event::Status::Ignored => {
+                    Some(Message::FocusNext(modifiers.shift()))
+                }
+                _ => None,
+            }),
+        ])
+    }
+
     fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
         Some(cosmic::applet::style())
     }
 }
 
+impl Power {
+    /// Asks to confirm the pending action while counting down to it.
+    fn confirm_view(&self) -> Option<Element<'_, Message>> {
+        let Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+        let (action, countdown) = self.confirm?;
+        let action = action.name();
+
+        Some(
+            padded_control(
+                column![
+                    text::title4(fl!("confirm-title", action = action)),
+                    text::body(fl!("confirm-body", action = action, countdown = countdown)),
+                    row![
+                        Space::with_width(Length::Fill),
+                        button::standard(fl!("cancel")).on_press(Message::CancelConfirm),
+                        button::destructive(fl!("confirm-button", action = action))
+                            .on_press(Message::Confirm),
+                    ]
+                    .spacing(space_xxs),
+                ]
+                .spacing(space_xxs),
+            )
+            .into(),
+        )
+    }
+
+    /// The entry for logging out other sessions, or its confirmation once chosen.
+    fn terminate_sessions_view(&self) -> Option<Element<'_, Message>> {
+        let Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+        if self.terminate_sessions_failed {
+            return Some(
+                padded_control(text::body(fl!("log-out-others-denied")).width(Length::Fill)).into(),
+            );
+        }
+
+        if !self.config.show_terminate_sessions || self.other_sessions.is_empty() {
+            return None;
+        }
+
+        if !self.confirm_terminate_sessions {
+            return Some(
+                menu_button(
+                    row![
+                        text_icon("system-users-symbolic", 24),
+                        text::body(fl!("log-out-others")),
+                        Space::with_width(Length::Fill),
+                        text::body(self.other_sessions.len().to_string()),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(space_xxs),
+                )
+                .on_press(Message::TerminateSessions)
+                .into(),
+            );
+        }
+
+        let sessions = self
+            .other_sessions
+            .iter()
+            .fold(column![].spacing(space_xxs), |col, session| {
+                col.push(text::caption(session.label()))
+            });
+
+        Some(
+            padded_control(
+                column![
+                    text::body(fl!(
+                        "log-out-others-confirm",
+                        count = self.other_sessions.len()
+                    )),
+                    sessions,
+                    row![
+                        Space::with_width(Length::Fill),
+                        button::standard(fl!("cancel")).on_press(Message::CancelTerminateSessions),
+                        button::destructive(fl!("log-out"))
+                            .on_press(Message::ConfirmTerminateSessions),
+                    ]
+                    .spacing(space_xxs),
+                ]
+                .spacing(space_xxs),
+            )
+            .into(),
+        )
+    }
+}
+
 fn power_buttons(name: &str, msg: String) -> cosmic::widget::Button<Message> {
     button::custom(
-        column![text_icon(name, 40), text::body(msg)]
+        column![text_icon(name, 40), text::body(msg.clone())]
             .spacing(4)
             .align_x(Alignment::Center)
             .width(Length::Fill),
@@ -268,6 +688,7 @@ fn power_buttons(name: &str, msg: String) -> cosmic::widget::Button<Message> {
     .width(Length::Fill)
     .height(Length::Fixed(76.0))
     .class(theme::Button::Text)
+    .name(msg)
 }
 
 fn text_icon(name: &str, size: u16) -> cosmic::widget::Icon {
@@ -282,18 +703,170 @@ async fn restart() -> zbus::Result<()> {
     manager_proxy.reboot(true).await
 }
 
+async fn restart_to_firmware() -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.set_reboot_to_firmware_setup(true).await?;
+    manager_proxy.reboot(true).await
+}
+
+async fn can_restart_to_firmware() -> bool {
+    let Ok(connection) = Connection::system().await else {
+        return false;
+    };
+    let Ok(manager_proxy) = ManagerProxy::new(&connection).await else {
+        return false;
+    };
+    manager_proxy
+        .can_reboot_to_firmware_setup()
+        .await
+        .is_ok_and(|can| matches!(can, IsSupported::Yes | IsSupported::Challenge))
+}
+
+async fn can_hibernate() -> bool {
+    let Ok(connection) = Connection::system().await else {
+        return false;
+    };
+    let Ok(manager_proxy) = ManagerProxy::new(&connection).await else {
+        return false;
+    };
+    manager_proxy
+        .can_hibernate()
+        .await
+        .is_ok_and(|can| matches!(can, IsSupported::Yes))
+}
+
 async fn shutdown() -> zbus::Result<()> {
     let connection = Connection::system().await?;
     let manager_proxy = ManagerProxy::new(&connection).await?;
     manager_proxy.power_off(true).await
 }
 
+async fn scheduled_shutdown() -> Option<ScheduledShutdown> {
+    let connection = Connection::system().await.ok()?;
+    let manager_proxy = LogindManagerProxy::new(&connection).await.ok()?;
+    match manager_proxy.scheduled_shutdown().await {
+        Ok(property) => ScheduledShutdown::from_property(property),
+        Err(err) => {
+            tracing::error!("Failed to get scheduled shutdown. {err:?}");
+            None
+        }
+    }
+}
+
+async fn cancel_scheduled_shutdown() -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = LogindManagerProxy::new(&connection).await?;
+    if manager_proxy.cancel_scheduled_shutdown().await? {
+        Ok(())
+    } else {
+        Err(zbus::Error::Failure(
+            "no scheduled shutdown to cancel".to_string(),
+        ))
+    }
+}
+
+/// Whether polkit lets us manage other sessions, possibly after authenticating.
+async fn can_manage_sessions(connection: &Connection) -> bool {
+    let Some(name) = connection.unique_name() else {
+        return false;
+    };
+    let Ok(authority) = PolkitAuthorityProxy::new(connection).await else {
+        return false;
+    };
+    let subject = (
+        "system-bus-name",
+        HashMap::from([("name", Value::from(name.as_str()))]),
+    );
+    match authority
+        .check_authorization(
+            &subject,
+            "org.freedesktop.login1.manage",
+            HashMap::new(),
+            0,
+            "",
+        )
+        .await
+    {
+        Ok((authorized, challenge, _)) => authorized || challenge,
+        Err(err) => {
+            tracing::error!("Failed to check session management permission. {err:?}");
+            false
+        }
+    }
+}
+
+async fn other_sessions() -> Vec<OtherSession> {
+    match list_other_sessions().await {
+        Ok(sessions) => sessions,
+        Err(err) => {
+            tracing::error!("Failed to list other sessions. {err:?}");
+            Vec::new()
+        }
+    }
+}
+
+async fn list_other_sessions() -> zbus::Result<Vec<OtherSession>> {
+    let connection = Connection::system().await?;
+    if !can_manage_sessions(&connection).await {
+        return Ok(Vec::new());
+    }
+
+    let manager_proxy = LogindManagerProxy::new(&connection).await?;
+    let own_session = manager_proxy
+        .get_session_by_pid(std::process::id())
+        .await
+        .ok();
+
+    let mut sessions = Vec::new();
+    for (id, _, user, seat, session_path) in manager_proxy.list_sessions().await? {
+        if own_session.as_ref() == Some(&session_path) {
+            continue;
+        }
+
+        let Ok(session) = SessionProxy::builder(&connection)
+            .path(session_path)?
+            .build()
+            .await
+        else {
+            continue;
+        };
+
+        if session.class().await == Ok(SessionClass::User)
+            && matches!(
+                session.state().await,
+                Ok(SessionState::Active | SessionState::Online)
+            )
+        {
+            sessions.push(OtherSession { id, user, seat });
+        }
+    }
+
+    Ok(sessions)
+}
+
+async fn terminate_sessions(ids: Vec<String>) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = LogindManagerProxy::new(&connection).await?;
+    // Stop at the first failure; a denial applies to every session alike.
+    for id in ids {
+        manager_proxy.terminate_session(&id).await?;
+    }
+    Ok(())
+}
+
 async fn suspend() -> zbus::Result<()> {
     let connection = Connection::system().await?;
     let manager_proxy = ManagerProxy::new(&connection).await?;
     manager_proxy.suspend(true).await
 }
 
+async fn hibernate() -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy.hibernate(true).await
+}
+
 async fn lock() -> zbus::Result<()> {
     let connection = Connection::system().await?;
     let manager_proxy = ManagerProxy::new(&connection).await?;
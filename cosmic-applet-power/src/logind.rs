@@ -0,0 +1,37 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use zbus::{proxy, zvariant::OwnedObjectPath};
+
+/// Members of logind's manager that `logind_zbus::manager::ManagerProxy`
+/// doesn't provide in the form the applet needs: terminating sessions has to
+/// allow interactive authorization, and the scheduled shutdown is read raw.
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait LogindManager {
+    /// CancelScheduledShutdown method
+    fn cancel_scheduled_shutdown(&self) -> zbus::Result<bool>;
+
+    /// ListSessions method
+    ///
+    /// Each session as its ID, user ID, user name, seat and object path.
+    fn list_sessions(&self) -> zbus::Result<Vec<(String, u32, String, String, OwnedObjectPath)>>;
+
+    /// GetSessionByPID method
+    #[zbus(name = "GetSessionByPID")]
+    fn get_session_by_pid(&self, pid: u32) -> zbus::Result<OwnedObjectPath>;
+
+    /// TerminateSession method
+    #[zbus(allow_interactive_auth)]
+    fn terminate_session(&self, session_id: &str) -> zbus::Result<()>;
+
+    /// ScheduledShutdown property
+    ///
+    /// The type of the pending shutdown and its time in microseconds since
+    /// the epoch, or an empty type and zero when nothing is scheduled.
+    #[zbus(property)]
+    fn scheduled_shutdown(&self) -> zbus::Result<(String, u64)>;
+}
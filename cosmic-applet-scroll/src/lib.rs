@@ -0,0 +1,34 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::mouse::ScrollDelta;
+
+/// Pixels of smooth scrolling that make one step.
+pub const PIXELS_PER_STEP: f32 = 40.0;
+
+/// Turns scroll events into steps for applets that switch between items when
+/// scrolled over. Mouse wheels report lines, which are passed through, while
+/// touchpads report pixels, which are accumulated until they make whole steps.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScrollSteps {
+    x: f32,
+    y: f32,
+}
+
+impl ScrollSteps {
+    /// Horizontal and vertical steps for an event.
+    pub fn steps(&mut self, delta: ScrollDelta) -> (f32, f32) {
+        match delta {
+            ScrollDelta::Lines { x, y } => (x, y),
+            ScrollDelta::Pixels { x, y } => {
+                self.x += x;
+                self.y += y;
+                let steps_x = (self.x / PIXELS_PER_STEP).trunc();
+                let steps_y = (self.y / PIXELS_PER_STEP).trunc();
+                self.x -= steps_x * PIXELS_PER_STEP;
+                self.y -= steps_y * PIXELS_PER_STEP;
+                (steps_x, steps_y)
+            }
+        }
+    }
+}
@@ -4,17 +4,29 @@
 use cosmic::{
     app,
     applet::cosmic_panel_config::PanelAnchor,
+    cosmic_config::{self, CosmicConfigEntry},
     iced::{
         self,
+        mouse::ScrollDelta,
         platform_specific::shell::commands::popup::{destroy_popup, get_popup},
+        widget::mouse_area,
         window, Limits, Subscription,
     },
-    widget::mouse_area,
     Element, Task,
 };
-use std::collections::BTreeMap;
+use cosmic_applet_scroll::ScrollSteps;
+use std::{collections::BTreeMap, time::Duration};
 
-use crate::{components::status_menu, subscriptions::status_notifier_watcher};
+/// Scroll delta sent for one wheel notch, as in Qt's `angleDelta`.
+const SCROLL_DELTA_PER_STEP: f32 = 120.0;
+/// How long each icon is shown while pulsing items that need attention.
+const ATTENTION_PULSE_INTERVAL: Duration = Duration::from_millis(800);
+
+use crate::{
+    components::status_menu,
+    config::{self, ClickAction, ClickActions, StatusAreaConfig},
+    subscriptions::status_notifier_watcher,
+};
 
 #[derive(Clone, Debug)]
 pub enum Msg {
@@ -23,7 +35,13 @@ pub enum Msg {
     StatusMenu((usize, status_menu::Msg)),
     StatusNotifier(status_notifier_watcher::Event),
     TogglePopup(usize),
+    ToggleOverflowPopup,
     Hovered(usize),
+    Click(usize, ClickAction),
+    Scroll(usize, ScrollDelta),
+    Activated(usize, zbus::Result<()>),
+    ConfigChanged(StatusAreaConfig),
+    AttentionPulse,
 }
 
 #[derive(Default)]
@@ -34,6 +52,13 @@ struct App {
     open_menu: Option<usize>,
     max_menu_id: usize,
     popup: Option<window::Id>,
+    /// Popup with the items that don't fit in the panel.
+    overflow_popup: Option<window::Id>,
+    config: StatusAreaConfig,
+    /// Smooth scrolling not yet forwarded to an item, horizontally and vertically.
+    scroll: ScrollSteps,
+    /// Whether pulsing items show their usual icon rather than the attention icon.
+    attention_pulse_off: bool,
 }
 
 impl App {
@@ -46,10 +71,91 @@ impl App {
         window::Id::unique()
     }
 
+    fn click_actions(&self, menu: &status_menu::State) -> ClickActions {
+        self.config
+            .item_click_actions
+            .get(menu.id())
+            .copied()
+            .unwrap_or(self.config.click_actions)
+    }
+
+    /// Number of icons that fit in the panel, if not all of them do.
+    fn max_icon_count(&self) -> Option<usize> {
+        let bounds = self.core.applet.suggested_bounds.as_ref()?;
+        let max_major_axis_len = match self.core.applet.anchor {
+            PanelAnchor::Top | PanelAnchor::Bottom => bounds.width as u32,
+            PanelAnchor::Left | PanelAnchor::Right => bounds.height as u32,
+        };
+        let button_total_size = self.core.applet.suggested_size(true).0 as u32
+            + self.core.applet.suggested_padding(true) as u32 * 2;
+        let btn_count = max_major_axis_len / button_total_size;
+        if btn_count as usize >= self.menus.len() {
+            None
+        } else {
+            Some((btn_count as usize).max(2))
+        }
+    }
+
+    /// Number of items shown in the panel, the rest are in the overflow popup.
+    fn panel_icon_count(&self) -> usize {
+        // one slot goes to the overflow button
+        self.max_icon_count()
+            .map_or(self.menus.len(), |n| (n - 1).min(self.menus.len()))
+    }
+
+    /// Index of the panel icon for an item, or of the overflow button if the
+    /// item is in the overflow popup.
+    fn icon_index(&self, id: usize) -> usize {
+        let i = self.menus.keys().position(|&i| i == id).unwrap();
+        i.min(self.panel_icon_count())
+    }
+
+    /// Offset of the icon at `i` along the panel.
+    fn icon_offset(&self, i: usize) -> (i32, i32) {
+        if matches!(
+            self.core.applet.anchor,
+            PanelAnchor::Left | PanelAnchor::Right
+        ) {
+            let suggested_size = self.core.applet.suggested_size(false).1
+                + 2 * self.core.applet.suggested_padding(false);
+            (0, i as i32 * suggested_size as i32)
+        } else {
+            let suggested_size = self.core.applet.suggested_size(false).0
+                + 2 * self.core.applet.suggested_padding(false);
+            (i as i32 * suggested_size as i32, 0)
+        }
+    }
+
+    fn item_button<'a>(&'a self, id: usize, menu: &'a status_menu::State) -> Element<'a, Msg> {
+        let actions = self.click_actions(menu);
+        let icon_size = self.core.applet.suggested_size(true).0 as u32;
+        let attention_icon = menu
+            .attention_icon_name()
+            .filter(|_| !(self.config.pulse_attention && self.attention_pulse_off));
+        mouse_area(
+            match (attention_icon, menu.icon_pixmap(icon_size)) {
+                (Some(name), _) => self.core.applet.icon_button(name),
+                // Some items only set a pixmap, or name an icon the theme lacks
+                (None, Some(icon)) if !menu.icon_name_resolves() => self
+                    .core
+                    .applet
+                    .icon_button_from_handle(icon.clone().symbolic(true)),
+                _ => self.core.applet.icon_button(menu.icon_name()),
+            }
+            .on_press_down(Msg::Click(id, actions.left)),
+        )
+        .on_middle_press(Msg::Click(id, actions.middle))
+        .on_right_press(Msg::Click(id, actions.right))
+        .on_scroll(move |delta| Msg::Scroll(id, delta))
+        .on_enter(Msg::Hovered(id))
+        .into()
+    }
+
     fn resize_window(&self) -> app::Task<Msg> {
         let icon_size = self.core.applet.suggested_size(true).0 as u32
             + self.core.applet.suggested_padding(true) as u32 * 2;
-        let n = self.menus.len() as u32;
+        let overflow = self.panel_icon_count() < self.menus.len();
+        let n = self.panel_icon_count() as u32 + u32::from(overflow);
         window::resize(
             self.core.main_window_id().unwrap(),
             iced::Size::new(1.max(icon_size * n) as f32, icon_size as f32),
@@ -61,12 +167,16 @@ impl cosmic::Application for App {
     type Message = Msg;
     type Executor = cosmic::SingleThreadExecutor;
     type Flags = ();
-    const APP_ID: &'static str = "com.system76.CosmicAppletStatusArea";
+    const APP_ID: &'static str = config::APP_ID;
 
     fn init(core: app::Core, _flags: ()) -> (Self, app::Task<Msg>) {
         (
             Self {
                 core,
+                config: cosmic_config::Config::new(config::APP_ID, StatusAreaConfig::VERSION)
+                    .ok()
+                    .and_then(|c| StatusAreaConfig::get_entry(&c).ok())
+                    .unwrap_or_default(),
                 ..Self::default()
             },
             Task::none(),
@@ -92,8 +202,35 @@ impl cosmic::Application for App {
                     self.popup = None;
                     self.open_menu = None;
                 }
+                if self.overflow_popup == Some(surface) {
+                    self.overflow_popup = None;
+                }
                 Task::none()
             }
+            Msg::ToggleOverflowPopup => {
+                if let Some(popup_id) = self.overflow_popup.take() {
+                    return destroy_popup(popup_id);
+                }
+                let mut cmds = Vec::new();
+                if let Some(popup_id) = self.popup.take() {
+                    self.open_menu = None;
+                    cmds.push(destroy_popup(popup_id));
+                }
+                let popup_id = self.next_popup_id();
+                let mut popup_settings = self.core.applet.get_popup_settings(
+                    self.core.main_window_id().unwrap(),
+                    popup_id,
+                    None,
+                    None,
+                    None,
+                );
+                self.overflow_popup = Some(popup_id);
+                let (x, y) = self.icon_offset(self.panel_icon_count());
+                popup_settings.positioner.anchor_rect.x = x;
+                popup_settings.positioner.anchor_rect.y = y;
+                cmds.push(get_popup(popup_settings));
+                app::Task::batch(cmds)
+            }
             Msg::StatusMenu((id, msg)) => match self.menus.get_mut(&id) {
                 Some(state) => state
                     .update(msg)
@@ -107,11 +244,10 @@ impl cosmic::Application for App {
                 }
                 status_notifier_watcher::Event::Registered(name) => {
                     let (state, cmd) = status_menu::State::new(name);
-                    if let Some((id, m)) = self
-                        .menus
-                        .iter_mut()
-                        .find(|(_, prev_state)| prev_state.name() == state.name())
-                    {
+                    // replace a stale item left behind by an application that registered again
+                    if let Some((id, m)) = self.menus.iter_mut().find(|(_, prev_state)| {
+                        prev_state.name() == state.name() || state.is_duplicate_of(prev_state)
+                    }) {
                         *m = state;
                         let id = *id;
                         return cmd.map(move |msg| app::message::app(Msg::StatusMenu((id, msg))));
@@ -135,6 +271,14 @@ impl cosmic::Application for App {
                             }
                         }
                     }
+                    if self.panel_icon_count() == self.menus.len() {
+                        if let Some(popup_id) = self.overflow_popup.take() {
+                            return app::Task::batch([
+                                destroy_popup(popup_id),
+                                self.resize_window(),
+                            ]);
+                        }
+                    }
                     self.resize_window()
                 }
                 status_notifier_watcher::Event::Error(err) => {
@@ -155,6 +299,10 @@ impl cosmic::Application for App {
                     if let Some(popup_id) = self.popup.take() {
                         cmds.push(destroy_popup(popup_id));
                     }
+                    // the menu takes the overflow button's place
+                    if let Some(popup_id) = self.overflow_popup.take() {
+                        cmds.push(destroy_popup(popup_id));
+                    }
                     let popup_id = self.next_popup_id();
                     let mut popup_settings = self.core.applet.get_popup_settings(
                         self.core.main_window_id().unwrap(),
@@ -164,19 +312,9 @@ impl cosmic::Application for App {
                         None,
                     );
                     self.popup = Some(popup_id);
-                    let i = self.menus.keys().position(|&i| i == id).unwrap();
-                    if matches!(
-                        self.core.applet.anchor,
-                        PanelAnchor::Left | PanelAnchor::Right
-                    ) {
-                        let suggested_size = self.core.applet.suggested_size(false).1
-                            + 2 * self.core.applet.suggested_padding(false);
-                        popup_settings.positioner.anchor_rect.y = i as i32 * suggested_size as i32;
-                    } else {
-                        let suggested_size = self.core.applet.suggested_size(false).0
-                            + 2 * self.core.applet.suggested_padding(false);
-                        popup_settings.positioner.anchor_rect.x = i as i32 * suggested_size as i32;
-                    }
+                    let (x, y) = self.icon_offset(self.icon_index(id));
+                    popup_settings.positioner.anchor_rect.x = x;
+                    popup_settings.positioner.anchor_rect.y = y;
                     cmds.push(get_popup(popup_settings));
                     return app::Task::batch(cmds);
                 } else if let Some(popup_id) = self.popup {
@@ -186,6 +324,66 @@ impl cosmic::Application for App {
                 }
                 Task::none()
             }
+            Msg::Click(id, action) => {
+                let Some(menu) = self.menus.get(&id) else {
+                    return Task::none();
+                };
+                match action {
+                    ClickAction::Activate if !menu.item_is_menu() => {
+                        iced::Task::perform(menu.activate(false), move |result| {
+                            app::message::app(Msg::Activated(id, result))
+                        })
+                    }
+                    ClickAction::Activate | ClickAction::ContextMenu => {
+                        self.update(Msg::TogglePopup(id))
+                    }
+                    ClickAction::SecondaryActivate => {
+                        let activate = menu.activate(true);
+                        tokio::spawn(async move {
+                            if let Err(err) = activate.await {
+                                tracing::error!("Failed to activate tray item: {err}");
+                            }
+                        });
+                        Task::none()
+                    }
+                    ClickAction::Nothing => Task::none(),
+                }
+            }
+            Msg::Scroll(id, delta) => {
+                let Some(menu) = self.menus.get(&id) else {
+                    return Task::none();
+                };
+                let (x, y) = self.scroll.steps(delta);
+                let horizontal = (x * SCROLL_DELTA_PER_STEP).round() as i32;
+                let vertical = (y * SCROLL_DELTA_PER_STEP).round() as i32;
+                if horizontal == 0 && vertical == 0 {
+                    return Task::none();
+                }
+                let scroll = menu.scroll(horizontal, vertical);
+                tokio::spawn(async move {
+                    // Most items don't implement Scroll
+                    if let Err(err) = scroll.await {
+                        tracing::debug!("Failed to scroll tray item: {err}");
+                    }
+                });
+                Task::none()
+            }
+            Msg::Activated(id, result) => {
+                // Items without a main action only have their menu to show.
+                if let Err(err) = result {
+                    tracing::debug!("Tray item can't be activated, opening its menu: {err}");
+                    return self.update(Msg::TogglePopup(id));
+                }
+                Task::none()
+            }
+            Msg::ConfigChanged(config) => {
+                self.config = config;
+                Task::none()
+            }
+            Msg::AttentionPulse => {
+                self.attention_pulse_off = !self.attention_pulse_off;
+                Task::none()
+            }
             Msg::Hovered(id) => {
                 let mut cmds = Vec::new();
                 if let Some(old_id) = self.open_menu.take() {
@@ -210,19 +408,9 @@ impl cosmic::Application for App {
                     None,
                 );
                 self.popup = Some(popup_id);
-                let i = self.menus.keys().position(|&i| i == id).unwrap();
-                if matches!(
-                    self.core.applet.anchor,
-                    PanelAnchor::Left | PanelAnchor::Right
-                ) {
-                    let suggested_size = self.core.applet.suggested_size(false).1
-                        + 2 * self.core.applet.suggested_padding(false);
-                    popup_settings.positioner.anchor_rect.y = i as i32 * suggested_size as i32;
-                } else {
-                    let suggested_size = self.core.applet.suggested_size(false).0
-                        + 2 * self.core.applet.suggested_padding(false);
-                    popup_settings.positioner.anchor_rect.x = i as i32 * suggested_size as i32;
-                }
+                let (x, y) = self.icon_offset(self.icon_index(id));
+                popup_settings.positioner.anchor_rect.x = x;
+                popup_settings.positioner.anchor_rect.y = y;
                 cmds.push(get_popup(popup_settings));
                 app::Task::batch(cmds)
             }
@@ -233,6 +421,22 @@ impl cosmic::Application for App {
         let mut subscriptions = Vec::new();
 
         subscriptions.push(status_notifier_watcher::subscription().map(Msg::StatusNotifier));
+        subscriptions.push(self.core.watch_config(config::APP_ID).map(|u| {
+            for err in u.errors {
+                tracing::error!(?err, "Error watching config");
+            }
+            Msg::ConfigChanged(u.config)
+        }));
+
+        if self.config.pulse_attention
+            && self
+                .menus
+                .values()
+                .any(|menu| menu.attention_icon_name().is_some())
+        {
+            subscriptions
+                .push(iced::time::every(ATTENTION_PULSE_INTERVAL).map(|_| Msg::AttentionPulse));
+        }
 
         for (id, menu) in self.menus.iter() {
             subscriptions.push(menu.subscription().with(*id).map(Msg::StatusMenu));
@@ -242,20 +446,27 @@ impl cosmic::Application for App {
     }
 
     fn view(&self) -> cosmic::Element<'_, Msg> {
-        let children = self.menus.iter().map(|(id, menu)| {
-            mouse_area(
-                match menu.icon_pixmap() {
-                    Some(icon) if menu.icon_name() == "" => self
-                        .core
-                        .applet
-                        .icon_button_from_handle(icon.clone().symbolic(true)),
-                    _ => self.core.applet.icon_button(menu.icon_name()),
-                }
-                .on_press_down(Msg::TogglePopup(*id)),
-            )
-            .on_enter(Msg::Hovered(*id))
-            .into()
-        });
+        let panel_icon_count = self.panel_icon_count();
+        let overflow_btn: Option<Element<'_, Msg>> =
+            (panel_icon_count < self.menus.len()).then(|| {
+                let icon = match self.core.applet.anchor {
+                    PanelAnchor::Bottom => "go-up-symbolic",
+                    PanelAnchor::Left => "go-next-symbolic",
+                    PanelAnchor::Right => "go-previous-symbolic",
+                    PanelAnchor::Top => "go-down-symbolic",
+                };
+                self.core
+                    .applet
+                    .icon_button(icon)
+                    .on_press_down(Msg::ToggleOverflowPopup)
+                    .into()
+            });
+        let children = self
+            .menus
+            .iter()
+            .take(panel_icon_count)
+            .map(|(id, menu)| self.item_button(*id, menu))
+            .chain(overflow_btn);
         self.core
             .applet
             .autosize_window(
@@ -271,7 +482,29 @@ impl cosmic::Application for App {
             .into()
     }
 
-    fn view_window(&self, _surface: window::Id) -> cosmic::Element<'_, Msg> {
+    fn view_window(&self, surface: window::Id) -> cosmic::Element<'_, Msg> {
+        if self.overflow_popup == Some(surface) {
+            let children = self
+                .menus
+                .iter()
+                .skip(self.panel_icon_count())
+                .map(|(id, menu)| self.item_button(*id, menu));
+            return self
+                .core
+                .applet
+                .popup_container(
+                    if matches!(
+                        self.core.applet.anchor,
+                        PanelAnchor::Left | PanelAnchor::Right
+                    ) {
+                        Element::from(iced::widget::column(children))
+                    } else {
+                        iced::widget::row(children).into()
+                    },
+                )
+                .into();
+        }
+
         match self.open_menu {
             Some(id) => match self.menus.get(&id) {
                 Some(menu) => self
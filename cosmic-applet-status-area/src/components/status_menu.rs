@@ -3,11 +3,12 @@
 
 use cosmic::{applet::menu_button, iced, widget::icon};
 
-use crate::subscriptions::status_notifier_item::{Layout, StatusNotifierItem};
+use crate::subscriptions::status_notifier_item::{Layout, Status, StatusNotifierItem};
 
 #[derive(Clone, Debug)]
 pub enum Msg {
     Layout(Result<Layout, String>),
+    Status((Status, String)),
     Click(i32, bool),
 }
 
@@ -15,12 +16,16 @@ pub struct State {
     item: StatusNotifierItem,
     layout: Option<Layout>,
     expanded: Option<i32>,
+    status: Status,
+    attention_icon_name: String,
 }
 
 impl State {
     pub fn new(item: StatusNotifierItem) -> (Self, iced::Task<Msg>) {
         (
             Self {
+                status: item.status(),
+                attention_icon_name: item.attention_icon_name().to_string(),
                 item,
                 layout: None,
                 expanded: None,
@@ -40,6 +45,11 @@ impl State {
                 }
                 iced::Task::none()
             }
+            Msg::Status((status, attention_icon_name)) => {
+                self.status = status;
+                self.attention_icon_name = attention_icon_name;
+                iced::Task::none()
+            }
             Msg::Click(id, is_submenu) => {
                 let menu_proxy = self.item.menu_proxy().clone();
                 tokio::spawn(async move {
@@ -63,12 +73,64 @@ impl State {
         self.item.name()
     }
 
+    pub fn id(&self) -> &str {
+        self.item.id()
+    }
+
     pub fn icon_name(&self) -> &str {
         self.item.icon_name()
     }
 
-    pub fn icon_pixmap(&self) -> Option<&icon::Handle> {
-        self.item.icon_pixmap()
+    pub fn item_is_menu(&self) -> bool {
+        self.item.item_is_menu()
+    }
+
+    /// Asks the application to perform its main or secondary action.
+    pub fn activate(&self, secondary: bool) -> impl std::future::Future<Output = zbus::Result<()>> {
+        let item_proxy = self.item.item_proxy().clone();
+        async move {
+            if secondary {
+                item_proxy.secondary_activate(0, 0).await
+            } else {
+                item_proxy.activate(0, 0).await
+            }
+        }
+    }
+
+    /// Forwards a scroll of `horizontal` and `vertical` steps to the application.
+    pub fn scroll(
+        &self,
+        horizontal: i32,
+        vertical: i32,
+    ) -> impl std::future::Future<Output = zbus::Result<()>> {
+        let item_proxy = self.item.item_proxy().clone();
+        async move {
+            if horizontal != 0 {
+                item_proxy.scroll(horizontal, "horizontal").await?;
+            }
+            if vertical != 0 {
+                item_proxy.scroll(vertical, "vertical").await?;
+            }
+            Ok(())
+        }
+    }
+
+    pub fn is_duplicate_of(&self, other: &Self) -> bool {
+        self.item.is_duplicate_of(&other.item)
+    }
+
+    /// The icon to show instead of the usual one while the item needs attention.
+    pub fn attention_icon_name(&self) -> Option<&str> {
+        (self.status == Status::NeedsAttention && !self.attention_icon_name.is_empty())
+            .then_some(self.attention_icon_name.as_str())
+    }
+
+    pub fn icon_name_resolves(&self) -> bool {
+        self.item.icon_name_resolves()
+    }
+
+    pub fn icon_pixmap(&self, size: u32) -> Option<&icon::Handle> {
+        self.item.icon_pixmap(size)
     }
 
     pub fn popup_view(&self) -> cosmic::Element<Msg> {
@@ -80,7 +142,10 @@ impl State {
     }
 
     pub fn subscription(&self) -> iced::Subscription<Msg> {
-        self.item.layout_subscription().map(Msg::Layout)
+        iced::Subscription::batch([
+            self.item.layout_subscription().map(Msg::Layout),
+            self.item.status_subscription().map(Msg::Status),
+        ])
     }
 
     pub fn opened(&self) {
@@ -0,0 +1,62 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::BTreeMap;
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
+pub const APP_ID: &str = "com.system76.CosmicAppletStatusArea";
+
+/// What clicking a tray item does.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ClickAction {
+    /// Ask the application to perform its main action, usually showing its
+    /// window. Items that only provide a menu open it instead.
+    Activate,
+    /// Ask the application to perform its secondary action.
+    SecondaryActivate,
+    /// Open the item's menu.
+    ContextMenu,
+    Nothing,
+}
+
+/// Actions for each mouse button.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClickActions {
+    pub left: ClickAction,
+    pub middle: ClickAction,
+    pub right: ClickAction,
+}
+
+impl Default for ClickActions {
+    fn default() -> Self {
+        Self {
+            left: ClickAction::Activate,
+            middle: ClickAction::SecondaryActivate,
+            right: ClickAction::ContextMenu,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct StatusAreaConfig {
+    /// Actions of the mouse buttons on tray items.
+    pub click_actions: ClickActions,
+    /// Actions for specific items by their StatusNotifierItem id, for
+    /// applications that expect different clicks than most.
+    pub item_click_actions: BTreeMap<String, ClickActions>,
+    /// Alternate between the attention and usual icons of items that need attention.
+    pub pulse_attention: bool,
+}
+
+impl Default for StatusAreaConfig {
+    fn default() -> Self {
+        Self {
+            click_actions: ClickActions::default(),
+            item_click_actions: BTreeMap::new(),
+            pulse_attention: true,
+        }
+    }
+}
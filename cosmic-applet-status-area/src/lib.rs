@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 mod components;
+mod config;
 mod subscriptions;
 
 pub fn run() -> cosmic::iced::Result {
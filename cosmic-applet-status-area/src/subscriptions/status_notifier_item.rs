@@ -11,13 +11,39 @@ use zbus::zvariant::{self, OwnedValue};
 #[derive(Clone, Debug)]
 pub struct StatusNotifierItem {
     name: String,
+    id: String,
+    title: String,
     icon_name: String,
-    // TODO Handle icon with multiple sizes?
-    icon_pixmap: Option<icon::Handle>,
-    _item_proxy: StatusNotifierItemProxy<'static>,
+    /// Whether `icon_name` is found in the icon theme.
+    icon_name_resolves: bool,
+    /// The `IconPixmap` sizes with their width, smallest first.
+    icon_pixmaps: Vec<(u32, icon::Handle)>,
+    status: Status,
+    attention_icon_name: String,
+    item_is_menu: bool,
+    item_proxy: StatusNotifierItemProxy<'static>,
     menu_proxy: DBusMenuProxy<'static>,
 }
 
+/// Whether the item is idle, active or asking for the user's attention.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Status {
+    Passive,
+    #[default]
+    Active,
+    NeedsAttention,
+}
+
+impl Status {
+    fn from_property(status: &str) -> Self {
+        match status {
+            "Passive" => Self::Passive,
+            "NeedsAttention" => Self::NeedsAttention,
+            _ => Self::Active,
+        }
+    }
+}
+
 #[derive(Clone, Debug, zvariant::Value)]
 pub struct Icon {
     width: i32,
@@ -25,6 +51,23 @@ pub struct Icon {
     bytes: Vec<u8>,
 }
 
+impl Icon {
+    /// Converts the pixmap to a handle, skipping pixmaps whose data doesn't
+    /// match their size.
+    fn into_handle(mut self) -> Option<(u32, icon::Handle)> {
+        let width = u32::try_from(self.width).ok().filter(|w| *w > 0)?;
+        let height = u32::try_from(self.height).ok().filter(|h| *h > 0)?;
+        if self.bytes.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        // Pixels are ARGB32 in network byte order, so convert to RGBA
+        for pixel in self.bytes.chunks_exact_mut(4) {
+            pixel.rotate_left(1);
+        }
+        Some((width, icon::from_raster_pixels(width, height, self.bytes)))
+    }
+}
+
 impl StatusNotifierItem {
     pub async fn new(connection: &zbus::Connection, name: String) -> zbus::Result<Self> {
         let (dest, path) = if let Some(idx) = name.find('/') {
@@ -33,26 +76,31 @@ impl StatusNotifierItem {
             (name.as_str(), "/StatusNotifierItem")
         };
 
+        // Items announce changes with their own signals instead of PropertiesChanged,
+        // so cached properties would never be updated
         let item_proxy = StatusNotifierItemProxy::builder(connection)
             .destination(dest.to_string())?
             .path(path.to_string())?
+            .cache_properties(zbus::proxy::CacheProperties::No)
             .build()
             .await?;
 
+        let id = item_proxy.id().await.unwrap_or_default();
+        let title = item_proxy.title().await.unwrap_or_default();
         let icon_name = item_proxy.icon_name().await.unwrap_or_default();
-        let icon_pixmap = item_proxy
+        let icon_name_resolves =
+            !icon_name.is_empty() && icon::from_name(icon_name.as_str()).path().is_some();
+        let mut icon_pixmaps: Vec<_> = item_proxy
             .icon_pixmap()
             .await
             .unwrap_or_default()
             .into_iter()
-            .max_by_key(|i| (i.width, i.height))
-            .map(|mut i| {
-                // Convert ARGB to RGBA
-                for pixel in i.bytes.chunks_exact_mut(4) {
-                    pixel.rotate_left(1);
-                }
-                icon::from_raster_pixels(i.width as u32, i.height as u32, i.bytes)
-            });
+            .filter_map(Icon::into_handle)
+            .collect();
+        icon_pixmaps.sort_by_key(|(width, _)| *width);
+
+        let (status, attention_icon_name) = get_status(item_proxy.clone()).await;
+        let item_is_menu = item_proxy.item_is_menu().await.unwrap_or_default();
 
         let menu_path = item_proxy.menu().await?;
         let menu_proxy = DBusMenuProxy::builder(connection)
@@ -63,9 +111,15 @@ impl StatusNotifierItem {
 
         Ok(Self {
             name,
+            id,
+            title,
             icon_name,
-            icon_pixmap,
-            _item_proxy: item_proxy,
+            icon_name_resolves,
+            icon_pixmaps,
+            status,
+            attention_icon_name,
+            item_is_menu,
+            item_proxy,
             menu_proxy,
         })
     }
@@ -74,12 +128,62 @@ impl StatusNotifierItem {
         &self.name
     }
 
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     pub fn icon_name(&self) -> &str {
         &self.icon_name
     }
 
-    pub fn icon_pixmap(&self) -> Option<&icon::Handle> {
-        self.icon_pixmap.as_ref()
+    /// Whether the item only provides a menu and doesn't support `Activate`.
+    pub fn item_is_menu(&self) -> bool {
+        self.item_is_menu
+    }
+
+    /// Whether `other` is the same application item registered again, e.g. by an
+    /// application that re-registers without unregistering its previous item.
+    ///
+    /// Only items with identical, non-empty ids and titles are considered the same.
+    pub fn is_duplicate_of(&self, other: &Self) -> bool {
+        !self.id.is_empty() && self.id == other.id && self.title == other.title
+    }
+
+    /// Whether the item has a themed icon that can be shown instead of a pixmap.
+    pub fn icon_name_resolves(&self) -> bool {
+        self.icon_name_resolves
+    }
+
+    /// The smallest pixmap at least `size` pixels wide, or the largest one.
+    pub fn icon_pixmap(&self, size: u32) -> Option<&icon::Handle> {
+        self.icon_pixmaps
+            .iter()
+            .find(|(width, _)| *width >= size)
+            .or(self.icon_pixmaps.last())
+            .map(|(_, handle)| handle)
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn attention_icon_name(&self) -> &str {
+        &self.attention_icon_name
+    }
+
+    /// The status and attention icon name, whenever either changes.
+    pub fn status_subscription(&self) -> iced::Subscription<(Status, String)> {
+        let item_proxy = self.item_proxy.clone();
+        Subscription::run_with_id(
+            format!("status-notifier-item-status-{}", &self.name),
+            async move {
+                let new_status = item_proxy.receive_new_status().await.unwrap();
+                let new_attention_icon = item_proxy.receive_new_attention_icon().await.unwrap();
+                futures::stream::select(new_status.map(|_| ()), new_attention_icon.map(|_| ()))
+                    .then(move |()| get_status(item_proxy.clone()))
+            }
+            .flatten_stream(),
+        )
     }
 
     // TODO: Only fetch changed part of layout, if that's any faster
@@ -97,11 +201,21 @@ impl StatusNotifierItem {
         )
     }
 
+    pub fn item_proxy(&self) -> &StatusNotifierItemProxy<'static> {
+        &self.item_proxy
+    }
+
     pub fn menu_proxy(&self) -> &DBusMenuProxy<'static> {
         &self.menu_proxy
     }
 }
 
+async fn get_status(item_proxy: StatusNotifierItemProxy<'static>) -> (Status, String) {
+    let status = item_proxy.status().await.unwrap_or_default();
+    let attention_icon_name = item_proxy.attention_icon_name().await.unwrap_or_default();
+    (Status::from_property(&status), attention_icon_name)
+}
+
 async fn get_layout(menu_proxy: DBusMenuProxy<'static>) -> Result<Layout, String> {
     match menu_proxy.get_layout(0, -1, &[]).await {
         Ok((_, layout)) => Ok(layout),
@@ -111,15 +225,43 @@ async fn get_layout(menu_proxy: DBusMenuProxy<'static>) -> Result<Layout, String
 
 #[zbus::proxy(interface = "org.kde.StatusNotifierItem")]
 trait StatusNotifierItem {
+    #[zbus(property)]
+    fn id(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn title(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn status(&self) -> zbus::Result<String>;
+
     #[zbus(property)]
     fn icon_name(&self) -> zbus::Result<String>;
 
+    #[zbus(property)]
+    fn attention_icon_name(&self) -> zbus::Result<String>;
+
     // https://www.freedesktop.org/wiki/Specifications/StatusNotifierItem/Icons
     #[zbus(property)]
     fn icon_pixmap(&self) -> zbus::Result<Vec<Icon>>;
 
+    #[zbus(property)]
+    fn item_is_menu(&self) -> zbus::Result<bool>;
+
     #[zbus(property)]
     fn menu(&self) -> zbus::Result<zvariant::OwnedObjectPath>;
+
+    fn activate(&self, x: i32, y: i32) -> zbus::Result<()>;
+
+    fn secondary_activate(&self, x: i32, y: i32) -> zbus::Result<()>;
+
+    /// `orientation` is either `"horizontal"` or `"vertical"`.
+    fn scroll(&self, delta: i32, orientation: &str) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn new_status(&self, status: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn new_attention_icon(&self) -> zbus::Result<()>;
 }
 
 #[derive(Clone, Debug)]
@@ -1 +1,2 @@
-datetime-settings = Date, Time and Calendar Settings...
\ No newline at end of file
+datetime-settings = Date, Time and Calendar Settings...
+all-day = All day
@@ -11,6 +11,25 @@ pub struct TimeAppletConfig {
     pub first_day_of_week: u8,
     pub show_date_in_top_panel: bool,
     pub show_weekday: bool,
+    /// Desktop id of the calendar application opened with a middle click.
+    /// Falls back to the date and time settings page when unset.
+    pub calendar_app: Option<String>,
+    /// Open the calendar application with a left click and the popup with a middle click.
+    pub left_click_opens_calendar: bool,
+    /// Locale used to format dates and times instead of the system one, e.g. `en_GB`.
+    pub locale: Option<String>,
+    /// Show a map of where it is day and night in the popup.
+    pub show_world_map: bool,
+    /// Cities marked on the map, as a name with a latitude and longitude in degrees.
+    pub world_map_cities: Vec<(String, i16, i16)>,
+    /// Time zones of the clocks listed in the popup, as IANA names like `Europe/Paris`.
+    pub world_clocks: Vec<String>,
+    /// Show the number of each week in the calendar.
+    pub show_week_numbers: bool,
+    /// `strftime` format of the panel text, e.g. `%a %H:%M`, used instead of
+    /// the locale's format and `military_time` when set. Seconds still only
+    /// update every second while `show_seconds` is enabled and the popup is open.
+    pub panel_format: Option<String>,
 }
 
 impl Default for TimeAppletConfig {
@@ -21,6 +40,14 @@ impl Default for TimeAppletConfig {
             first_day_of_week: 6,
             show_date_in_top_panel: true,
             show_weekday: false,
+            calendar_app: None,
+            left_click_opens_calendar: false,
+            locale: None,
+            show_world_map: false,
+            world_map_cities: Vec::new(),
+            world_clocks: Vec::new(),
+            show_week_numbers: false,
+            panel_format: None,
         }
     }
 }
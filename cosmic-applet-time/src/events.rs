@@ -0,0 +1,29 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{future::Future, pin::Pin};
+
+use chrono::{NaiveDate, NaiveTime};
+
+/// An event shown below the calendar for the selected day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub summary: String,
+    /// Start and end of the event, or `None` if it lasts all day.
+    pub time: Option<(NaiveTime, NaiveTime)>,
+}
+
+/// Looks up the events of a day. Lookups are asynchronous so that a provider
+/// can ask a calendar service without blocking the popup.
+pub trait EventsProvider {
+    fn events(&self, date: NaiveDate) -> Pin<Box<dyn Future<Output = Vec<Event>> + Send>>;
+}
+
+/// Provider used until there's a calendar backend, every day is empty.
+pub struct NoEvents;
+
+impl EventsProvider for NoEvents {
+    fn events(&self, _date: NaiveDate) -> Pin<Box<dyn Future<Output = Vec<Event>> + Send>> {
+        Box::pin(std::future::ready(Vec::new()))
+    }
+}
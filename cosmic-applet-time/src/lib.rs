@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 mod config;
+mod events;
 mod localize;
 mod time;
 mod window;
+mod world_map;
 
 use window::Window;
 
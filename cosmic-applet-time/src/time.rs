@@ -9,3 +9,17 @@ pub fn get_calender_first(year: i32, month: u32, from_weekday: Weekday) -> Naive
     let num_days = (date.weekday() as u32 + 7 - from_weekday as u32) % 7; // chrono::Weekday.num_days_from
     date.checked_sub_days(Days::new(num_days as u64)).unwrap()
 }
+
+/// Gets the number of the week starting on the given date. Weeks starting on
+/// Monday are numbered as in ISO 8601, otherwise week 1 is the one with January 1st.
+pub fn get_week_number(first_day: NaiveDate) -> u32 {
+    if first_day.weekday() == Weekday::Mon {
+        return first_day.iso_week().week();
+    }
+
+    // a week belongs to the year of its last day
+    let last_day = first_day.checked_add_days(Days::new(6)).unwrap();
+    let new_year = NaiveDate::from_ymd_opt(last_day.year(), 1, 1).unwrap();
+    let offset = (new_year.weekday() as u32 + 7 - first_day.weekday() as u32) % 7;
+    (last_day.ordinal0() + offset) / 7 + 1
+}
@@ -3,7 +3,10 @@
 
 use std::str::FromStr;
 
-use chrono::{Datelike, Timelike};
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike, Timelike,
+};
 use cosmic::iced_futures::stream;
 use cosmic::widget::Id;
 use cosmic::{
@@ -17,14 +20,16 @@ use cosmic::{
         widget::{column, row, vertical_space},
         window, Alignment, Length, Rectangle, Subscription,
     },
+    iced_runtime::core::layout::Limits,
     iced_widget::{horizontal_rule, Column},
     theme,
     widget::{
-        autosize, button, container, divider, grid, horizontal_space, icon, rectangle_tracker::*,
-        text, Button, Grid, Space,
+        autosize, button, container, divider, grid, horizontal_space, icon, mouse_area,
+        rectangle_tracker::*, text, Button, Grid, Space,
     },
     Element, Task,
 };
+use freedesktop_desktop_entry::DesktopEntry;
 use once_cell::sync::Lazy;
 use timedate_zbus::TimeDateProxy;
 use tokio::{sync::watch, time};
@@ -41,7 +46,13 @@ use icu::{
     locid::Locale,
 };
 
-use crate::{config::TimeAppletConfig, fl, time::get_calender_first};
+use crate::{
+    config::TimeAppletConfig,
+    events::{Event, EventsProvider, NoEvents},
+    fl,
+    time::{get_calender_first, get_week_number},
+    world_map,
+};
 use cosmic::applet::token::subscription::{
     activation_token_subscription, TokenRequest, TokenUpdate,
 };
@@ -63,6 +74,17 @@ pub struct Window {
     config: TimeAppletConfig,
     show_seconds_tx: watch::Sender<bool>,
     locale: Locale,
+    /// Locale from the environment, used unless the config overrides it.
+    system_locale: Locale,
+    /// Day and night map with the redraw interval it was drawn in.
+    world_map: Option<(i64, icon::Handle)>,
+    /// Valid time zones from the `world_clocks` config.
+    world_clocks: Vec<chrono_tz::Tz>,
+    /// The `panel_format` from the config, if it's a valid format.
+    panel_format: Option<String>,
+    events_provider: Box<dyn EventsProvider>,
+    /// Events of `date_selected`.
+    events: Vec<Event>,
 }
 
 #[derive(Debug, Clone)]
@@ -71,10 +93,14 @@ pub enum Message {
     CloseRequested(window::Id),
     Tick,
     Rectangle(RectangleUpdate<u32>),
-    SelectDay(u32),
+    DaySelected(chrono::NaiveDate),
+    Events(chrono::NaiveDate, Vec<Event>),
     PreviousMonth,
     NextMonth,
     OpenDateTimeSettings,
+    OpenCalendar,
+    /// Command line of the calendar app, once its desktop entry was read.
+    LaunchCalendar(Option<String>),
     Token(TokenUpdate),
     ConfigChanged(TimeAppletConfig),
     TimezoneUpdate(String),
@@ -82,6 +108,11 @@ pub enum Message {
 
 impl Window {
     fn format<D: Datelike>(&self, bag: Bag, date: &D) -> String {
+        self.format_at(bag, date, &self.now)
+    }
+
+    /// Formats a date with the time of day taken from `time`.
+    fn format_at<D: Datelike, T: Timelike>(&self, bag: Bag, date: &D, time: &T) -> String {
         let options = DateTimeFormatterOptions::Components(bag);
 
         let dtf =
@@ -91,11 +122,10 @@ impl Window {
             date.year(),
             date.month() as u8,
             date.day() as u8,
-            // hack cause we know that we will only use "now"
-            // when we need hours (NaiveDate don't support this functions)
-            self.now.hour() as u8,
-            self.now.minute() as u8,
-            self.now.second() as u8,
+            // NaiveDate doesn't have a time of day, so it's passed separately
+            time.hour() as u8,
+            time.minute() as u8,
+            time.second() as u8,
         )
         .unwrap()
         .to_iso()
@@ -105,6 +135,119 @@ impl Window {
             .expect("can't format value")
             .to_string()
     }
+
+    /// Seconds are only shown while the popup is open so that the panel
+    /// doesn't wake up every second while nobody is looking at it.
+    fn show_seconds(&self) -> bool {
+        self.config.show_seconds && self.popup.is_some()
+    }
+
+    /// Redraws the day and night map when it's shown and out of date.
+    fn update_world_map(&mut self) {
+        if !self.config.show_world_map || self.popup.is_none() {
+            self.world_map = None;
+            return;
+        }
+
+        let interval = self.now.timestamp() / world_map::REDRAW_INTERVAL;
+        if matches!(self.world_map, Some((drawn, _)) if drawn == interval) {
+            return;
+        }
+
+        let cities: Vec<_> = self
+            .config
+            .world_map_cities
+            .iter()
+            .map(|(_, lat, lon)| (*lat, *lon))
+            .collect();
+        let pixels = world_map::render(self.now.with_timezone(&chrono::Utc), &cities);
+        self.world_map = Some((
+            interval,
+            icon::from_raster_pixels(world_map::WIDTH, world_map::HEIGHT, pixels),
+        ));
+    }
+
+    fn update_world_clocks(&mut self) {
+        self.world_clocks = self
+            .config
+            .world_clocks
+            .iter()
+            .filter_map(|name| match name.parse::<chrono_tz::Tz>() {
+                Ok(tz) => Some(tz),
+                Err(e) => {
+                    tracing::warn!("skipping world clock with invalid time zone {name}: {e}");
+                    None
+                }
+            })
+            .collect();
+    }
+
+    fn update_panel_format(&mut self) {
+        self.panel_format = self.config.panel_format.clone().filter(|format| {
+            let valid = !StrftimeItems::new(format).any(|item| matches!(item, Item::Error));
+            if !valid {
+                tracing::error!("invalid panel format {format:?}, using the default format");
+            }
+            valid
+        });
+    }
+
+    /// Selects a day in the calendar and looks up its events.
+    fn select_day(&mut self, date: chrono::NaiveDate) -> Task<app::Message<Message>> {
+        self.date_selected = date;
+        self.events.clear();
+        Task::perform(self.events_provider.events(date), move |events| {
+            cosmic::app::Message::App(Message::Events(date, events))
+        })
+    }
+
+    fn time_bag(&self) -> Bag {
+        let mut time_bag = Bag::empty();
+        time_bag.hour = Some(components::Numeric::Numeric);
+        time_bag.minute = Some(components::Numeric::Numeric);
+        let hour_cycle = if self.config.military_time {
+            preferences::HourCycle::H23
+        } else {
+            preferences::HourCycle::H12
+        };
+        time_bag.preferences = Some(preferences::Bag::from_hour_cycle(hour_cycle));
+        time_bag
+    }
+
+    fn sync_tick_rate(&self) {
+        let seconds = self.show_seconds();
+        // Don't interrupt the tick subscription unless necessary
+        self.show_seconds_tx.send_if_modified(|show_seconds| {
+            if *show_seconds == seconds {
+                false
+            } else {
+                *show_seconds = seconds;
+                true
+            }
+        });
+    }
+
+    fn update_locale(&mut self) {
+        self.locale = match self.config.locale.as_deref().map(parse_locale) {
+            Some(Ok(locale)) => locale,
+            Some(Err(e)) => {
+                tracing::warn!("invalid locale override, using the system locale: {e}");
+                self.system_locale.clone()
+            }
+            None => self.system_locale.clone(),
+        };
+    }
+
+    fn request_token(&self, exec: String) {
+        if let Some(tx) = self.token_tx.as_ref() {
+            let _ = tx.send(TokenRequest {
+                app_id: Self::APP_ID.to_string(),
+                exec,
+            });
+        } else {
+            tracing::error!("Wayland tx is None");
+        };
+    }
 }
 
 impl cosmic::Application for Window {
@@ -119,13 +262,7 @@ impl cosmic::Application for Window {
     ) -> (Self, cosmic::iced::Task<app::Message<Self::Message>>) {
         fn get_local() -> Result<Locale, Box<dyn std::error::Error>> {
             let locale = std::env::var("LC_TIME").or_else(|_| std::env::var("LANG"))?;
-            let locale = locale
-                .split('.')
-                .next()
-                .ok_or(format!("Can't split the locale {locale}"))?;
-
-            let locale = Locale::from_str(locale).map_err(|e| format!("{e:?}"))?;
-            Ok(locale)
+            Ok(parse_locale(&locale)?)
         }
 
         let locale = match get_local() {
@@ -142,8 +279,8 @@ impl cosmic::Application for Window {
         // timezone is ever externally changed
         let now = chrono::Local::now().fixed_offset();
 
-        // Synch `show_seconds` from the config within the time subscription
-        let (show_seconds_tx, _) = watch::channel(true);
+        // Synch `show_seconds` from the config and popup state within the time subscription
+        let (show_seconds_tx, _) = watch::channel(false);
 
         (
             Self {
@@ -157,7 +294,13 @@ impl cosmic::Application for Window {
                 token_tx: None,
                 config: TimeAppletConfig::default(),
                 show_seconds_tx,
-                locale,
+                locale: locale.clone(),
+                system_locale: locale,
+                world_map: None,
+                world_clocks: Vec::new(),
+                panel_format: None,
+                events_provider: Box::new(NoEvents),
+                events: Vec::new(),
             },
             Task::none(),
         )
@@ -297,17 +440,20 @@ impl cosmic::Application for Window {
         match message {
             Message::TogglePopup => {
                 if let Some(p) = self.popup.take() {
+                    self.sync_tick_rate();
                     destroy_popup(p)
                 } else {
-                    self.date_selected = chrono::NaiveDate::from(self.now.naive_local());
+                    let select_today = self.select_day(self.now.date_naive());
 
                     let new_id = window::Id::unique();
                     self.popup = Some(new_id);
+                    self.sync_tick_rate();
+                    self.update_world_map();
 
                     let mut popup_settings = self.core.applet.get_popup_settings(
                         self.core.main_window_id().unwrap(),
                         new_id,
-                        None,
+                        Some((1, 1)),
                         None,
                         None,
                     );
@@ -324,9 +470,13 @@ impl cosmic::Application for Window {
                         height: height.max(1.) as i32,
                     };
 
-                    popup_settings.positioner.size = Some((300, 500));
+                    popup_settings.positioner.size_limits = Limits::NONE
+                        .min_width(300.0)
+                        .max_width(300.0)
+                        .min_height(1.0)
+                        .max_height(1080.0);
 
-                    get_popup(popup_settings)
+                    Task::batch([get_popup(popup_settings), select_today])
                 }
             }
             Message::Tick => {
@@ -334,6 +484,7 @@ impl cosmic::Application for Window {
                     .timezone
                     .map(|tz| chrono::Local::now().with_timezone(&tz).fixed_offset())
                     .unwrap_or_else(|| chrono::Local::now().into());
+                self.update_world_map();
                 Task::none()
             }
             Message::Rectangle(u) => {
@@ -350,14 +501,16 @@ impl cosmic::Application for Window {
             Message::CloseRequested(id) => {
                 if Some(id) == self.popup {
                     self.popup = None;
+                    self.sync_tick_rate();
+                    self.update_world_map();
                 }
                 Task::none()
             }
-            Message::SelectDay(_day) => {
-                if let Some(date) = self.date_selected.with_day(_day) {
-                    self.date_selected = date;
-                } else {
-                    tracing::error!("invalid naivedate");
+            Message::DaySelected(date) => self.select_day(date),
+            Message::Events(date, events) => {
+                // Ignore lookups that finish after another day was selected
+                if date == self.date_selected {
+                    self.events = events;
                 }
                 Task::none()
             }
@@ -366,33 +519,44 @@ impl cosmic::Application for Window {
                     .date_selected
                     .checked_sub_months(chrono::Months::new(1))
                 {
-                    self.date_selected = date;
+                    self.select_day(date)
                 } else {
                     tracing::error!("invalid naivedate");
+                    Task::none()
                 }
-                Task::none()
             }
             Message::NextMonth => {
                 if let Some(date) = self
                     .date_selected
                     .checked_add_months(chrono::Months::new(1))
                 {
-                    self.date_selected = date;
+                    self.select_day(date)
                 } else {
                     tracing::error!("invalid naivedate");
+                    Task::none()
                 }
-                Task::none()
             }
             Message::OpenDateTimeSettings => {
-                let exec = "cosmic-settings time".to_string();
-                if let Some(tx) = self.token_tx.as_ref() {
-                    let _ = tx.send(TokenRequest {
-                        app_id: Self::APP_ID.to_string(),
-                        exec,
-                    });
-                } else {
-                    tracing::error!("Wayland tx is None");
+                self.request_token("cosmic-settings time".to_string());
+                Task::none()
+            }
+            Message::OpenCalendar => {
+                let Some(id) = self.config.calendar_app.clone() else {
+                    return self.update(Message::LaunchCalendar(None));
                 };
+                Task::perform(
+                    async move {
+                        let exec = desktop_exec(&id).await;
+                        if exec.is_none() {
+                            tracing::warn!("No Exec found for calendar app {id}");
+                        }
+                        exec
+                    },
+                    |exec| cosmic::app::Message::App(Message::LaunchCalendar(exec)),
+                )
+            }
+            Message::LaunchCalendar(exec) => {
+                self.request_token(exec.unwrap_or_else(|| "cosmic-settings time".to_string()));
                 Task::none()
             }
             Message::Token(u) => {
@@ -403,29 +567,26 @@ impl cosmic::Application for Window {
                     TokenUpdate::Finished => {
                         self.token_tx = None;
                     }
-                    TokenUpdate::ActivationToken { token, .. } => {
-                        let mut cmd = std::process::Command::new("cosmic-settings");
-                        cmd.arg("time");
+                    TokenUpdate::ActivationToken { token, exec, .. } => {
+                        let mut envs = Vec::new();
                         if let Some(token) = token {
-                            cmd.env("XDG_ACTIVATION_TOKEN", &token);
-                            cmd.env("DESKTOP_STARTUP_ID", &token);
+                            envs.push(("XDG_ACTIVATION_TOKEN".to_string(), token.clone()));
+                            envs.push(("DESKTOP_STARTUP_ID".to_string(), token));
                         }
-                        tokio::spawn(cosmic::process::spawn(cmd));
+                        tokio::spawn(async move {
+                            cosmic::desktop::spawn_desktop_exec(exec, envs, None).await
+                        });
                     }
                 }
                 Task::none()
             }
             Message::ConfigChanged(c) => {
-                // Don't interrupt the tick subscription unless necessary
-                self.show_seconds_tx.send_if_modified(|show_seconds| {
-                    if *show_seconds == c.show_seconds {
-                        false
-                    } else {
-                        *show_seconds = c.show_seconds;
-                        true
-                    }
-                });
                 self.config = c;
+                self.sync_tick_rate();
+                self.update_locale();
+                self.update_world_map();
+                self.update_world_clocks();
+                self.update_panel_format();
                 Task::none()
             }
             Message::TimezoneUpdate(timezone) => {
@@ -460,10 +621,7 @@ impl cosmic::Application for Window {
 
             bag.hour = Some(components::Numeric::Numeric);
             bag.minute = Some(components::Numeric::Numeric);
-            bag.second = self
-                .config
-                .show_seconds
-                .then_some(components::Numeric::Numeric);
+            bag.second = self.show_seconds().then_some(components::Numeric::Numeric);
 
             let hour_cycle = if self.config.military_time {
                 preferences::HourCycle::H23
@@ -473,9 +631,14 @@ impl cosmic::Application for Window {
 
             bag.preferences = Some(preferences::Bag::from_hour_cycle(hour_cycle));
 
+            let formatted = match &self.panel_format {
+                Some(format) => self.now.format(format).to_string(),
+                None => self.format(bag, &self.now),
+            };
+
             Element::from(
                 row!(
-                    self.core.applet.text(self.format(bag, &self.now)),
+                    self.core.applet.text(formatted),
                     container(vertical_space().height(Length::Fixed(
                         (self.core.applet.suggested_size(true).1
                             + 2 * self.core.applet.suggested_padding(true))
@@ -489,44 +652,50 @@ impl cosmic::Application for Window {
 
             let mut elements = Vec::new();
 
-            if self.config.show_date_in_top_panel {
-                let mut date_bag = Bag::empty();
+            if let Some(format) = &self.panel_format {
+                for p in self.now.format(format).to_string().split_whitespace() {
+                    elements.push(self.core.applet.text(p.to_owned()).into());
+                }
+            } else {
+                if self.config.show_date_in_top_panel {
+                    let mut date_bag = Bag::empty();
 
-                date_bag.day = Some(components::Day::NumericDayOfMonth);
-                date_bag.month = Some(components::Month::Short);
+                    date_bag.day = Some(components::Day::NumericDayOfMonth);
+                    date_bag.month = Some(components::Month::Short);
 
-                let formated = self.format(date_bag, &self.now);
+                    let formated = self.format(date_bag, &self.now);
 
-                for p in formated.split_whitespace() {
-                    elements.push(self.core.applet.text(p.to_owned()).into());
-                }
+                    for p in formated.split_whitespace() {
+                        elements.push(self.core.applet.text(p.to_owned()).into());
+                    }
 
-                elements.push(
-                    horizontal_rule(2)
-                        .width(self.core.applet.suggested_size(true).0)
-                        .into(),
-                )
-            }
+                    elements.push(
+                        horizontal_rule(2)
+                            .width(self.core.applet.suggested_size(true).0)
+                            .into(),
+                    )
+                }
 
-            let mut time_bag: Bag = Bag::empty();
+                let mut time_bag: Bag = Bag::empty();
 
-            time_bag.hour = Some(components::Numeric::Numeric);
-            time_bag.minute = Some(components::Numeric::Numeric);
+                time_bag.hour = Some(components::Numeric::Numeric);
+                time_bag.minute = Some(components::Numeric::Numeric);
 
-            let hour_cycle = if self.config.military_time {
-                preferences::HourCycle::H23
-            } else {
-                preferences::HourCycle::H12
-            };
+                let hour_cycle = if self.config.military_time {
+                    preferences::HourCycle::H23
+                } else {
+                    preferences::HourCycle::H12
+                };
 
-            time_bag.preferences = Some(preferences::Bag::from_hour_cycle(hour_cycle));
+                time_bag.preferences = Some(preferences::Bag::from_hour_cycle(hour_cycle));
 
-            let formated = self.format(time_bag, &self.now);
+                let formated = self.format(time_bag, &self.now);
 
-            // todo: split using formatToParts when it is implemented
-            // https://github.com/unicode-org/icu4x/issues/4936#issuecomment-2128812667
-            for p in formated.split_whitespace().flat_map(|s| s.split(':')) {
-                elements.push(self.core.applet.text(p.to_owned()).into());
+                // todo: split using formatToParts when it is implemented
+                // https://github.com/unicode-org/icu4x/issues/4936#issuecomment-2128812667
+                for p in formated.split_whitespace().flat_map(|s| s.split(':')) {
+                    elements.push(self.core.applet.text(p.to_owned()).into());
+                }
             }
 
             let date_time_col = Column::with_children(elements)
@@ -550,9 +719,20 @@ impl cosmic::Application for Window {
         } else {
             [self.core.applet.suggested_padding(true), 0]
         })
-        .on_press_down(Message::TogglePopup)
+        .on_press_down(if self.config.left_click_opens_calendar {
+            Message::OpenCalendar
+        } else {
+            Message::TogglePopup
+        })
         .class(cosmic::theme::Button::AppletIcon);
 
+        let button =
+            mouse_area(button).on_middle_release(if self.config.left_click_opens_calendar {
+                Message::TogglePopup
+            } else {
+                Message::OpenCalendar
+            });
+
         autosize::autosize(
             if let Some(tracker) = self.rectangle_tracker.as_ref() {
                 Element::from(tracker.container(0, button).ignore_bounds(true))
@@ -608,6 +788,9 @@ impl cosmic::Application for Window {
 
         let mut day_iter = first_day.iter_days();
 
+        if self.config.show_week_numbers {
+            calender = calender.push(Space::with_width(Length::Fixed(24.0)));
+        }
         for _ in 0..7 {
             calender = calender.push(
                 text(self.format(weekday_bag, &day_iter.next().unwrap()))
@@ -627,13 +810,98 @@ impl cosmic::Application for Window {
             }
 
             let date = day_iter.next().unwrap();
+            if i % 7 == 0 && self.config.show_week_numbers {
+                calender = calender.push(
+                    text(get_week_number(date).to_string())
+                        .size(12)
+                        .width(Length::Fixed(24.0))
+                        .align_x(Alignment::Center),
+                );
+            }
             let is_month = date.month() == self.date_selected.month()
                 && date.year_ce() == self.date_selected.year_ce();
-            let is_day = date.day() == self.date_selected.day() && is_month;
+            let is_selected = date == self.date_selected;
+            let is_today = date == self.now.date_naive();
 
-            calender = calender.push(date_button(date.day(), is_month, is_day));
+            calender = calender.push(date_button(date, is_month, is_selected, is_today));
         }
 
+        let world_clocks = (!self.world_clocks.is_empty()).then(|| {
+            let time_bag = self.time_bag();
+            let mut day_bag = time_bag;
+            day_bag.weekday = Some(components::Text::Short);
+
+            let clocks = self.world_clocks.iter().map(|tz| {
+                let now = self.now.with_timezone(tz);
+                // name the day when it's not the same as here
+                let bag = if now.date_naive() == self.now.date_naive() {
+                    time_bag
+                } else {
+                    day_bag
+                };
+                let city = tz.name().rsplit('/').next().unwrap_or_default();
+                row![
+                    text::body(city.replace('_', " ")),
+                    horizontal_space(),
+                    text::body(self.format_at(bag, &now, &now)),
+                ]
+                .into()
+            });
+            column![
+                padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+                Column::with_children(clocks)
+                    .spacing(space_xxs)
+                    .padding([space_xxs, 20]),
+            ]
+        });
+
+        let events = (!self.events.is_empty()).then(|| {
+            let time_bag = self.time_bag();
+            let events = self.events.iter().map(|event| {
+                let time = match event.time {
+                    Some((start, end)) => format!(
+                        "{} – {}",
+                        self.format_at(time_bag, &self.date_selected, &start),
+                        self.format_at(time_bag, &self.date_selected, &end),
+                    ),
+                    None => fl!("all-day"),
+                };
+                column![text::body(event.summary.clone()), text::caption(time)].into()
+            });
+            column![
+                padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+                Column::with_children(events)
+                    .spacing(space_xxs)
+                    .padding([space_xxs, 20]),
+            ]
+        });
+
+        let world_map = self.world_map.as_ref().map(|(_, handle)| {
+            let names = self
+                .config
+                .world_map_cities
+                .iter()
+                .map(|(name, ..)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(" · ");
+            let map = handle
+                .clone()
+                .icon()
+                .width(Length::Fixed(world_map::WIDTH as f32))
+                .height(Length::Fixed(world_map::HEIGHT as f32));
+            column![
+                padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+                container(
+                    column![map]
+                        .push_maybe((!names.is_empty()).then(|| text::caption(names)))
+                        .spacing(space_xxs)
+                        .align_x(Alignment::Center)
+                )
+                .center_x(Length::Fill)
+                .padding([space_xxs, 0]),
+            ]
+        });
+
         // content
         let content_list = column![
             row![
@@ -644,10 +912,15 @@ impl cosmic::Application for Window {
             .align_y(Alignment::Center)
             .padding([12, 20]),
             calender.padding([0, 12].into()),
-            padded_control(divider::horizontal::default()).padding([space_xxs, space_s]),
+        ]
+        .push_maybe(events)
+        .push_maybe(world_clocks)
+        .push_maybe(world_map)
+        .push(padded_control(divider::horizontal::default()).padding([space_xxs, space_s]))
+        .push(
             menu_button(text::body(fl!("datetime-settings")))
                 .on_press(Message::OpenDateTimeSettings),
-        ]
+        )
         .padding([8, 0]);
 
         self.core
@@ -662,21 +935,57 @@ impl cosmic::Application for Window {
     }
 }
 
-fn date_button(day: u32, is_month: bool, is_day: bool) -> Button<'static, Message> {
-    let style = if is_day {
+fn date_button(
+    date: chrono::NaiveDate,
+    is_month: bool,
+    is_selected: bool,
+    is_today: bool,
+) -> Button<'static, Message> {
+    let style = if is_selected {
         button::ButtonClass::Suggested
+    } else if is_today {
+        button::ButtonClass::Standard
     } else {
         button::ButtonClass::Text
     };
 
-    let button = button::custom(text::body(format!("{day}")).center())
+    let button = button::custom(text::body(format!("{}", date.day())).center())
         .class(style)
         .height(Length::Fixed(36.0))
         .width(Length::Fixed(36.0));
 
     if is_month {
-        button.on_press(Message::SelectDay(day))
+        button.on_press(Message::DaySelected(date))
     } else {
         button
     }
 }
+
+/// Parses a POSIX locale such as `en_GB.UTF-8`.
+fn parse_locale(locale: &str) -> Result<Locale, String> {
+    let name = locale
+        .split('.')
+        .next()
+        .ok_or(format!("Can't split the locale {locale}"))?;
+
+    Locale::from_str(name).map_err(|e| format!("{locale}: {e:?}"))
+}
+
+/// Looks up the `Exec` key of the desktop entry with the given id.
+async fn desktop_exec(id: &str) -> Option<String> {
+    let filename = format!("{}.desktop", id.trim_end_matches(".desktop"));
+    let paths: Vec<_> = freedesktop_desktop_entry::default_paths().collect();
+    for mut path in paths {
+        path.push(&filename);
+        let Ok(bytes) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        let Ok(entry) = DesktopEntry::from_str(&path, &bytes, None::<&[&str]>) else {
+            continue;
+        };
+        if let Some(exec) = entry.exec() {
+            return Some(exec.to_owned());
+        }
+    }
+    None
+}
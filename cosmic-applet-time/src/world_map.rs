@@ -0,0 +1,341 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::f64::consts::{PI, TAU};
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use once_cell::sync::Lazy;
+
+pub const WIDTH: u32 = 256;
+pub const HEIGHT: u32 = 128;
+
+/// Seconds between redraws; the terminator moves a degree every four minutes.
+pub const REDRAW_INTERVAL: i64 = 300;
+
+const DAY: [f64; 3] = [108., 164., 214.];
+const NIGHT: [f64; 3] = [22., 32., 58.];
+const LAND_DAY: [f64; 3] = [142., 184., 112.];
+const LAND_NIGHT: [f64; 3] = [38., 50., 44.];
+const COAST: [f64; 3] = [0., 0., 0.];
+const GRID: [f64; 3] = [255., 255., 255.];
+const SUN: [u8; 3] = [255, 214, 92];
+const CITY: [u8; 3] = [255, 120, 92];
+
+/// Sine of the sun's elevation at which twilight ends, about -6°.
+const TWILIGHT: f64 = 0.105;
+
+/// Draws an equirectangular map with the land masses outlined, the night side
+/// shaded, a graticule every 30°, the point where the sun is overhead and markers for the given cities
+/// as `(latitude, longitude)` in degrees. Returns RGBA pixels.
+pub fn render(now: DateTime<Utc>, cities: &[(i16, i16)]) -> Vec<u8> {
+    let (sun_lat, sun_lon) = subsolar_point(now);
+    let (sin_decl, cos_decl) = sun_lat.sin_cos();
+
+    let mut pixels = Vec::with_capacity((WIDTH * HEIGHT * 4) as usize);
+    for y in 0..HEIGHT {
+        let lat = PI / 2. - (f64::from(y) + 0.5) / f64::from(HEIGHT) * PI;
+        let (sin_lat, cos_lat) = lat.sin_cos();
+        let grid_row = is_grid_line(y, HEIGHT, 6);
+        for x in 0..WIDTH {
+            let lon = (f64::from(x) + 0.5) / f64::from(WIDTH) * TAU - PI;
+            let elevation = sin_lat * sin_decl + cos_lat * cos_decl * (lon - sun_lon).cos();
+            let daylight = ((elevation + TWILIGHT) / (2. * TWILIGHT)).clamp(0., 1.);
+
+            let mut color = match LAND_MASK[(y * WIDTH + x) as usize] {
+                Terrain::Water => blend(NIGHT, DAY, daylight),
+                Terrain::Land => blend(LAND_NIGHT, LAND_DAY, daylight),
+                Terrain::Coast => blend(blend(LAND_NIGHT, LAND_DAY, daylight), COAST, 0.35),
+            };
+            if grid_row || is_grid_line(x, WIDTH, 12) {
+                color = blend(color, GRID, 0.15);
+            }
+            pixels.extend(color.map(|c| c.round() as u8));
+            pixels.push(255);
+        }
+    }
+
+    mark(&mut pixels, sun_lat.to_degrees(), sun_lon.to_degrees(), SUN);
+    for &(lat, lon) in cities {
+        mark(&mut pixels, f64::from(lat), f64::from(lon), CITY);
+    }
+
+    pixels
+}
+
+/// Latitude and longitude in radians of the point where the sun is at the
+/// zenith, ignoring the equation of time.
+fn subsolar_point(now: DateTime<Utc>) -> (f64, f64) {
+    let day = f64::from(now.ordinal0()) + f64::from(now.num_seconds_from_midnight()) / 86400.;
+    let declination = -23.44f64.to_radians() * (TAU / 365.24 * (day + 10.)).cos();
+    let hours = f64::from(now.num_seconds_from_midnight()) / 3600.;
+    let longitude = (-15. * (hours - 12.)).to_radians();
+    (declination, longitude)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Terrain {
+    Water,
+    Land,
+    /// Land next to water.
+    Coast,
+}
+
+/// Terrain of every pixel, rasterized once from [`LAND`].
+static LAND_MASK: Lazy<Vec<Terrain>> = Lazy::new(|| {
+    let land = (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let lon = (f64::from(x) + 0.5) / f64::from(WIDTH) * 360. - 180.;
+            let lat = 90. - (f64::from(y) + 0.5) / f64::from(HEIGHT) * 180.;
+            // Even-odd over all outlines, so inland seas cut holes.
+            LAND.iter()
+                .filter(|outline| contains(outline, lon, lat))
+                .count()
+                % 2
+                == 1
+        })
+        .collect::<Vec<_>>();
+
+    let is_land = |x: i64, y: i64| {
+        // The map wraps around horizontally, the poles don't have neighbours.
+        let x = x.rem_euclid(i64::from(WIDTH));
+        y < 0 || y >= i64::from(HEIGHT) || land[(y * i64::from(WIDTH) + x) as usize]
+    };
+    (0..i64::from(HEIGHT))
+        .flat_map(|y| (0..i64::from(WIDTH)).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            if !is_land(x, y) {
+                Terrain::Water
+            } else if [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .all(|(dx, dy)| is_land(x + dx, y + dy))
+            {
+                Terrain::Land
+            } else {
+                Terrain::Coast
+            }
+        })
+        .collect()
+});
+
+/// Whether the point is inside the polygon, by casting a ray towards the east.
+fn contains(outline: &[(f32, f32)], lon: f64, lat: f64) -> bool {
+    let mut inside = false;
+    let mut previous = outline[outline.len() - 1];
+    for &point in outline {
+        let (x0, y0) = (f64::from(previous.0), f64::from(previous.1));
+        let (x1, y1) = (f64::from(point.0), f64::from(point.1));
+        if (y0 > lat) != (y1 > lat) && lon < x0 + (lat - y0) / (y1 - y0) * (x1 - x0) {
+            inside = !inside;
+        }
+        previous = point;
+    }
+    inside
+}
+
+fn is_grid_line(pos: u32, size: u32, divisions: u32) -> bool {
+    let cell = size / divisions;
+    pos % cell == 0 && pos != 0
+}
+
+fn blend(from: [f64; 3], to: [f64; 3], t: f64) -> [f64; 3] {
+    [0, 1, 2].map(|i| from[i] + (to[i] - from[i]) * t)
+}
+
+/// Draws a 3×3 dot centered on the given coordinates.
+fn mark(pixels: &mut [u8], lat: f64, lon: f64, color: [u8; 3]) {
+    let x = ((lon + 180.) / 360. * f64::from(WIDTH)).floor() as i64;
+    let y = ((90. - lat) / 180. * f64::from(HEIGHT)).floor() as i64;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let (px, py) = (x + dx, y + dy);
+            if px < 0 || py < 0 || px >= i64::from(WIDTH) || py >= i64::from(HEIGHT) {
+                continue;
+            }
+            let i = ((py * i64::from(WIDTH) + px) * 4) as usize;
+            pixels[i..i + 3].copy_from_slice(&color);
+        }
+    }
+}
+
+/// Coarse coastlines as `(longitude, latitude)` in degrees, good for a map a
+/// few hundred pixels wide. The Black and Caspian seas are listed as outlines
+/// of their own and cut out of Eurasia.
+#[rustfmt::skip]
+const LAND: &[&[(f32, f32)]] = &[
+    // North America
+    &[
+        (-77.3, 8.2), (-80., 7.4), (-83., 8.3), (-85.7, 10.), (-87.5, 13.), (-92., 14.5),
+        (-96., 15.7), (-101., 17.5), (-105.5, 20.5), (-105.7, 22.5), (-109., 26.), (-112.5, 29.),
+        (-114.7, 31.7), (-113., 29.), (-111., 25.), (-109.5, 23.), (-112., 24.5), (-114.5, 28.),
+        (-116.5, 31.5), (-117.2, 32.7), (-120.6, 34.5), (-122.5, 37.5), (-124.2, 40.4),
+        (-124., 46.), (-124.7, 48.4), (-127.5, 50.8), (-130., 54.5), (-135., 58.), (-140., 59.8),
+        (-146., 61.), (-151.5, 59.2), (-157., 57.5), (-164., 54.8), (-157.5, 58.7), (-162., 59.5),
+        (-165., 62.5), (-163., 64.5), (-166.5, 65.2), (-164., 66.5), (-166., 68.8), (-160., 70.7),
+        (-156.5, 71.3), (-150., 70.5), (-141., 69.6), (-128.5, 70.2), (-122., 69.5), (-114., 68.3),
+        (-106., 68.5), (-96., 67.5), (-90., 68.5), (-85., 69.5), (-81.5, 67.3), (-86., 64.5),
+        (-90., 63.5), (-94.5, 59.), (-92.5, 57.), (-87., 55.5), (-82., 52.8), (-79.5, 51.5),
+        (-79., 54.5), (-77., 58.), (-78., 62.3), (-74., 62.3), (-70., 61.), (-65., 60.3),
+        (-61., 56.), (-56., 52.), (-60., 50.2), (-66.5, 49.5), (-64.5, 48.7), (-64.5, 46.5),
+        (-61., 45.5), (-60., 46.), (-66., 43.5), (-70., 43.5), (-70., 41.8), (-74., 40.5),
+        (-76., 37.), (-75.5, 35.2), (-78., 33.8), (-81., 31.5), (-80.5, 28.), (-80., 25.5),
+        (-81.5, 25.2), (-82.7, 27.5), (-84., 30.), (-89., 30.3), (-90., 29.), (-94., 29.6),
+        (-97.2, 27.8), (-97.7, 22.), (-95.5, 18.6), (-91., 18.7), (-90.3, 21.), (-87., 21.5),
+        (-88.3, 16.), (-84., 15.8), (-83.5, 11.), (-81.5, 9.), (-79., 9.5),
+    ],
+    // Baffin Island
+    &[
+        (-82., 73.7), (-77., 72.7), (-70., 70.5), (-62., 66.5), (-65., 62.), (-72., 63.),
+        (-78., 64.5), (-73., 68.), (-85., 70.),
+    ],
+    // Ellesmere Island
+    &[(-90., 80.), (-80., 83.), (-62., 82.5), (-75., 79.), (-80., 77.)],
+    // Victoria Island
+    &[(-118., 73.), (-102., 73.), (-100., 69.), (-118., 69.)],
+    // Greenland
+    &[
+        (-73., 78.), (-60., 82.), (-30., 83.5), (-20., 81.), (-18., 77.), (-22., 72.),
+        (-22., 70.), (-32., 68.), (-40., 65.), (-43., 60.), (-48., 61.), (-52., 65.),
+        (-54., 69.), (-58., 75.), (-66., 77.),
+    ],
+    // Cuba
+    &[(-85., 21.8), (-82., 23.2), (-77., 22.3), (-74.2, 20.2), (-77.5, 19.8), (-80., 21.7)],
+    // South America
+    &[
+        (-77.3, 8.2), (-75.5, 10.5), (-72., 12.), (-66., 10.5), (-61., 10.5), (-57., 6.),
+        (-52., 5.), (-50., 0.), (-44., -2.5), (-35., -5.), (-35., -9.), (-39., -13.),
+        (-39., -18.), (-41., -22.), (-48., -26.), (-49., -29.), (-53., -34.), (-58., -34.5),
+        (-57., -38.), (-62., -39.), (-65., -41.), (-64., -43.), (-67.5, -46.), (-66., -48.),
+        (-69., -51.), (-68.5, -53.), (-71., -55.), (-74., -52.), (-75., -47.), (-73.5, -42.),
+        (-73., -37.), (-71.5, -30.), (-70.5, -23.), (-70., -18.5), (-76., -14.), (-79., -8.),
+        (-81., -5.), (-80., -1.), (-80., 1.), (-78.8, 2.), (-77.3, 4.), (-77.5, 7.),
+    ],
+    // Iceland
+    &[(-24., 65.5), (-22., 66.5), (-15., 66.5), (-13.5, 65.), (-18., 63.4), (-22., 63.8)],
+    // Great Britain
+    &[
+        (-5.7, 50.), (1.4, 51.2), (1.7, 52.7), (0., 53.5), (-1.5, 55.), (-2., 56.),
+        (-1.8, 57.6), (-3.3, 58.6), (-5., 58.6), (-6.2, 57.5), (-5.5, 56.), (-4.8, 54.8),
+        (-3., 54.), (-3., 53.4), (-4.5, 53.3), (-4.5, 52.), (-5.3, 51.7), (-3., 51.3),
+    ],
+    // Ireland
+    &[(-6., 52.), (-6., 54.), (-7.5, 55.3), (-10., 54.2), (-10., 51.6), (-8., 51.5)],
+    // Eurasia
+    &[
+        (-5.6, 36.), (-9., 37.), (-9.5, 39.), (-9., 43.), (-8., 43.7), (-1.5, 43.5), (-1.2, 46.),
+        (-4.5, 48.), (-1.5, 49.5), (2., 51.), (4.5, 52.8), (8.5, 53.5), (8.5, 57.), (10.5, 57.7),
+        (10.5, 54.5), (12., 54.3), (14., 54.), (18.5, 54.7), (21., 55.), (21., 57.), (24., 57.5),
+        (23.5, 59.2), (30., 60.), (27., 60.5), (22.5, 60.), (21.5, 61.), (21.5, 64.), (25., 65.7),
+        (21., 65.), (17., 61.), (18.5, 60.), (16.5, 57.), (14., 55.5), (12.5, 56.), (11., 58.5),
+        (8., 58.), (5.5, 59.), (5., 62.), (10., 64.), (14., 67.5), (19., 70.), (25., 71.),
+        (31., 70.), (41., 67.5), (44., 68.5), (53., 68.5), (60., 69.), (68., 69.), (68., 73.),
+        (80., 73.), (87., 75.), (100., 78.), (113., 73.5), (128., 73.), (140., 72.5),
+        (150., 71.5), (160., 70.), (170., 70.), (180., 69.), (180., 65.), (178., 62.5),
+        (173., 61.), (164., 60.), (163., 58.), (162., 56.), (156.5, 51.), (156., 57.5),
+        (160., 61.5), (155., 59.5), (143., 59.5), (137., 54.), (141., 53.), (140., 48.),
+        (135., 43.), (131., 42.5), (129.5, 41.), (129.5, 37.), (129., 35.), (126.5, 34.5),
+        (126., 37.5), (125., 39.5), (121.5, 39.), (121., 41.), (118., 39.), (122., 37.5),
+        (120., 35.), (121.5, 31.5), (122., 30.), (119.5, 25.5), (116.5, 23.), (113.5, 22.3),
+        (110., 21.), (108.5, 21.7), (106., 20.), (106.5, 18.), (109., 15.), (109., 11.5),
+        (105., 8.6), (104.8, 10.3), (103., 11.), (101., 12.7), (100., 13.5), (99.2, 10.),
+        (100.3, 6.5), (103.5, 1.3), (101., 3.), (98.3, 8.), (98.5, 12.), (97.5, 16.5),
+        (94.5, 16.), (94., 19.5), (92., 22.), (90., 22.), (87., 21.5), (85., 19.5), (80., 15.5),
+        (80., 10.), (77.5, 8.), (76., 10.), (73., 17.), (72.5, 21.), (69., 22.5), (67., 24.8),
+        (62., 25.), (57., 25.7), (56.3, 27.), (51., 28.), (48.5, 30.), (48., 29.3), (50.5, 26.5),
+        (51.5, 24.), (54., 24.2), (56.3, 26.3), (56.5, 24.5), (59.8, 22.5), (57.5, 19.),
+        (52., 16.), (45., 13.), (43.3, 12.8), (43., 15.), (40., 20.), (38.5, 22.), (35., 28.),
+        (34.5, 29.5), (34., 27.8), (32.5, 30.), (34.3, 31.3), (35., 33.), (36., 36.), (30., 36.3),
+        (27., 37.), (26.5, 40.2), (26., 40.7), (23., 40.5), (22.5, 37.), (21.5, 37.), (19.5, 40.),
+        (19.5, 42.), (16., 43.5), (13.7, 45.6), (12.3, 45.), (12.5, 44.), (14., 42.), (16., 41.5),
+        (18.5, 40.2), (17., 39.), (16.5, 38.), (15.7, 38.), (15.7, 40.), (12.5, 41.5),
+        (10.5, 43.), (8.7, 44.4), (6.5, 43.), (3.2, 43.2), (3., 42.), (0.8, 41.), (-0.3, 39.5),
+        (0., 38.7), (-2., 36.8),
+    ],
+    // Black Sea
+    &[
+        (28., 41.2), (28., 44.), (30., 46.), (33., 46.), (36., 45.3), (38., 47.), (39.5, 44.3),
+        (41.5, 41.5), (36., 41.7), (31., 41.1),
+    ],
+    // Caspian Sea
+    &[
+        (47., 44.5), (49., 46.5), (53., 47.), (53., 45.), (50.5, 44.3), (51., 42.), (53., 41.),
+        (54., 37.5), (49., 37.5), (49.5, 40.5), (47.5, 42.5),
+    ],
+    // Chukotka, east of the antimeridian
+    &[(-180., 68.8), (-175., 67.5), (-170., 66.), (-172., 64.5), (-180., 65.)],
+    // Novaya Zemlya
+    &[(52., 71.), (55., 74.), (62., 76.5), (69., 77.), (59., 74.), (56., 70.5)],
+    // Sakhalin
+    &[(142., 46.), (141.5, 53.), (143., 54.), (144., 49.)],
+    // Honshu, Kyushu and Shikoku
+    &[
+        (129.7, 33.3), (132.5, 35.4), (136., 35.6), (137., 37.), (139.5, 38.), (140., 40.5),
+        (141.5, 41.4), (142., 39.), (141., 36.), (140., 35.), (138.5, 34.6), (136., 33.5),
+        (133., 33.5), (131.5, 31.5), (130.2, 31.2),
+    ],
+    // Hokkaido
+    &[(140., 42.3), (140., 43.3), (141.8, 45.4), (145.5, 43.3), (143.5, 42.), (141., 41.8)],
+    // Taiwan
+    &[(120.1, 23.), (121., 25.2), (122., 25.), (120.8, 22.)],
+    // Luzon
+    &[(120., 18.5), (122.3, 18.4), (121.6, 15.7), (124., 13.), (120.6, 13.8), (120., 16.)],
+    // Mindanao
+    &[(122., 7.), (125.5, 9.8), (126.6, 7.2), (125.5, 5.6), (124., 6.5)],
+    // Sri Lanka
+    &[(80., 9.8), (81.9, 7.5), (80.6, 5.9), (79.8, 7.)],
+    // Sumatra
+    &[(95.3, 5.6), (98., 4.), (104., -1.), (106., -6.), (104.5, -5.8), (100., -1.), (97., 2.)],
+    // Java
+    &[(105.2, -6.8), (106., -6.), (111., -6.5), (114.5, -7.7), (111., -8.2), (106.5, -7.4)],
+    // Borneo
+    &[
+        (109., 1.5), (110.5, -3.), (114.5, -4.), (116.5, -2.), (118., 1.), (119., 5.),
+        (117., 7.), (115., 5.), (113., 3.2), (111., 1.8),
+    ],
+    // New Guinea
+    &[
+        (131., -1.), (135., -3.3), (138., -1.6), (141., -2.6), (145.5, -5.), (147.5, -6.5),
+        (150., -10.5), (147., -10.), (144., -7.7), (142.5, -9.3), (139., -8.), (137.6, -5.2),
+        (134., -4.), (132., -2.8),
+    ],
+    // Africa
+    &[
+        (-6., 36.), (10., 37.), (11., 33.5), (20., 30.5), (20., 32.), (25., 32.), (32., 31.3),
+        (32.5, 30.), (33.5, 27.), (35.5, 23.5), (37.3, 19.), (39., 15.5), (43., 12.5), (51., 12.),
+        (51., 10.), (49., 6.), (46., 2.), (41., -2.), (39.5, -5.), (40.5, -10.5), (40.5, -15.),
+        (35.5, -22.), (33., -26.), (32.5, -29.), (27.5, -33.5), (20., -35.), (18.5, -34.),
+        (18., -31.), (14.5, -23.), (11.8, -17.), (13.5, -12.), (12., -6.), (9., -1.), (9.5, 4.),
+        (6., 4.3), (2., 6.3), (-4., 5.2), (-7.5, 4.4), (-12., 7.5), (-15., 11.), (-17., 14.7),
+        (-16., 19.), (-17., 21.), (-13., 27.5), (-10., 29.5), (-9.5, 32.5),
+    ],
+    // Madagascar
+    &[
+        (49.3, -12.), (50.5, -15.5), (47.5, -25.), (45., -25.5), (43.5, -22.), (44.3, -17.),
+        (47., -15.5),
+    ],
+    // Australia
+    &[
+        (113.5, -22.), (114., -26.5), (115., -34.), (118., -35.), (123.5, -34.), (126., -32.2),
+        (131., -31.5), (134., -32.8), (135.8, -34.8), (138., -35.6), (140., -38.), (144., -38.3),
+        (146.3, -39.), (150., -37.5), (153.5, -28.), (153., -25.), (149., -20.5), (146., -18.),
+        (145.3, -15.), (143.5, -14.), (142.5, -10.7), (141.6, -12.8), (141.5, -17.),
+        (139.5, -17.5), (136., -15.), (137., -12.), (132.5, -11.5), (130., -13.), (129., -15.),
+        (126., -14.), (122., -17.5), (121., -19.5), (117., -20.6),
+    ],
+    // Tasmania
+    &[(144.6, -40.7), (148.3, -40.9), (148., -43.3), (146., -43.6), (145., -42.)],
+    // North Island
+    &[(172.7, -34.4), (178.5, -37.7), (176.8, -39.6), (175., -41.6), (174.5, -39.6)],
+    // South Island
+    &[
+        (172.7, -40.5), (174.3, -41.7), (172.7, -43.8), (171., -44.9), (169., -46.6),
+        (166.5, -46.), (168.3, -44.), (171.3, -41.8),
+    ],
+    // Antarctica
+    &[
+        (-180., -90.), (180., -90.), (180., -78.), (170., -71.5), (150., -68.5), (120., -66.5),
+        (90., -66.5), (60., -67.), (30., -69.5), (0., -70.), (-20., -73.), (-35., -78.),
+        (-60., -74.), (-61., -67.), (-57., -63.2), (-60., -63.), (-65., -66.), (-75., -72.5),
+        (-90., -73.), (-120., -74.), (-150., -77.), (-160., -78.5), (-180., -78.),
+    ],
+];
@@ -1 +1,5 @@
 cosmic-applet-workspaces = Cosmic Workspaces
+empty = Empty
+rename-workspace = Rename Workspace
+rename = Rename
+cancel = Cancel
@@ -4,16 +4,21 @@
 use cctk::sctk::reexports::{calloop::channel::SyncSender, client::backend::ObjectId};
 use cosmic::{
     applet::cosmic_panel_config::PanelAnchor,
+    cosmic_config::{self, CosmicConfigEntry},
     iced::{
         event,
         mouse::{self, ScrollDelta},
+        platform_specific::shell::commands::popup::{destroy_popup, get_popup},
         widget::{button, column, row},
-        Alignment,
+        window, Alignment,
         Event::Mouse,
         Length, Limits, Subscription,
     },
     iced_core::{Background, Border},
-    widget::{autosize, container, horizontal_space, vertical_space, Id},
+    widget::{
+        self, autosize, container, horizontal_space, mouse_area, text, text_input, tooltip,
+        vertical_space, Id,
+    },
     Element, Task, Theme,
 };
 
@@ -22,7 +27,8 @@ use once_cell::sync::Lazy;
 use std::cmp::Ordering;
 
 use crate::{
-    config,
+    config::{self, BubbleOrientation, WorkspacesAppletConfig},
+    fl,
     wayland::{WorkspaceEvent, WorkspaceList},
     wayland_subscription::{workspaces, WorkspacesUpdate},
 };
@@ -30,6 +36,7 @@ use crate::{
 use std::process::Command as ShellCommand;
 
 static AUTOSIZE_MAIN_ID: Lazy<Id> = Lazy::new(|| Id::new("autosize-main"));
+static RENAME_INPUT_ID: Lazy<Id> = Lazy::new(|| Id::new("rename-input"));
 
 pub fn run() -> cosmic::iced::Result {
     cosmic::applet::run::<IcedWorkspacesApplet>(())
@@ -45,10 +52,28 @@ struct IcedWorkspacesApplet {
     core: cosmic::app::Core,
     workspaces: WorkspaceList,
     workspace_tx: Option<SyncSender<WorkspaceEvent>>,
-    layout: Layout,
+    config: WorkspacesAppletConfig,
+    /// Popup renaming a workspace, with the name typed so far.
+    rename: Option<(window::Id, ObjectId, String)>,
 }
 
 impl IcedWorkspacesApplet {
+    fn layout(&self) -> Layout {
+        match self.config.bubble_orientation {
+            BubbleOrientation::Auto if self.core.applet.is_horizontal() => Layout::Row,
+            BubbleOrientation::Auto => Layout::Column,
+            BubbleOrientation::Horizontal => Layout::Row,
+            BubbleOrientation::Vertical => Layout::Column,
+        }
+    }
+
+    /// Length of a workspace button along the strip.
+    fn bubble_size(&self) -> u16 {
+        self.config.bubble_size().unwrap_or_else(|| {
+            self.core.applet.suggested_size(true).0 + self.core.applet.suggested_padding(true) * 2
+        })
+    }
+
     /// returns the index of the workspace button after which which must be moved to a popup
     /// if it exists.
     fn popup_index(&self) -> Option<usize> {
@@ -62,9 +87,7 @@ impl IcedWorkspacesApplet {
         }) else {
             return index;
         };
-        let button_total_size = self.core.applet.suggested_size(true).0
-            + self.core.applet.suggested_padding(true) * 2
-            + 4;
+        let button_total_size = self.bubble_size() + self.config.bubble_spacing();
         let btn_count = max_major_axis_len / button_total_size as u32;
         if btn_count >= self.workspaces.len() as u32 {
             index = None;
@@ -81,6 +104,13 @@ enum Message {
     WorkspacePressed(ObjectId),
     WheelScrolled(ScrollDelta),
     WorkspaceOverview,
+    ConfigChanged(WorkspacesAppletConfig),
+    /// Open the popup to rename the workspace at the given index.
+    StartRename(usize),
+    RenameInput(String),
+    Rename,
+    CloseRename,
+    Closed(window::Id),
 }
 
 impl cosmic::Application for IcedWorkspacesApplet {
@@ -98,13 +128,14 @@ impl cosmic::Application for IcedWorkspacesApplet {
     ) {
         (
             Self {
-                layout: match &core.applet.anchor {
-                    PanelAnchor::Left | PanelAnchor::Right => Layout::Column,
-                    PanelAnchor::Top | PanelAnchor::Bottom => Layout::Row,
-                },
                 core,
                 workspaces: Vec::new(),
                 workspace_tx: Default::default(),
+                config: cosmic_config::Config::new(config::APP_ID, WorkspacesAppletConfig::VERSION)
+                    .ok()
+                    .and_then(|c| WorkspacesAppletConfig::get_entry(&c).ok())
+                    .unwrap_or_default(),
+                rename: None,
             },
             Task::none(),
         )
@@ -159,6 +190,64 @@ impl cosmic::Application for IcedWorkspacesApplet {
             Message::WorkspaceOverview => {
                 let _ = ShellCommand::new("cosmic-workspaces").spawn();
             }
+            Message::ConfigChanged(config) => {
+                self.config = config;
+            }
+            Message::StartRename(index) => {
+                let Some(w) = self.workspaces.get(index) else {
+                    return Task::none();
+                };
+                let mut tasks = Vec::new();
+                if let Some((popup, ..)) = self.rename.take() {
+                    tasks.push(destroy_popup(popup));
+                }
+                let new_id = window::Id::unique();
+                self.rename = Some((new_id, w.2.clone(), w.0.clone()));
+
+                let mut popup_settings = self.core.applet.get_popup_settings(
+                    self.core.main_window_id().unwrap(),
+                    new_id,
+                    None,
+                    None,
+                    None,
+                );
+                let offset =
+                    (index as u16 * (self.bubble_size() + self.config.bubble_spacing())) as i32;
+                if self.layout() == Layout::Row {
+                    popup_settings.positioner.anchor_rect.x = offset;
+                } else {
+                    popup_settings.positioner.anchor_rect.y = offset;
+                }
+                tasks.push(get_popup(popup_settings));
+                tasks.push(text_input::focus(RENAME_INPUT_ID.clone()));
+                return Task::batch(tasks);
+            }
+            Message::RenameInput(name) => {
+                if let Some((_, _, input)) = self.rename.as_mut() {
+                    *input = name;
+                }
+            }
+            Message::Rename => {
+                if let Some((popup, id, name)) = self.rename.take() {
+                    let name = name.trim();
+                    if !name.is_empty() {
+                        if let Some(tx) = self.workspace_tx.as_mut() {
+                            let _ = tx.try_send(WorkspaceEvent::Rename(id, name.to_string()));
+                        }
+                    }
+                    return destroy_popup(popup);
+                }
+            }
+            Message::CloseRename => {
+                if let Some((popup, ..)) = self.rename.take() {
+                    return destroy_popup(popup);
+                }
+            }
+            Message::Closed(id) => {
+                if self.rename.as_ref().is_some_and(|(popup, ..)| *popup == id) {
+                    self.rename = None;
+                }
+            }
         }
         Task::none()
     }
@@ -167,26 +256,38 @@ impl cosmic::Application for IcedWorkspacesApplet {
         if self.workspaces.is_empty() {
             return row![].padding(8).into();
         }
-        let horizontal = matches!(
-            self.core.applet.anchor,
-            PanelAnchor::Top | PanelAnchor::Bottom
-        );
-        let suggested_total =
-            self.core.applet.suggested_size(true).0 + self.core.applet.suggested_padding(true) * 2;
+        let layout = self.layout();
+        let horizontal = layout == Layout::Row;
+        let suggested_total = self.bubble_size();
+        let spacing = self.config.bubble_spacing();
         let suggested_window_size = self.core.applet.suggested_window_size();
         let popup_index = self.popup_index().unwrap_or(self.workspaces.len());
 
-        let buttons = self.workspaces[..popup_index].iter().filter_map(|w| {
-            let content = self
-                .core
-                .applet
-                .text(w.0.clone())
-                .font(cosmic::font::bold());
+        let tooltip_position = match self.core.applet.anchor {
+            PanelAnchor::Left => tooltip::Position::Right,
+            PanelAnchor::Right => tooltip::Position::Left,
+            PanelAnchor::Top => tooltip::Position::Bottom,
+            PanelAnchor::Bottom => tooltip::Position::Top,
+        };
 
-            let (width, height) = if self.core.applet.is_horizontal() {
-                (suggested_total as f32, suggested_window_size.1.get() as f32)
+        let workspaces = self.workspaces[..popup_index].iter().enumerate();
+        let buttons = workspaces.filter_map(|(i, w)| {
+            let name = if w.0.is_empty() {
+                (i + 1).to_string()
             } else {
-                (suggested_window_size.0.get() as f32, suggested_total as f32)
+                w.0.clone()
+            };
+            let content = self.core.applet.text(name).font(cosmic::font::bold());
+
+            // buttons laid out across the panel are squares instead of filling its thickness
+            let (width, height) = match (layout, self.core.applet.is_horizontal()) {
+                (Layout::Row, true) => {
+                    (suggested_total as f32, suggested_window_size.1.get() as f32)
+                }
+                (Layout::Column, false) => {
+                    (suggested_window_size.0.get() as f32, suggested_total as f32)
+                }
+                _ => (suggested_total as f32, suggested_total as f32),
             };
 
             let content = row!(content, vertical_space().height(Length::Fixed(height)))
@@ -211,88 +312,95 @@ impl cosmic::Application for IcedWorkspacesApplet {
             })
             .padding(0);
 
-            Some(
-                btn.class(match w.1 {
-                    Some(zcosmic_workspace_handle_v1::State::Active) => {
-                        cosmic::theme::iced::Button::Primary
-                    }
-                    Some(zcosmic_workspace_handle_v1::State::Urgent) => {
-                        let appearance = |theme: &Theme| {
-                            let cosmic = theme.cosmic();
-                            button::Style {
+            let windows = if w.3.is_empty() {
+                fl!("empty")
+            } else {
+                w.3.join("\n")
+            };
+
+            let btn = btn.class(match w.1 {
+                Some(zcosmic_workspace_handle_v1::State::Active) => {
+                    cosmic::theme::iced::Button::Primary
+                }
+                Some(zcosmic_workspace_handle_v1::State::Urgent) => {
+                    let appearance = |theme: &Theme| {
+                        let cosmic = theme.cosmic();
+                        button::Style {
+                            background: Some(Background::Color(cosmic.palette.neutral_3.into())),
+                            border: Border {
+                                radius: cosmic.radius_xl().into(),
+                                ..Default::default()
+                            },
+                            border_radius: theme.cosmic().radius_xl().into(),
+                            text_color: theme.cosmic().destructive_button.base.into(),
+                            ..button::Style::default()
+                        }
+                    };
+                    cosmic::theme::iced::Button::Custom(Box::new(
+                        move |theme, status| match status {
+                            button::Status::Active => appearance(theme),
+                            button::Status::Hovered => button::Style {
                                 background: Some(Background::Color(
-                                    cosmic.palette.neutral_3.into(),
+                                    theme.current_container().component.hover.into(),
                                 )),
                                 border: Border {
-                                    radius: cosmic.radius_xl().into(),
+                                    radius: theme.cosmic().radius_xl().into(),
                                     ..Default::default()
                                 },
-                                border_radius: theme.cosmic().radius_xl().into(),
-                                text_color: theme.cosmic().destructive_button.base.into(),
-                                ..button::Style::default()
-                            }
-                        };
-                        cosmic::theme::iced::Button::Custom(Box::new(move |theme, status| {
-                            match status {
-                                button::Status::Active => appearance(theme),
-                                button::Status::Hovered => button::Style {
-                                    background: Some(Background::Color(
-                                        theme.current_container().component.hover.into(),
-                                    )),
-                                    border: Border {
-                                        radius: theme.cosmic().radius_xl().into(),
-                                        ..Default::default()
-                                    },
-                                    ..appearance(theme)
-                                },
-                                button::Status::Pressed => appearance(theme),
-                                button::Status::Disabled => appearance(theme),
-                            }
-                        }))
-                    }
-                    None => {
-                        let appearance = |theme: &Theme| {
-                            let cosmic = theme.cosmic();
-                            button::Style {
-                                background: None,
+                                ..appearance(theme)
+                            },
+                            button::Status::Pressed => appearance(theme),
+                            button::Status::Disabled => appearance(theme),
+                        },
+                    ))
+                }
+                None => {
+                    let appearance = |theme: &Theme| {
+                        let cosmic = theme.cosmic();
+                        button::Style {
+                            background: None,
+                            border: Border {
+                                radius: cosmic.radius_xl().into(),
+                                ..Default::default()
+                            },
+                            border_radius: cosmic.radius_xl().into(),
+                            text_color: theme.current_container().component.on.into(),
+                            ..button::Style::default()
+                        }
+                    };
+                    cosmic::theme::iced::Button::Custom(Box::new(
+                        move |theme, status| match status {
+                            button::Status::Active => appearance(theme),
+                            button::Status::Hovered => button::Style {
+                                background: Some(Background::Color(
+                                    theme.current_container().component.hover.into(),
+                                )),
                                 border: Border {
-                                    radius: cosmic.radius_xl().into(),
+                                    radius: theme.cosmic().radius_xl().into(),
                                     ..Default::default()
                                 },
-                                border_radius: cosmic.radius_xl().into(),
-                                text_color: theme.current_container().component.on.into(),
-                                ..button::Style::default()
-                            }
-                        };
-                        cosmic::theme::iced::Button::Custom(Box::new(move |theme, status| {
-                            match status {
-                                button::Status::Active => appearance(theme),
-                                button::Status::Hovered => button::Style {
-                                    background: Some(Background::Color(
-                                        theme.current_container().component.hover.into(),
-                                    )),
-                                    border: Border {
-                                        radius: theme.cosmic().radius_xl().into(),
-                                        ..Default::default()
-                                    },
-                                    ..appearance(theme)
-                                },
-                                button::Status::Pressed | button::Status::Disabled => {
-                                    appearance(theme)
-                                }
-                            }
-                        }))
-                    }
-                    _ => return None,
-                })
-                .into(),
+                                ..appearance(theme)
+                            },
+                            button::Status::Pressed | button::Status::Disabled => appearance(theme),
+                        },
+                    ))
+                }
+                _ => return None,
+            });
+
+            let btn = mouse_area(btn).on_right_press(Message::StartRename(i));
+
+            Some(
+                tooltip(btn, text(windows), tooltip_position)
+                    .snap_within_viewport(false)
+                    .into(),
             )
         });
         // TODO if there is a popup_index, create a button with a popup for the remaining workspaces
         // Should it appear on hover or on click?
-        let layout_section: Element<_> = match self.layout {
-            Layout::Row => row(buttons).spacing(4).into(),
-            Layout::Column => column(buttons).spacing(4).into(),
+        let layout_section: Element<_> = match layout {
+            Layout::Row => row(buttons).spacing(spacing).into(),
+            Layout::Column => column(buttons).spacing(spacing).into(),
         };
         let mut limits = Limits::NONE.min_width(1.).min_height(1.);
         if let Some(b) = self.core.applet.suggested_bounds {
@@ -312,6 +420,38 @@ impl cosmic::Application for IcedWorkspacesApplet {
         .into()
     }
 
+    fn view_window(&self, id: window::Id) -> Element<Message> {
+        let Some((_, _, name)) = self.rename.as_ref().filter(|(popup, ..)| *popup == id) else {
+            return text("").into();
+        };
+
+        let content = column![
+            text::heading(fl!("rename-workspace")),
+            text_input("", name)
+                .id(RENAME_INPUT_ID.clone())
+                .on_input(Message::RenameInput)
+                .on_submit(Message::Rename),
+            row![
+                horizontal_space(),
+                widget::button::standard(fl!("cancel")).on_press(Message::CloseRename),
+                widget::button::suggested(fl!("rename")).on_press(Message::Rename),
+            ]
+            .spacing(8),
+        ]
+        .spacing(8)
+        .padding(12);
+
+        self.core
+            .applet
+            .popup_container(content)
+            .max_width(300.)
+            .into()
+    }
+
+    fn on_close_requested(&self, id: window::Id) -> Option<Message> {
+        Some(Message::Closed(id))
+    }
+
     fn subscription(&self) -> Subscription<Message> {
         Subscription::batch(vec![
             workspaces().map(Message::WorkspaceUpdate),
@@ -319,6 +459,12 @@ impl cosmic::Application for IcedWorkspacesApplet {
                 Mouse(mouse::Event::WheelScrolled { delta }) => Some(Message::WheelScrolled(delta)),
                 _ => None,
             }),
+            self.core.watch_config(Self::APP_ID).map(|u| {
+                for err in u.errors {
+                    tracing::error!(?err, "Error watching config");
+                }
+                Message::ConfigChanged(u.config)
+            }),
         ])
     }
 
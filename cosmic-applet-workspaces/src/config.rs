@@ -1,4 +1,53 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
 pub const APP_ID: &str = "com.system76.CosmicWorkspacesApplet";
+
+/// Smallest bubble size, in pixels, that is still comfortable to click.
+const MIN_BUBBLE_SIZE: u16 = 16;
+/// Largest gap allowed between bubbles, in pixels.
+const MAX_BUBBLE_SPACING: u16 = 32;
+
+/// Direction in which the workspace buttons are laid out.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BubbleOrientation {
+    /// Follow the orientation of the panel.
+    #[default]
+    Auto,
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, CosmicConfigEntry, PartialEq, Eq)]
+#[version = 1]
+pub struct WorkspacesAppletConfig {
+    pub bubble_orientation: BubbleOrientation,
+    /// Length of a bubble along the strip, in pixels. The panel's suggested
+    /// size is used when unset.
+    pub bubble_size: Option<u16>,
+    /// Gap between bubbles, in pixels.
+    pub bubble_spacing: u16,
+}
+
+impl Default for WorkspacesAppletConfig {
+    fn default() -> Self {
+        Self {
+            bubble_orientation: BubbleOrientation::Auto,
+            bubble_size: None,
+            bubble_spacing: 4,
+        }
+    }
+}
+
+impl WorkspacesAppletConfig {
+    pub fn bubble_size(&self) -> Option<u16> {
+        self.bubble_size.map(|size| size.max(MIN_BUBBLE_SIZE))
+    }
+
+    pub fn bubble_spacing(&self) -> u16 {
+        self.bubble_spacing.min(MAX_BUBBLE_SPACING)
+    }
+}
@@ -13,9 +13,13 @@ use cctk::{
         },
         registry::{ProvidesRegistryState, RegistryState},
     },
+    toplevel_info::{ToplevelInfoHandler, ToplevelInfoState},
     workspace::{WorkspaceHandler, WorkspaceState},
 };
-use cosmic_protocols::workspace::v1::client::zcosmic_workspace_handle_v1;
+use cosmic_protocols::{
+    toplevel_info::v1::client::zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1,
+    workspace::v1::client::zcosmic_workspace_handle_v1,
+};
 use futures::{channel::mpsc, executor::block_on, SinkExt};
 use std::{
     os::{
@@ -35,8 +39,15 @@ use wayland_client::{
 pub enum WorkspaceEvent {
     Activate(ObjectId),
     Scroll(f64, bool),
+    Rename(ObjectId, String),
 }
-pub type WorkspaceList = Vec<(String, Option<zcosmic_workspace_handle_v1::State>, ObjectId)>;
+/// Name, state, id, and the titles of the windows on each workspace.
+pub type WorkspaceList = Vec<(
+    String,
+    Option<zcosmic_workspace_handle_v1::State>,
+    ObjectId,
+    Vec<String>,
+)>;
 
 pub fn spawn_workspaces(tx: mpsc::Sender<WorkspaceList>) -> SyncSender<WorkspaceEvent> {
     let (workspaces_tx, workspaces_rx) = calloop::channel::sync_channel(100);
@@ -76,6 +87,8 @@ pub fn spawn_workspaces(tx: mpsc::Sender<WorkspaceList>) -> SyncSender<Workspace
                 output_state: OutputState::new(&globals, &qhandle),
                 configured_output,
                 workspace_state: WorkspaceState::new(&registry_state, &qhandle),
+                toplevel_info_state: ToplevelInfoState::new(&registry_state, &qhandle),
+                sent: Vec::new(),
                 registry_state,
                 expected_output: None,
                 tx,
@@ -104,6 +117,22 @@ pub fn spawn_workspaces(tx: mpsc::Sender<WorkspaceList>) -> SyncSender<Workspace
                                 .commit();
                         }
                     }
+                    Event::Msg(WorkspaceEvent::Rename(id, name)) => {
+                        if let Some(w) = state
+                            .workspace_state
+                            .workspace_groups()
+                            .iter()
+                            .find_map(|g| g.workspaces.iter().find(|w| w.handle.id() == id))
+                        {
+                            w.handle.rename(name);
+                            state
+                                .workspace_state
+                                .workspace_manager()
+                                .get()
+                                .unwrap()
+                                .commit();
+                        }
+                    }
                     Event::Msg(WorkspaceEvent::Scroll(v, debounce)) => {
                         let dur = if debounce {
                             Duration::from_millis(350)
@@ -209,6 +238,9 @@ pub struct State {
     output_state: OutputState,
     registry_state: RegistryState,
     workspace_state: WorkspaceState,
+    toplevel_info_state: ToplevelInfoState,
+    /// Last list sent to the applet.
+    sent: WorkspaceList,
     have_workspaces: bool,
     scroll: f64,
     next_scroll: Option<Instant>,
@@ -216,9 +248,9 @@ pub struct State {
 }
 
 impl State {
-    pub fn workspace_list(
-        &self,
-    ) -> Vec<(String, Option<zcosmic_workspace_handle_v1::State>, ObjectId)> {
+    /// Workspaces of the configured output, leaving out the windows of
+    /// `closed`, which is still known while its closing is handled.
+    pub fn workspace_list(&self, closed: Option<&ZcosmicToplevelHandleV1>) -> WorkspaceList {
         self.workspace_state
             .workspace_groups()
             .iter()
@@ -252,6 +284,7 @@ impl State {
                                 _ => None,
                             },
                             w.handle.id(),
+                            self.window_titles(&w.handle, closed),
                         )
                     }))
                 } else {
@@ -263,6 +296,46 @@ impl State {
     }
 }
 
+impl State {
+    fn window_titles(
+        &self,
+        workspace: &zcosmic_workspace_handle_v1::ZcosmicWorkspaceHandleV1,
+        closed: Option<&ZcosmicToplevelHandleV1>,
+    ) -> Vec<String> {
+        let mut titles: Vec<String> = self
+            .toplevel_info_state
+            .toplevels()
+            .filter(|(handle, _)| Some(*handle) != closed)
+            .filter_map(|(_, info)| info)
+            .filter(|info| info.workspace.contains(workspace))
+            .map(|info| {
+                if info.title.is_empty() {
+                    info.app_id.clone()
+                } else {
+                    info.title.clone()
+                }
+            })
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    fn send_workspace_list(&mut self, list: WorkspaceList) {
+        let _ = block_on(self.tx.send(list.clone()));
+        self.sent = list;
+    }
+
+    /// Resends the list if the windows changed the titles shown for a workspace.
+    fn toplevels_changed(&mut self, closed: Option<&ZcosmicToplevelHandleV1>) {
+        if self.have_workspaces {
+            let list = self.workspace_list(closed);
+            if list != self.sent {
+                self.send_workspace_list(list);
+            }
+        }
+    }
+}
+
 impl ProvidesRegistryState for State {
     fn registry(&mut self) -> &mut RegistryState {
         &mut self.registry_state
@@ -285,7 +358,7 @@ impl OutputHandler for State {
         if info.name.as_deref() == Some(&self.configured_output) {
             self.expected_output = Some(output);
             if self.have_workspaces {
-                let _ = block_on(self.tx.send(self.workspace_list()));
+                self.send_workspace_list(self.workspace_list(None));
             }
         }
     }
@@ -314,10 +387,44 @@ impl WorkspaceHandler for State {
 
     fn done(&mut self) {
         self.have_workspaces = true;
-        let _ = block_on(self.tx.send(self.workspace_list()));
+        self.send_workspace_list(self.workspace_list(None));
+    }
+}
+
+impl ToplevelInfoHandler for State {
+    fn toplevel_info_state(&mut self) -> &mut ToplevelInfoState {
+        &mut self.toplevel_info_state
+    }
+
+    fn new_toplevel(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _toplevel: &ZcosmicToplevelHandleV1,
+    ) {
+        self.toplevels_changed(None);
+    }
+
+    fn update_toplevel(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _toplevel: &ZcosmicToplevelHandleV1,
+    ) {
+        self.toplevels_changed(None);
+    }
+
+    fn toplevel_closed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        toplevel: &ZcosmicToplevelHandleV1,
+    ) {
+        self.toplevels_changed(Some(toplevel));
     }
 }
 
+cctk::delegate_toplevel_info!(State);
 cctk::delegate_workspace!(State);
 sctk::delegate_output!(State);
 sctk::delegate_registry!(State);
@@ -6,8 +6,10 @@ use cosmic::{
     app,
     applet::{
         cosmic_panel_config::{PanelAnchor, PanelSize},
+        token::subscription::{activation_token_subscription, TokenRequest, TokenUpdate},
         Size,
     },
+    cctk::sctk::reexports::calloop,
     iced::{self, Length},
     iced_widget::row,
     widget::{autosize, vertical_space, Id},
@@ -33,12 +35,32 @@ struct Button {
     core: cosmic::app::Core,
     desktop: Desktop,
     config: IndividualConfig,
+    token_tx: Option<calloop::channel::Sender<TokenRequest>>,
+}
+
+impl Button {
+    fn spawn(&self, token: Option<String>) {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(&self.desktop.exec);
+        if let Some(token) = token {
+            cmd.env("XDG_ACTIVATION_TOKEN", &token);
+            cmd.env("DESKTOP_STARTUP_ID", &token);
+        }
+        if let Err(why) = cmd.spawn() {
+            tracing::error!(
+                why = why.to_string(),
+                "Failed to launch {}",
+                self.desktop.exec
+            );
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 enum Msg {
     Press,
     ConfigUpdated(CosmicPanelButtonConfig),
+    Token(TokenUpdate),
 }
 
 impl cosmic::Application for Button {
@@ -61,6 +83,7 @@ impl cosmic::Application for Button {
                 core,
                 desktop,
                 config,
+                token_tx: None,
             },
             Task::none(),
         )
@@ -81,12 +104,26 @@ impl cosmic::Application for Button {
     fn update(&mut self, message: Msg) -> app::Task<Msg> {
         match message {
             Msg::Press => {
-                let _ = Command::new("sh")
-                    .arg("-c")
-                    .arg(&self.desktop.exec)
-                    .spawn()
-                    .unwrap();
+                if let Some(tx) = self.token_tx.as_ref() {
+                    let _ = tx.send(TokenRequest {
+                        app_id: Self::APP_ID.to_string(),
+                        exec: self.desktop.exec.clone(),
+                    });
+                } else {
+                    self.spawn(None);
+                }
             }
+            Msg::Token(u) => match u {
+                TokenUpdate::Init(tx) => {
+                    self.token_tx = Some(tx);
+                }
+                TokenUpdate::Finished => {
+                    self.token_tx = None;
+                }
+                TokenUpdate::ActivationToken { token, .. } => {
+                    self.spawn(token);
+                }
+            },
             Msg::ConfigUpdated(conf) => {
                 self.config = conf
                     .configs
@@ -141,12 +178,15 @@ impl cosmic::Application for Button {
     }
 
     fn subscription(&self) -> iced::Subscription<Self::Message> {
-        self.core.watch_config(Self::APP_ID).map(|u| {
-            for why in u.errors {
-                tracing::error!(why = why.to_string(), "Error watching config");
-            }
-            Msg::ConfigUpdated(u.config)
-        })
+        iced::Subscription::batch([
+            self.core.watch_config(Self::APP_ID).map(|u| {
+                for why in u.errors {
+                    tracing::error!(why = why.to_string(), "Error watching config");
+                }
+                Msg::ConfigUpdated(u.config)
+            }),
+            activation_token_subscription(0).map(Msg::Token),
+        ])
     }
 }
 